@@ -1,13 +1,29 @@
-use std::rc::Rc;
+use std::{cell::{Cell, RefCell}, rc::Rc};
 
-use gtk::{prelude::{BoxExt, ButtonExt, EditableExt, EntryExt, GridExt, GtkWindowExt,  WidgetExt}, CssProvider};
+use gtk::{glib, prelude::{ActionMapExtManual, BoxExt, ButtonExt, Cast, FileExt, GtkApplicationExt, GtkWindowExt, WidgetExt}, CssProvider};
 
-use super::{ widgets, styles};
+use hadocrx::models::draft::Draft;
+
+use super::{ widgets, styles, printing, session::Session};
+
+/// How often open prescriptions are written to the draft store.
+const AUTOSAVE_INTERVAL_SECS: u32 = 30;
 
 pub struct AppState {
     pub widgets: AppWidgets,
     pub window: gtk::ApplicationWindow,
     pub dialog: gtk::AlertDialog,
+    /// Open prescriptions in the order of their tabs.
+    pub sessions: RefCell<Vec<Rc<Session>>>,
+    /// Printer and options chosen for the last print job, reused for the next one.
+    pub print_settings: RefCell<Option<gtk::PrintSettings>>,
+    /// Set once the user has been told that autosave failed, until it works again.
+    autosave_failed: Cell<bool>,
+    /// Set when the user chose to close the window although the tabs could not be saved.
+    close_confirmed: Cell<bool>,
+    /// Loaded once and shared by the search boxes of every tab.
+    generic_names: Rc<[String]>,
+    brand_names: Rc<[String]>,
 }
 
 impl AppState {
@@ -15,14 +31,25 @@ impl AppState {
         let widgets = AppWidgets::new();
         let window = Self::create_window(app);
         let dialog = gtk::AlertDialog::builder().build();     
-        Rc::new(Self { widgets, window, dialog })
+        Rc::new(Self {
+            widgets, window, dialog,
+            sessions: RefCell::new(Vec::new()),
+            print_settings: RefCell::new(None),
+            autosave_failed: Cell::new(false),
+            close_confirmed: Cell::new(false),
+            generic_names: hadocrx::ffi::db::get_generic_names().into(),
+            brand_names: hadocrx::ffi::db::get_brand_names().into(),
+        })
     }
 
     pub fn setup_ui(self: &Rc<Self>) {
         self.setup_styles();
         self.prepare_widgets();
         self.setup_layout();  
+        self.new_session();
         self.window.present();
+        self.offer_drafts();
+        self.setup_autosave();
     }
 
     fn setup_styles(&self) {
@@ -36,192 +63,605 @@ impl AppState {
         );
     }
 
-    fn setup_layout(&self) { 
+    fn setup_layout(&self) {
+        let header_bar = gtk::HeaderBar::new();
+        header_bar.pack_start(&self.widgets.btn_new_tab);
+        header_bar.pack_start(&self.widgets.btn_new_patient);
+        header_bar.pack_start(&self.widgets.btn_load_patient);
+        header_bar.pack_start(&self.widgets.btn_history);
+        header_bar.pack_start(&self.widgets.btn_save);
+        header_bar.pack_start(&self.widgets.template_popover.button);
+        header_bar.pack_end(&self.widgets.btn_print);
+        header_bar.pack_end(&self.widgets.btn_print_preview);
+        header_bar.pack_end(&self.widgets.btn_export_pdf);
+        header_bar.pack_end(&self.widgets.page_layout_popover.button);
         let root = hadocrx_macros::gtk_box! (
             @orientation gtk::Orientation::Vertical,
             @margin_start 0, @margin_end 0, @margin_top 0, @margin_bottom 0,
-            gtk::HeaderBar::new(),
-            gtk::ScrolledWindow::builder().child(&self.widgets.container).build()
+            header_bar,
+            self.widgets.notebook.clone()
         );
         self.window.set_child(Some(&root));
-        
-        let grid = hadocrx_macros::gtk_grid!(
-            @margin_top 16, @margin_bottom 16, @margin_start 16, @margin_end 16,
-            @column_spacing 16, @row_spacing 8,
-            @halign gtk::Align::Center,
-            @width_request 800, [ 
-                &Self::right_aligned_label("Brand Name"), 
-                &self.widgets.brand_name_search_box,
-                &Self::right_aligned_label("Manufacturer"),
-                &self.widgets.manufacturer_dropdown_box.entry,
-            ], [ 
-                &Self::right_aligned_label("Generic Name"), 
-                &self.widgets.generic_name_search_box,
-                &Self::right_aligned_label("Dosing"),
-                &self.widgets.dosing_box,
-                &self.widgets.btn_add
-            ], [
-                &Self::right_aligned_label("Strength"), 
-                &self.widgets.strength_dropdown_box.entry,
-                &Self::right_aligned_label("Instructions"),
-                &self.widgets.instructions_box
-            ], [
-                &Self::right_aligned_label("Formulation"), 
-                &self.widgets.formulation_dropdown_box.entry,
-                &Self::right_aligned_label("Duration"),
-                &self.widgets.duration_box
-            ]
-        );
-        
-        self.widgets.container.append(&grid);
-        self.widgets.container.append(&self.widgets.medicine_box.container);    
     }
 
-    fn right_aligned_label(label: &str) -> gtk::Label {
-        gtk::Label::builder().label(label).halign(gtk::Align::End).build()
+    fn prepare_widgets(self: &Rc<Self>) {
+        self.setup_tab_actions();
+        self.setup_patient_actions();
+        self.setup_prescription_actions();
+        self.setup_edit_actions();
+        self.setup_print_actions();
+        self.setup_templates();
+
+        let self_clone = self.clone();
+        self.widgets.page_layout_popover.connect_changed(move |layout| {
+            if let Err(err) = layout.save_default() {
+                self_clone.dialog.set_message("Unable to save the page setup!");
+                self_clone.dialog.set_detail(&err.to_string());
+                self_clone.dialog.show(Some(&self_clone.window));
+            }
+        });
     }
 
-    fn prepare_widgets(self: &Rc<Self>) {
-        let generic_names = hadocrx::ffi::db::get_generic_names();
-        let brand_names = hadocrx::ffi::db::get_brand_names();
-        self.widgets.generic_name_search_box.initialize(generic_names);
-        self.widgets.brand_name_search_box.initialize(brand_names);
-        self.widgets.strength_dropdown_box.initialize(Vec::new()); 
-        self.widgets.formulation_dropdown_box.initialize(Vec::new());
-        self.widgets.manufacturer_dropdown_box.initialize(Vec::new());
-        self.widgets.strength_dropdown_box.entry.set_secondary_icon_sensitive(false);
-        self.widgets.formulation_dropdown_box.entry.set_secondary_icon_sensitive(false);
-        self.widgets.manufacturer_dropdown_box.entry.set_secondary_icon_sensitive(false);
-        
+    /// `win.new-tab` (Ctrl+T) opens an empty prescription, `win.close-tab` (Ctrl+W) closes the current one.
+    fn setup_tab_actions(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        let action_new_tab = gtk::gio::ActionEntry::builder("new-tab")
+            .activate(move |_, _, _| { self_clone.new_session(); })
+            .build();
+        let self_clone = self.clone();
+        let action_close_tab = gtk::gio::ActionEntry::builder("close-tab")
+            .activate(move |_, _, _| {
+                if let Some(session) = self_clone.current_session() {
+                    self_clone.close_session(&session);
+                }
+            })
+            .build();
+        self.window.add_action_entries([action_new_tab, action_close_tab]);
+        if let Some(app) = self.window.application() {
+            app.set_accels_for_action("win.new-tab", &["<Control>t"]);
+            app.set_accels_for_action("win.close-tab", &["<Control>w"]);
+        }
+    }
+
+    /// Opens an empty prescription in a new tab and switches to it.
+    fn new_session(self: &Rc<Self>) -> Rc<Session> {
+        let session = Session::new(&self.window, self.generic_names.clone(), self.brand_names.clone());
+        let page = self.widgets.notebook.append_page(&session.widgets.page, Some(&session.widgets.tab));
+        self.widgets.notebook.set_tab_reorderable(&session.widgets.page, true);
+        let self_clone = self.clone();
+        // weak, the button belongs to the session and would otherwise keep a closed tab alive
+        session.widgets.btn_close.connect_clicked(glib::clone!(
+            #[weak] session,
+            move |_| self_clone.close_session(&session)
+        ));
+        self.sessions.borrow_mut().push(session.clone());
+        self.widgets.notebook.set_current_page(Some(page));
+        session.widgets.brand_name_search_box.grab_focus();
+        session
+    }
+
+    /// Closes the tab, asking first when it holds a prescription that has not been printed or saved.
+    /// The window always keeps at least one tab open.
+    fn close_session(self: &Rc<Self>, session: &Rc<Session>) {
+        if session.is_empty() {
+            self.remove_session(session);
+            return;
+        }
+        let confirm_dialog = gtk::AlertDialog::builder()
+            .message("Close this prescription?")
+            .detail(format!("The prescription for {} will be lost.", session.title()))
+            .buttons(["Cancel", "Close"])
+            .cancel_button(0)
+            .default_button(0)
+            .modal(true)
+            .build();
+        let self_clone = self.clone();
+        let session_clone = session.clone();
+        confirm_dialog.choose(Some(&self.window), gtk::gio::Cancellable::NONE, move |result| {
+            if result == Ok(1) {
+                self_clone.remove_session(&session_clone);
+            }
+        });
+    }
+
+    fn remove_session(self: &Rc<Self>, session: &Rc<Session>) {
+        if let Err(err) = session.discard_draft() {
+            self.show_alert("Unable to delete the autosaved prescription!", &err.to_string());
+        }
+        if let Some(page) = self.widgets.notebook.page_num(&session.widgets.page) {
+            self.widgets.notebook.remove_page(Some(page));
+        }
+        self.sessions.borrow_mut().retain(|open_session| !Rc::ptr_eq(open_session, session));
+        if self.sessions.borrow().is_empty() {
+            self.new_session();
+        }
+    }
+
+    /// Writes every open prescription to the draft store every `AUTOSAVE_INTERVAL_SECS`, so that a
+    /// crash or a power cut loses at most that much work. Closing the window writes them once more
+    /// and keeps the drafts, a draft is only deleted when its tab is saved or closed.
+    fn setup_autosave(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        gtk::glib::timeout_add_seconds_local(AUTOSAVE_INTERVAL_SECS, move || {
+            self_clone.autosave();
+            gtk::glib::ControlFlow::Continue
+        });
+        let self_clone = self.clone();
+        self.window.connect_close_request(move |_| self_clone.on_close_request());
+    }
+
+    /// Saves every tab as a draft before the window closes, the unsaved tabs are offered again on the
+    /// next launch. When that fails the window stays open until the user agrees to lose them.
+    fn on_close_request(self: &Rc<Self>) -> gtk::glib::Propagation {
+        if self.close_confirmed.get() {
+            return gtk::glib::Propagation::Proceed;
+        }
+        let sessions = self.sessions.borrow().clone();
+        let Err(err) = sessions.iter().try_for_each(|session| session.autosave()) else {
+            return gtk::glib::Propagation::Proceed;
+        };
+        let confirm_dialog = gtk::AlertDialog::builder()
+            .message("Unable to autosave the prescriptions!")
+            .detail(format!("{}\n\nPrescriptions that were not printed or saved will be lost if the window is closed.", err))
+            .buttons(["Cancel", "Close Anyway"])
+            .cancel_button(0)
+            .default_button(0)
+            .modal(true)
+            .build();
         let self_clone = self.clone();
-        // self.widgets.brand_name_search_box.entry.connect_activate(move |entry| {
-        self.widgets.brand_name_search_box.entry().connect_activate(move |entry| {
-            if entry.text().is_empty() { return; }
-            let brand_name = entry.text().to_string();
-            
-            // get generic_name from the db
-            // update the generic_name box
-            let generic_name = hadocrx::ffi::db::get_generic_name_by_brand_name(brand_name.clone());
-            self_clone.widgets.generic_name_search_box.update_entry_text(&generic_name);
-            self_clone.widgets.generic_name_search_box.entry().emit_activate();
-
-            // get the manufacturer for this brand_name
-            // get all available manufacturers for this generic_name
-            // update the manufacturer box
-            let manufacturer = hadocrx::ffi::db::get_manufacturer_by_brand_name(brand_name); 
-            
-            self_clone.widgets.manufacturer_dropdown_box.update_entry_text(manufacturer.clone()); 
-            // enable the manufacturer dropdown
-            if !self_clone.widgets.manufacturer_dropdown_box.entry.is_secondary_icon_sensitive() {
-                self_clone.widgets.manufacturer_dropdown_box.entry.set_secondary_icon_sensitive(true);
+        confirm_dialog.choose(Some(&self.window), gtk::gio::Cancellable::NONE, move |result| {
+            if result == Ok(1) {
+                self_clone.close_confirmed.set(true);
+                self_clone.window.close();
             }
-            
-            // get available strengths for this generic_name
-            let strengths = hadocrx::ffi::db::get_strengths_by_generic_name(generic_name);
-            let count_strengths = strengths.len();
-            self_clone.widgets.strength_dropdown_box.update(strengths);
-            // enable the strength dropdown
-            self_clone.widgets.strength_dropdown_box.entry.set_secondary_icon_sensitive(true);
-            // if only one strength is available, set it on the strength box
-            if count_strengths == 1 {
-                self_clone.widgets.strength_dropdown_box.entry.emit_activate();
-            } else {
-                self_clone.widgets.formulation_dropdown_box.update(Vec::new());
+        });
+        gtk::glib::Propagation::Stop
+    }
+
+    fn autosave(&self) {
+        let sessions = self.sessions.borrow().clone();
+        match sessions.iter().try_for_each(|session| session.autosave()) {
+            Ok(()) => self.autosave_failed.set(false),
+            Err(err) => {
+                if !self.autosave_failed.replace(true) {
+                    self.show_alert("Unable to autosave the prescriptions!", &err.to_string());
+                }
+            }
+        }
+    }
+
+    /// Offers to reopen the prescriptions left unsaved by the last run.
+    fn offer_drafts(self: &Rc<Self>) {
+        let drafts = match hadocrx::patient_db::patient_db().and_then(|db| db.list_drafts()) {
+            Ok(drafts) => drafts,
+            Err(err) => {
+                self.show_alert("Unable to load the autosaved prescriptions!", &err.to_string());
+                return;
+            }
+        };
+        if drafts.is_empty() {
+            return;
+        }
+        let dialog = widgets::restore_drafts_dialog::RestoreDraftsDialog::new(&self.window, drafts);
+        let self_clone = self.clone();
+        dialog.present(move |restore, discard| {
+            for draft in restore {
+                self_clone.restore_draft(draft);
+            }
+            let deleted = hadocrx::patient_db::patient_db()
+                .and_then(|db| discard.iter().try_for_each(|draft| db.delete_draft(draft.id)));
+            if let Err(err) = deleted {
+                self_clone.show_alert("Unable to delete the autosaved prescriptions!", &err.to_string());
             }
         });
+    }
+
+    /// Opens a draft in the current tab while it is untouched, otherwise in a new one.
+    fn restore_draft(self: &Rc<Self>, draft: Draft) {
+        let patient_id = draft.prescription.patient_id;
+        let patient = if patient_id == 0 {
+            Ok(None)
+        } else {
+            hadocrx::patient_db::patient_db().and_then(|db| db.get_patient(patient_id)).map(Some)
+        };
+        let patient = match patient {
+            Ok(patient) => patient,
+            Err(err) => {
+                self.show_alert("Unable to load the patient of an autosaved prescription!", &err.to_string());
+                None
+            }
+        };
+        let session = match self.current_session() {
+            Some(session) if session.is_empty() && !session.read_only.get() && session.patient.borrow().is_none() => session,
+            _ => self.new_session(),
+        };
+        session.restore_draft(draft, patient);
+    }
+
+    /// The current session, or a new tab when the current one shows a read-only prescription.
+    fn writable_session(self: &Rc<Self>) -> Option<Rc<Session>> {
+        let session = self.current_session()?;
+        if session.read_only.get() {
+            Some(self.new_session())
+        } else {
+            Some(session)
+        }
+    }
+
+    /// The session shown in the selected tab.
+    fn current_session(&self) -> Option<Rc<Session>> {
+        let page = self.widgets.notebook.nth_page(self.widgets.notebook.current_page())?;
+        self.sessions.borrow().iter()
+            .find(|session| session.widgets.page.upcast_ref::<gtk::Widget>() == &page)
+            .cloned()
+    }
 
+    /// `win.new-patient` (Ctrl+N) and `win.load-patient` (Ctrl+O) bind the current tab to a new or existing patient.
+    fn setup_patient_actions(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        let action_new_patient = gtk::gio::ActionEntry::builder("new-patient")
+            .activate(move |_, _, _| self_clone.new_patient())
+            .build();
         let self_clone = self.clone();
-        // self.widgets.generic_name_search_box.entry.connect_activate(move |entry| {
-        self.widgets.generic_name_search_box.entry().connect_activate(move |entry| {
-            if entry.text().is_empty() { return; }
-            let generic_name = entry.text().to_string();
-            let manufacturers = hadocrx::ffi::db::get_manufacturers_by_generic_name(generic_name.clone());
-            self_clone.widgets.manufacturer_dropdown_box.update(manufacturers);
+        let action_load_patient = gtk::gio::ActionEntry::builder("load-patient")
+            .activate(move |_, _, _| self_clone.load_patient())
+            .build();
+        self.window.add_action_entries([action_new_patient, action_load_patient]);
+        if let Some(app) = self.window.application() {
+            app.set_accels_for_action("win.new-patient", &["<Control>n"]);
+            app.set_accels_for_action("win.load-patient", &["<Control>o"]);
+        }
+    }
+
+    fn new_patient(self: &Rc<Self>) {
+        let Some(session) = self.writable_session() else { return; };
+        let dialog = widgets::new_patient_dialog::NewPatientDialog::new(&self.window);
+        dialog.present(move |patient| {
+            let patient = {
+                let db = hadocrx::patient_db::patient_db()?;
+                let id = db.create_patient(&patient)?;
+                db.get_patient(id)?
+            };
+            session.set_patient(Some(patient));
+            Ok(())
         });
+    }
+
+    fn load_patient(self: &Rc<Self>) {
+        let Some(session) = self.writable_session() else { return; };
+        let patients = match hadocrx::patient_db::patient_db().and_then(|db| db.list_patients_by_last_visit()) {
+            Ok(patients) => patients,
+            Err(err) => {
+                self.show_alert("Unable to load the patients!", &err.to_string());
+                return;
+            }
+        };
+        let dialog = widgets::load_patient_dialog::LoadPatientDialog::new(&self.window, patients);
+        dialog.present(move |patient| session.set_patient(Some(patient)));
+    }
 
+    /// `win.undo` (Ctrl+Z) and `win.redo` (Ctrl+Shift+Z) step through the changes made in the current tab.
+    fn setup_edit_actions(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        let action_undo = gtk::gio::ActionEntry::builder("undo")
+            .activate(move |_, _, _| {
+                if let Some(session) = self_clone.current_session() {
+                    session.undo();
+                }
+            })
+            .build();
+        let self_clone = self.clone();
+        let action_redo = gtk::gio::ActionEntry::builder("redo")
+            .activate(move |_, _, _| {
+                if let Some(session) = self_clone.current_session() {
+                    session.redo();
+                }
+            })
+            .build();
+        self.window.add_action_entries([action_undo, action_redo]);
+        if let Some(app) = self.window.application() {
+            app.set_accels_for_action("win.undo", &["<Control>z"]);
+            app.set_accels_for_action("win.redo", &["<Control><Shift>z"]);
+        }
+    }
+
+    /// `win.save` (Ctrl+S) stores the prescription in the current tab, `win.history` (Ctrl+H) lists
+    /// the past prescriptions of its patient.
+    fn setup_prescription_actions(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        let action_save = gtk::gio::ActionEntry::builder("save")
+            .activate(move |_, _, _| self_clone.save_prescription())
+            .build();
         let self_clone = self.clone();
-        self.widgets.strength_dropdown_box.entry.connect_activate(move |entry| {
-            if entry.text().is_empty() { return; }
-            let strength = entry.text().to_string();
-            let generic_name = self_clone.widgets.generic_name_search_box.entry().text().to_string();
-            let manufacturer = self_clone.widgets.manufacturer_dropdown_box.entry.text().to_string();
-            // get the correct brand_name for the generic_name, strength and manufacturer 
-            let brand_name = hadocrx::ffi::db::get_brand_name_by_generic_name_manufacturer_and_strength(
-                generic_name.clone(), manufacturer.clone(), strength.clone()
-            );
-            
-            if let Some(name) = brand_name {
-                self_clone.widgets.brand_name_search_box.update_entry_text(&name);
-                // get available formulations for the brand_name and strength
-                let formulations = hadocrx::ffi::db::get_formulations_by_brand_name_and_strength(name, strength);
-                let count_formulations = formulations.len();
-                self_clone.widgets.formulation_dropdown_box.update(formulations);
-                // enable the formulation box
-                self_clone.widgets.formulation_dropdown_box.entry.set_secondary_icon_sensitive(true);
-                // if only one formulation is available, set it on the formulation box
-                if count_formulations == 1 {
-                    self_clone.widgets.formulation_dropdown_box.entry.emit_activate();
+        let action_history = gtk::gio::ActionEntry::builder("history")
+            .activate(move |_, _, _| self_clone.show_history())
+            .build();
+        self.window.add_action_entries([action_save, action_history]);
+        if let Some(app) = self.window.application() {
+            app.set_accels_for_action("win.save", &["<Control>s"]);
+            app.set_accels_for_action("win.history", &["<Control>h"]);
+        }
+    }
+
+    /// Saves the prescription in the current tab, dated today when it is new. Saving again
+    /// overwrites the same record.
+    fn save_prescription(&self) {
+        let Some(session) = self.current_session() else { return; };
+        if session.read_only.get() {
+            self.show_alert("This prescription can't be changed!", "Use Repeat in the prescription history to write a new one from it.");
+            return;
+        }
+        let prescription = session.prescription();
+        if prescription.patient_id == 0 {
+            self.show_alert("No patient!", "Create or load a patient before saving the prescription.");
+            return;
+        }
+        if prescription.items.is_empty() {
+            self.show_alert("Nothing to save!", "Add at least one medicine to the prescription.");
+            return;
+        }
+        let saved = hadocrx::patient_db::patient_db().and_then(|db| {
+            let id = db.save_prescription(&prescription)?;
+            db.get_prescription(id)
+        });
+        match saved {
+            Ok(stored) => {
+                session.editor.borrow_mut().set_record(&stored);
+                if let Err(err) = session.mark_saved() {
+                    self.show_alert("Unable to delete the autosaved prescription!", &err.to_string());
                 }
-            } else {
-                self_clone.widgets.strength_dropdown_box.update_entry_text(String::new());
-                self_clone.widgets.formulation_dropdown_box.update_entry_text(String::new());
-                self_clone.dialog.set_message("Unavailable!");
-                self_clone.dialog.set_detail(&format!("{} - {} is not available from {}", generic_name, strength, manufacturer));
+            }
+            Err(err) => self.show_alert("Unable to save the prescription!", &err.to_string()),
+        }
+    }
+
+    /// Lists the past prescriptions of the patient in the current tab. One can be opened read-only
+    /// in a new tab, or repeated in a new tab as an unsaved prescription.
+    fn show_history(self: &Rc<Self>) {
+        let Some(session) = self.current_session() else { return; };
+        let Some(patient) = session.patient.borrow().clone() else {
+            self.show_alert("No patient!", "Load a patient to see their prescriptions.");
+            return;
+        };
+        let prescriptions = match hadocrx::patient_db::patient_db().and_then(|db| db.list_prescriptions_by_patient(patient.id)) {
+            Ok(prescriptions) => prescriptions,
+            Err(err) => {
+                self.show_alert("Unable to load the prescriptions!", &err.to_string());
+                return;
+            }
+        };
+        let dialog = widgets::prescription_history_dialog::PrescriptionHistoryDialog::new(&self.window, &patient.name, prescriptions);
+        let self_clone = self.clone();
+        let patient_clone = patient.clone();
+        let on_open = move |prescription| {
+            let session = self_clone.new_session();
+            session.set_patient(Some(patient_clone.clone()));
+            session.load_prescription(prescription, true);
+        };
+        let self_clone = self.clone();
+        let on_repeat = move |prescription: hadocrx::models::prescription::Prescription| {
+            // stored only when saved, dated that day, the follow-up date is not carried over
+            let copy = hadocrx::models::prescription::Prescription {
+                id: 0,
+                date: String::new(),
+                follow_up_date: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+                ..prescription
+            };
+            let session = self_clone.new_session();
+            session.load_prescription(copy, false);
+            // after the rows are in, so that lines unsafe for the patient today are flagged
+            session.set_patient(Some(patient.clone()));
+        };
+        dialog.present(on_open, on_repeat);
+    }
+
+    /// `win.print` (Ctrl+P), `win.print-preview` (Ctrl+Shift+P) and `win.export-pdf` (Ctrl+E),
+    /// triggered by the header bar buttons.
+    fn setup_print_actions(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        let action_print = gtk::gio::ActionEntry::builder("print")
+            .activate(move |_, _, _| self_clone.print(gtk::PrintOperationAction::PrintDialog))
+            .build();
+        let self_clone = self.clone();
+        let action_print_preview = gtk::gio::ActionEntry::builder("print-preview")
+            .activate(move |_, _, _| self_clone.print(gtk::PrintOperationAction::Preview))
+            .build();
+        let self_clone = self.clone();
+        let action_export_pdf = gtk::gio::ActionEntry::builder("export-pdf")
+            .activate(move |_, _, _| self_clone.export_pdf())
+            .build();
+        self.window.add_action_entries([action_print, action_print_preview, action_export_pdf]);
+        if let Some(app) = self.window.application() {
+            app.set_accels_for_action("win.print", &["<Control>p"]);
+            app.set_accels_for_action("win.print-preview", &["<Control><Shift>p"]);
+            app.set_accels_for_action("win.export-pdf", &["<Control>e"]);
+        }
+    }
+
+    /// The prescription in the current tab with its patient, letterhead and page setup.
+    /// Tells the user and returns `None` when there is nothing to print.
+    fn print_document(&self) -> Option<printing::PrintDocument> {
+        let session = self.current_session()?;
+        let mut prescription = session.prescription();
+        if prescription.items.is_empty() {
+            self.dialog.set_message("Nothing to print!");
+            self.dialog.set_detail("Add at least one medicine to the prescription.");
+            self.dialog.show(Some(&self.window));
+            return None;
+        }
+        let letterhead = match hadocrx::letterhead::Letterhead::load_default() {
+            Ok(letterhead) => letterhead,
+            Err(err) => {
+                self.dialog.set_message("Unable to load the letterhead!");
+                self.dialog.set_detail(&err.to_string());
+                self.dialog.show(Some(&self.window));
+                return None;
+            }
+        };
+        let patient = session.patient.borrow().clone().unwrap_or_default();
+        if prescription.date.is_empty() {
+            prescription.date = gtk::glib::DateTime::now_local()
+                .and_then(|now| now.format("%Y-%m-%d"))
+                .map(|date| date.to_string())
+                .unwrap_or_default();
+        }
+        Some(printing::PrintDocument {
+            prescription,
+            patient,
+            letterhead,
+            layout: self.widgets.page_layout_popover.layout(),
+        })
+    }
+
+    fn print(&self, action: gtk::PrintOperationAction) {
+        let Some(document) = self.print_document() else { return; };
+        let settings = self.print_settings.borrow().clone();
+        match printing::print(&self.window, document, action, settings.as_ref()) {
+            Ok(Some(settings)) => { self.print_settings.replace(Some(settings)); }
+            Ok(None) => {}
+            Err(err) => {
+                self.dialog.set_message("Unable to print!");
+                self.dialog.set_detail(&err.to_string());
+                self.dialog.show(Some(&self.window));
+            }
+        }
+    }
+
+    fn export_pdf(self: &Rc<Self>) {
+        let Some(document) = self.print_document() else { return; };
+        let initial_name = if document.patient.name.is_empty() {
+            "prescription.pdf".to_string()
+        } else {
+            format!("{} {}.pdf", document.patient.name, document.prescription.date)
+        };
+        let file_dialog = gtk::FileDialog::builder()
+            .title("Export PDF")
+            .initial_name(initial_name)
+            .modal(true)
+            .build();
+        let self_clone = self.clone();
+        file_dialog.save(Some(&self.window), gtk::gio::Cancellable::NONE, move |result| {
+            // an error here means the dialog was dismissed
+            let Some(path) = result.ok().and_then(|file| file.path()) else { return; };
+            let written = hadocrx::pdf::PdfFonts::discover()
+                .and_then(|fonts| hadocrx::pdf::render_prescription(
+                    &document.prescription, &document.patient, &document.letterhead,
+                    &hadocrx::pdf::PdfOptions { layout: document.layout, fonts },
+                ))
+                .and_then(|bytes| std::fs::write(&path, bytes).map_err(Into::into));
+            if let Err(err) = written {
+                self_clone.dialog.set_message("Unable to export the PDF!");
+                self_clone.dialog.set_detail(&err.to_string());
                 self_clone.dialog.show(Some(&self_clone.window));
             }
         });
+    }
+
+    fn setup_templates(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        self.widgets.template_popover.connect_apply(move |template| {
+            if let Some(session) = self_clone.writable_session() {
+                session.apply_template(template);
+            }
+        });
+        let self_clone = self.clone();
+        self.widgets.template_popover.connect_error(move |message, detail| self_clone.show_alert(message, &detail));
+        let self_clone = self.clone();
+        self.widgets.template_popover.btn_save.connect_clicked(move |_| self_clone.save_template());
+        let self_clone = self.clone();
+        self.widgets.template_popover.btn_import.connect_clicked(move |_| self_clone.import_templates());
+        let self_clone = self.clone();
+        self.widgets.template_popover.btn_export.connect_clicked(move |_| self_clone.export_templates());
+    }
 
+    /// Saves the medicine lines and advice under a name chosen by the user.
+    fn save_template(self: &Rc<Self>) {
+        let Some(session) = self.current_session() else { return; };
+        let prescription = session.prescription();
+        let items = prescription.items;
+        if items.is_empty() {
+            self.show_alert("Nothing to save!", "Add at least one medicine before saving a template.");
+            return;
+        }
+        let existing_names = match hadocrx::patient_db::patient_db().and_then(|db| db.list_templates()) {
+            Ok(templates) => templates.into_iter().map(|template| template.name).collect(),
+            Err(err) => {
+                self.show_alert("Unable to load the templates!", &err.to_string());
+                return;
+            }
+        };
+        let summary = items.iter().map(|item| item.brand_name.as_str()).collect::<Vec<&str>>().join(", ");
+        let dialog = widgets::save_template_dialog::SaveTemplateDialog::new(&self.window, &summary, existing_names);
         let self_clone = self.clone();
-        self.widgets.manufacturer_dropdown_box.entry.connect_activate(move |entry| {
-            if entry.text().is_empty() { return; }
-            let manufacturer = entry.text().to_string();
-            let generic_name = self_clone.widgets.generic_name_search_box.entry().text().to_string();
-            // get brand_name for this manufacturer and generic_name
-            let brand_name = hadocrx::ffi::db::get_brand_name_by_generic_name_and_manufacturer(generic_name, manufacturer);
-            self_clone.widgets.brand_name_search_box.update_entry_text(&brand_name);
+        dialog.present(move |name| {
+            let template = hadocrx::models::template::PrescriptionTemplate::new(&name, &prescription.advice, items.clone());
+            if let Err(err) = hadocrx::patient_db::patient_db().and_then(|db| db.save_template(&template)) {
+                self_clone.show_alert("Unable to save the template!", &err.to_string());
+            }
         });
+    }
 
+    fn export_templates(self: &Rc<Self>) {
+        let templates = match hadocrx::patient_db::patient_db().and_then(|db| db.list_templates()) {
+            Ok(templates) => templates,
+            Err(err) => {
+                self.show_alert("Unable to load the templates!", &err.to_string());
+                return;
+            }
+        };
+        if templates.is_empty() {
+            self.show_alert("Nothing to export!", "Save a template first.");
+            return;
+        }
+        let file_dialog = gtk::FileDialog::builder()
+            .title("Export Templates")
+            .initial_name("templates.json")
+            .modal(true)
+            .build();
         let self_clone = self.clone();
-        self.widgets.btn_add.connect_clicked(move |_| {
-            let brand_name = self_clone
-                .widgets.brand_name_search_box.entry().text()
-                .split_whitespace().collect::<Vec<&str>>().join(" ");
-            let generic_name = self_clone.widgets.generic_name_search_box.entry().text().to_string();
-            let strength = self_clone.widgets.strength_dropdown_box.entry.text().to_string();
-            let formulation = self_clone.widgets.formulation_dropdown_box.entry.text().to_string();
-            let manufacturer = self_clone.widgets.manufacturer_dropdown_box.entry.text().to_string();
-            // let dosing = self_clone.widgets.dosing_box.entry.text().to_string();
-            // let duration = self_clone.widgets.duration_box.entry.text().to_string();
-            // let instructions = self_clone.widgets.instructions_box.entry.text().to_string();
-            let dosing = self_clone.widgets.dosing_box.text().to_string();
-            let duration = self_clone.widgets.duration_box.text().to_string();
-            let instructions = self_clone.widgets.instructions_box.text().to_string();
-
-
-            let errors = widgets::utils::validation_errors!(brand_name, strength, formulation, dosing);
-            if let Some(message) = errors {
-                self_clone.dialog.set_message("Required fields are empty!");
-                self_clone.dialog.set_detail(&message);
-                self_clone.dialog.show(Some(&self_clone.window));
-            } else {
-                let medicine_data = hadocrx::ffi::prescription::MedicineData::new(brand_name, generic_name, strength, formulation, manufacturer, dosing, instructions, duration);
-                let medicine_row = widgets::medicine_row::MedicineRow::new(medicine_data);
-                self_clone.widgets.medicine_box.append(medicine_row);
-                
-                // self_clone.widgets.brand_name_search_box.entry.set_text("");
-                // self_clone.widgets.generic_name_search_box.entry.set_text("");
-                self_clone.widgets.strength_dropdown_box.update(Vec::new());
-                self_clone.widgets.formulation_dropdown_box.update(Vec::new());
-                self_clone.widgets.manufacturer_dropdown_box.entry.set_text("");
-
-                self_clone.widgets.dosing_box.clear();
-                self_clone.widgets.duration_box.clear();
-                self_clone.widgets.instructions_box.clear();
-                
-                // self_clone.widgets.brand_name_search_box.entry.grab_focus();
-                self_clone.widgets.brand_name_search_box.grab_focus();
+        file_dialog.save(Some(&self.window), gtk::gio::Cancellable::NONE, move |result| {
+            // an error here means the dialog was dismissed
+            let Some(path) = result.ok().and_then(|file| file.path()) else { return; };
+            let json = hadocrx::models::template::PrescriptionTemplate::export_json(&templates);
+            if let Err(err) = std::fs::write(&path, json) {
+                self_clone.show_alert("Unable to export the templates!", &err.to_string());
+            }
+        });
+    }
+
+    /// Reads templates shared by a colleague, replacing local templates with the same name.
+    fn import_templates(self: &Rc<Self>) {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Templates (*.json)"));
+        filter.add_pattern("*.json");
+        let filters = gtk::gio::ListStore::new::<gtk::FileFilter>();
+        filters.append(&filter);
+        let file_dialog = gtk::FileDialog::builder()
+            .title("Import Templates")
+            .filters(&filters)
+            .modal(true)
+            .build();
+        let self_clone = self.clone();
+        file_dialog.open(Some(&self.window), gtk::gio::Cancellable::NONE, move |result| {
+            // an error here means the dialog was dismissed
+            let Some(path) = result.ok().and_then(|file| file.path()) else { return; };
+            let imported = std::fs::read_to_string(&path)
+                .map_err(Into::into)
+                .and_then(|text| hadocrx::models::template::PrescriptionTemplate::import_json(&text))
+                .and_then(|templates| hadocrx::patient_db::patient_db()?.import_templates(&templates));
+            match imported {
+                Ok(count) => self_clone.show_alert("Templates imported", &format!("{} template(s) imported.", count)),
+                Err(err) => self_clone.show_alert("Unable to import the templates!", &err.to_string()),
             }
-        }); 
+        });
+    }
+
+    fn show_alert(&self, message: &str, detail: &str) {
+        self.dialog.set_message(message);
+        self.dialog.set_detail(detail);
+        self.dialog.show(Some(&self.window));
     }
 
     fn create_window(app: &gtk::Application) -> gtk::ApplicationWindow {
@@ -233,67 +673,76 @@ impl AppState {
 }
 
 pub struct AppWidgets {
-    pub container: gtk::Box,
-    pub brand_name_search_box: widgets::search_box::SearchBox,
-    pub generic_name_search_box: widgets::search_box::SearchBox,
-    pub strength_dropdown_box: Rc<widgets::dropdown_box::DropdownBox>,
-    pub formulation_dropdown_box: Rc<widgets::dropdown_box::DropdownBox>,
-    pub manufacturer_dropdown_box: Rc<widgets::dropdown_box::DropdownBox>,
-    pub dosing_box: widgets::avro_phonetic_entry::AvroPhoneticEntry,
-    pub instructions_box: widgets::avro_phonetic_entry::AvroPhoneticEntry,
-    pub duration_box: widgets::avro_phonetic_entry::AvroPhoneticEntry,
-    pub btn_add: gtk::Button,
-    pub medicine_box: Rc<widgets::medicine_box::MedicineBox>
+    pub notebook: gtk::Notebook,
+    pub btn_new_tab: gtk::Button,
+    pub btn_new_patient: gtk::Button,
+    pub btn_load_patient: gtk::Button,
+    pub btn_history: gtk::Button,
+    pub btn_save: gtk::Button,
+    pub btn_print: gtk::Button,
+    pub btn_print_preview: gtk::Button,
+    pub btn_export_pdf: gtk::Button,
+    pub page_layout_popover: Rc<widgets::page_layout_popover::PageLayoutPopover>,
+    pub template_popover: Rc<widgets::template_popover::TemplatePopover>,
 }
 
 impl AppWidgets {
     pub fn new() -> Self {
-        let container = gtk::Box::builder()
-            .orientation(gtk::Orientation::Vertical)
-            .margin_start(0).margin_end(0).margin_top(0).margin_bottom(0)
+        let notebook = gtk::Notebook::builder()
+            .scrollable(true)
             .hexpand(true)
             .vexpand(true)
             .build();
-        container.set_size_request(800, 600); 
-        
-        let brand_name_search_box = widgets::search_box::SearchBox::new();
-        brand_name_search_box.entry().set_placeholder_text(Some("Brand Name"));
-        brand_name_search_box.entry().set_size_request(250, -1);
-        
-        let generic_name_search_box = widgets::search_box::SearchBox::new();
-        generic_name_search_box.entry().set_placeholder_text(Some("Generic Name"));
-        
-        let strength_dropdown_box = widgets::dropdown_box::DropdownBox::new();
-        strength_dropdown_box.entry.set_placeholder_text(Some("Strength"));
-        
-        let formulation_dropdown_box = widgets::dropdown_box::DropdownBox::new();
-        formulation_dropdown_box.entry.set_placeholder_text(Some("Dosage Formulation"));
+        notebook.set_size_request(800, 600); 
 
-        let manufacturer_dropdown_box = widgets::dropdown_box::DropdownBox::new();
-        manufacturer_dropdown_box.entry.set_placeholder_text(Some("Manufacturer"));
-        manufacturer_dropdown_box.entry.set_size_request(250, -1);
-
-        let dosing_box = widgets::avro_phonetic_entry::AvroPhoneticEntry::new();
-        let instructions_box = widgets::avro_phonetic_entry::AvroPhoneticEntry::new();
-        let duration_box = widgets::avro_phonetic_entry::AvroPhoneticEntry::new();
-        
-        let btn_add = widgets::combo_button!(
-            gtk::Orientation::Horizontal,
-            widgets::label("Add Drug"),
-            gtk::Image::from_icon_name("list-add")
-        );
-        
-        let medicine_box = widgets::medicine_box::MedicineBox::new();
+        let btn_new_tab = gtk::Button::builder()
+            .icon_name("tab-new")
+            .tooltip_text("New prescription (Ctrl+T)")
+            .action_name("win.new-tab")
+            .build();
+        let btn_new_patient = gtk::Button::builder()
+            .icon_name("contact-new")
+            .tooltip_text("New patient (Ctrl+N)")
+            .action_name("win.new-patient")
+            .build();
+        let btn_load_patient = gtk::Button::builder()
+            .icon_name("system-users")
+            .tooltip_text("Load patient (Ctrl+O)")
+            .action_name("win.load-patient")
+            .build();
+        let btn_history = gtk::Button::builder()
+            .icon_name("document-open-recent")
+            .tooltip_text("Prescription history (Ctrl+H)")
+            .action_name("win.history")
+            .build();
+        let btn_save = gtk::Button::builder()
+            .icon_name("document-save")
+            .tooltip_text("Save prescription (Ctrl+S)")
+            .action_name("win.save")
+            .build();
+        let btn_print = gtk::Button::builder()
+            .icon_name("document-print")
+            .tooltip_text("Print (Ctrl+P)")
+            .action_name("win.print")
+            .build();
+        let btn_print_preview = gtk::Button::builder()
+            .icon_name("document-print-preview")
+            .tooltip_text("Print preview (Ctrl+Shift+P)")
+            .action_name("win.print-preview")
+            .build();
+        let btn_export_pdf = gtk::Button::builder()
+            .icon_name("document-save-as")
+            .tooltip_text("Export PDF (Ctrl+E)")
+            .action_name("win.export-pdf")
+            .build();
+        let page_layout = hadocrx::pdf::PageLayout::load_default().unwrap_or_default();
+        let page_layout_popover = widgets::page_layout_popover::PageLayoutPopover::new(&page_layout);
+        let template_popover = widgets::template_popover::TemplatePopover::new();
         
         Self { 
-            container,
-            brand_name_search_box, generic_name_search_box, 
-            manufacturer_dropdown_box,
-            strength_dropdown_box, formulation_dropdown_box,
-            dosing_box, instructions_box, duration_box,
-            btn_add, medicine_box
+            notebook, btn_new_tab, btn_new_patient, btn_load_patient, btn_history, btn_save,
+            btn_print, btn_print_preview, btn_export_pdf, page_layout_popover,
+            template_popover
         }
     }
-}
-
-
+}
\ No newline at end of file
@@ -6,6 +6,8 @@ mod widgets;
 mod styles;
 mod models;
 mod app;
+mod session;
+mod printing;
 
 const APP_ID: &str = "org.hadoc.rx";
 
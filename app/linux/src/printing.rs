@@ -0,0 +1,276 @@
+use std::{cell::RefCell, rc::Rc};
+
+use gtk::{glib, pango};
+use gtk::prelude::PrintOperationExt;
+
+use hadocrx::letterhead::Letterhead;
+use hadocrx::models::patient::Patient;
+use hadocrx::models::prescription::Prescription;
+use hadocrx::pdf::{
+    advice_lines, chamber_lines, clinical_notes, doctor_lines, format_date, medicine_lines, patient_fields, signature_lines,
+    PageLayout, PaperSize, TextRole,
+};
+
+const TITLE_FONT: &str = "Sans Bold 16";
+const HEADING_FONT: &str = "Sans Bold 11.5";
+const BODY_FONT: &str = "Sans 10.5";
+const BOLD_FONT: &str = "Sans Bold 10.5";
+const SMALL_FONT: &str = "Sans 9";
+const RX_FONT: &str = "Sans Bold 20";
+const SMALL_COLOR: &str = "#595959";
+const ITEM_INDENT: f64 = 18.0;
+const SIGNATURE_WIDTH: f64 = 156.0;
+
+/// Everything printed on a prescription.
+pub struct PrintDocument {
+    pub prescription: Prescription,
+    pub patient: Patient,
+    pub letterhead: Letterhead,
+    pub layout: PageLayout,
+}
+
+/// Prints `document` through `gtk::PrintOperation`, or previews it with
+/// `gtk::PrintOperationAction::Preview`. The paper size and margins come from the document's layout;
+/// returns the print settings to reuse for the next job when the user printed.
+pub fn print(
+    parent: &gtk::ApplicationWindow,
+    document: PrintDocument,
+    action: gtk::PrintOperationAction,
+    settings: Option<&gtk::PrintSettings>,
+) -> Result<Option<gtk::PrintSettings>, glib::Error> {
+    let operation = gtk::PrintOperation::builder()
+        .job_name(format!("Prescription - {}", document.patient.name))
+        .default_page_setup(&page_setup(&document.layout))
+        .unit(gtk::Unit::Points)
+        .embed_page_setup(true)
+        .build();
+    if let Some(settings) = settings {
+        operation.set_print_settings(Some(settings));
+    }
+
+    let pages: Rc<RefCell<Vec<Page>>> = Rc::new(RefCell::new(Vec::new()));
+    let pages_clone = pages.clone();
+    operation.connect_begin_print(move |operation, context| {
+        let laid_out = layout_pages(context, &document);
+        operation.set_n_pages(laid_out.len() as i32);
+        *pages_clone.borrow_mut() = laid_out;
+    });
+    let pages_clone = pages.clone();
+    operation.connect_draw_page(move |_, context, page_nr| {
+        if let Some(page) = pages_clone.borrow().get(page_nr as usize) {
+            draw_page(context, page);
+        }
+    });
+    let result = operation.run(action, Some(parent))?;
+    Ok((result == gtk::PrintOperationResult::Apply).then(|| operation.print_settings()).flatten())
+}
+
+fn page_setup(layout: &PageLayout) -> gtk::PageSetup {
+    let paper_name = match layout.paper {
+        PaperSize::A4 => "iso_a4",
+        PaperSize::A5 => "iso_a5",
+        PaperSize::Letter => "na_letter",
+    };
+    let page_setup = gtk::PageSetup::new();
+    page_setup.set_paper_size(&gtk::PaperSize::new(Some(paper_name)));
+    page_setup.set_top_margin(layout.margins.top as f64, gtk::Unit::Mm);
+    page_setup.set_bottom_margin(layout.margins.bottom as f64, gtk::Unit::Mm);
+    page_setup.set_left_margin(layout.margins.left as f64, gtk::Unit::Mm);
+    page_setup.set_right_margin(layout.margins.right as f64, gtk::Unit::Mm);
+    page_setup
+}
+
+enum Element {
+    Text { layout: pango::Layout, x: f64 },
+    Rule { x: f64, width: f64, thickness: f64 },
+}
+
+/// Elements with their distance from the top of the printable area.
+type Page = Vec<(f64, Element)>;
+
+/// Lays the document out top to bottom, starting a new page when the next block does not fit.
+struct Paginator<'a> {
+    context: &'a gtk::PrintContext,
+    width: f64,
+    height: f64,
+    pages: Vec<Page>,
+    y: f64,
+}
+
+impl<'a> Paginator<'a> {
+    fn new(context: &'a gtk::PrintContext) -> Self {
+        Self { context, width: context.width(), height: context.height(), pages: vec![Vec::new()], y: 0.0 }
+    }
+
+    fn text(&self, markup: &str, font: &str, width: f64, alignment: pango::Alignment) -> pango::Layout {
+        let layout = self.context.create_pango_layout();
+        layout.set_font_description(Some(&pango::FontDescription::from_string(font)));
+        layout.set_width((width * pango::SCALE as f64) as i32);
+        layout.set_wrap(pango::WrapMode::WordChar);
+        layout.set_alignment(alignment);
+        layout.set_markup(markup);
+        layout
+    }
+
+    fn ensure_space(&mut self, height: f64) {
+        if self.y > 0.0 && self.y + height > self.height {
+            self.pages.push(Vec::new());
+            self.y = 0.0;
+        }
+    }
+
+    fn place(&mut self, y: f64, element: Element) {
+        self.pages.last_mut().unwrap().push((y, element));
+    }
+
+    /// Wrapped text across the printable width, `indent` points from the left.
+    fn paragraph(&mut self, markup: &str, font: &str, indent: f64) {
+        let layout = self.text(markup, font, self.width - indent, pango::Alignment::Left);
+        let height = layout.pixel_size().1 as f64;
+        self.ensure_space(height);
+        self.place(self.y, Element::Text { layout, x: indent });
+        self.y += height;
+    }
+
+    /// Blocks printed side by side, each `x` points from the left, e.g. the doctor's details and the
+    /// chamber in the letterhead.
+    fn row(&mut self, blocks: Vec<(pango::Layout, f64)>) {
+        let height = blocks.iter().map(|(layout, _)| layout.pixel_size().1 as f64).fold(0.0, f64::max);
+        self.ensure_space(height);
+        for (layout, x) in blocks {
+            self.place(self.y, Element::Text { layout, x });
+        }
+        self.y += height;
+    }
+
+    fn rule(&mut self, thickness: f64) {
+        self.y += 4.0;
+        self.place(self.y, Element::Rule { x: 0.0, width: self.width, thickness });
+        self.y += 8.0;
+    }
+
+    fn space(&mut self, height: f64) {
+        self.y += height;
+    }
+
+    /// Signature line with the doctor's details, at the bottom right of the last page.
+    fn signature(&mut self, markup: &str) {
+        let width = SIGNATURE_WIDTH.min(self.width);
+        let layout = self.text(markup, BODY_FONT, width, pango::Alignment::Center);
+        let height = layout.pixel_size().1 as f64 + 36.0;
+        self.ensure_space(height);
+        let top = self.height - height + 32.0;
+        let x = self.width - width;
+        self.place(top, Element::Rule { x, width, thickness: 0.5 });
+        self.place(top + 4.0, Element::Text { layout, x });
+        self.y = self.height;
+    }
+}
+
+fn layout_pages(context: &gtk::PrintContext, document: &PrintDocument) -> Vec<Page> {
+    let PrintDocument { prescription, patient, letterhead, layout } = document;
+    let mut paginator = Paginator::new(context);
+    let full_width = paginator.width;
+
+    if layout.print_letterhead {
+        let doctor = doctor_lines(letterhead);
+        let chamber = chamber_lines(letterhead);
+        if !doctor.is_empty() || !chamber.is_empty() {
+            let left = paginator.text(&markup_lines(&doctor), BODY_FONT, full_width / 2.0, pango::Alignment::Left);
+            let right = paginator.text(&markup_lines(&chamber), BODY_FONT, full_width / 2.0, pango::Alignment::Right);
+            paginator.row(vec![(left, 0.0), (right, full_width / 2.0)]);
+            paginator.rule(1.0);
+        }
+    }
+
+    let name = field("Name", &patient.name);
+    let date = if prescription.date.is_empty() { String::new() } else { field("Date", &format_date(&prescription.date)) };
+    let left = paginator.text(&name, BODY_FONT, full_width, pango::Alignment::Left);
+    let right = paginator.text(&date, BODY_FONT, full_width, pango::Alignment::Right);
+    paginator.row(vec![(left, 0.0), (right, 0.0)]);
+    let fields = patient_fields(patient);
+    if !fields.is_empty() {
+        let fields = fields.iter().map(|(label, value)| field(label, value)).collect::<Vec<String>>().join("    ");
+        paginator.paragraph(&fields, BODY_FONT, 0.0);
+    }
+    if !patient.allergies.is_empty() {
+        paginator.paragraph(&field("Allergies", &patient.allergies.join(", ")), BODY_FONT, 0.0);
+    }
+    paginator.rule(0.5);
+
+    for (label, value) in clinical_notes(prescription) {
+        paginator.paragraph(&field(label, value), BODY_FONT, 0.0);
+    }
+
+    paginator.space(4.0);
+    paginator.paragraph("Rx", RX_FONT, 0.0);
+    for (index, item) in prescription.items.iter().enumerate() {
+        // Pango falls back to an installed Bangla font on its own
+        for (line, (text, role)) in medicine_lines(index, item, true).iter().enumerate() {
+            let indent = if line == 0 { ITEM_INDENT / 2.0 } else { ITEM_INDENT };
+            paginator.paragraph(&markup(text, *role), BODY_FONT, indent);
+        }
+        paginator.space(4.0);
+    }
+
+    let advice = advice_lines(prescription);
+    if !advice.is_empty() {
+        paginator.space(6.0);
+        paginator.paragraph("Advice", HEADING_FONT, 0.0);
+        for line in advice {
+            paginator.paragraph(&escape(line), BODY_FONT, ITEM_INDENT / 2.0);
+        }
+    }
+    if let Some(follow_up_date) = prescription.follow_up_date.as_deref().filter(|date| !date.trim().is_empty()) {
+        paginator.space(6.0);
+        paginator.paragraph(&field("Follow-up", &format_date(follow_up_date)), BODY_FONT, 0.0);
+    }
+
+    paginator.signature(&markup_lines(&signature_lines(letterhead)));
+    paginator.pages
+}
+
+fn draw_page(context: &gtk::PrintContext, page: &Page) {
+    let cr = context.cairo_context();
+    cr.set_source_rgb(0.0, 0.0, 0.0);
+    for (y, element) in page {
+        match element {
+            Element::Text { layout, x } => {
+                cr.move_to(*x, *y);
+                pangocairo::functions::show_layout(&cr, layout);
+            }
+            Element::Rule { x, width, thickness } => {
+                cr.set_line_width(*thickness);
+                cr.move_to(*x, *y);
+                cr.line_to(x + width, *y);
+                let _ = cr.stroke();
+            }
+        }
+    }
+}
+
+/// `"<b>Label:</b> value"`.
+fn field(label: &str, value: &str) -> String {
+    format!("<b>{}:</b> {}", escape(label), escape(value))
+}
+
+/// `text` set in the Pango font of `role`, the same roles the PDF export uses.
+fn markup(text: &str, role: TextRole) -> String {
+    let (font, color) = match role {
+        TextRole::Title => (TITLE_FONT, None),
+        TextRole::Heading => (HEADING_FONT, None),
+        TextRole::Body => (BODY_FONT, None),
+        TextRole::Bold => (BOLD_FONT, None),
+        TextRole::Small => (SMALL_FONT, Some(SMALL_COLOR)),
+    };
+    let color = color.map(|color| format!(" foreground=\"{}\"", color)).unwrap_or_default();
+    format!("<span font_desc=\"{}\"{}>{}</span>", font, color, escape(text))
+}
+
+fn markup_lines(lines: &[(String, TextRole)]) -> String {
+    lines.iter().map(|(text, role)| markup(text, *role)).collect::<Vec<String>>().join("\n")
+}
+
+fn escape(text: &str) -> String {
+    glib::markup_escape_text(text).to_string()
+}
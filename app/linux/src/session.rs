@@ -0,0 +1,838 @@
+use std::{cell::{Cell, RefCell}, rc::Rc};
+
+use gtk::glib;
+use gtk::prelude::{BoxExt, ButtonExt, Cast, EditableExt, EntryExt, GridExt, TextBufferExt, WidgetExt};
+
+use hadocrx::dose_limits::DoseWarning;
+use hadocrx::error::{DbError, DbResult};
+use hadocrx::models::draft::Draft;
+use hadocrx::models::patient::Patient;
+use hadocrx::models::prescription::Prescription;
+use hadocrx::models::prescription_editor::{PrescriptionEditor, TextField};
+use hadocrx::safety::{AllergyWarning, ContraindicationWarning};
+
+use widgets::medicine_box::RowAction;
+
+use super::widgets;
+
+/// Allergy and contraindication warnings for one medicine line.
+type SafetyWarnings = (Vec<AllergyWarning>, Vec<ContraindicationWarning>);
+
+/// One prescription being written, shown as a tab of the main window. Each session has its own entry
+/// form, medicine list, advice and patient, so several patients can be kept open during a busy chamber.
+pub struct Session {
+    pub widgets: SessionWidgets,
+    pub window: gtk::ApplicationWindow,
+    pub dialog: gtk::AlertDialog,
+    pub patient: RefCell<Option<Patient>>,
+    /// The prescription being written with its undo history. The medicine box and advice mirror it,
+    /// every change goes through the editor first.
+    pub editor: RefCell<PrescriptionEditor>,
+    /// Set for tabs showing a stored prescription that must not be changed.
+    pub read_only: Cell<bool>,
+    /// Set while the widgets are being updated from the editor, so that they don't record it as an edit.
+    syncing: Cell<bool>,
+    /// Row id of the autosaved draft of this tab, `0` while there is none.
+    draft_id: Cell<i64>,
+    /// The prescription as last autosaved, so that an unchanged tab is not written again.
+    autosaved: RefCell<Option<Prescription>>,
+    /// The prescription as last stored with Ctrl+S, a tab still matching it has nothing to lose.
+    saved: RefCell<Option<Prescription>>,
+}
+
+impl Session {
+    /// `generic_names` and `brand_names` feed the search boxes, they are loaded once and shared by every tab.
+    pub fn new(window: &gtk::ApplicationWindow, generic_names: Rc<[String]>, brand_names: Rc<[String]>) -> Rc<Self> {
+        let session = Rc::new(Self {
+            widgets: SessionWidgets::new(),
+            window: window.clone(),
+            dialog: gtk::AlertDialog::builder().build(),
+            patient: RefCell::new(None),
+            editor: RefCell::new(PrescriptionEditor::default()),
+            read_only: Cell::new(false),
+            syncing: Cell::new(false),
+            draft_id: Cell::new(0),
+            autosaved: RefCell::new(None),
+            saved: RefCell::new(None),
+        });
+        session.prepare_widgets(generic_names, brand_names);
+        session.setup_layout();
+        session.update_title();
+        session
+    }
+
+    /// Patient name shown on the tab, followed by the date for read-only prescriptions.
+    pub fn title(&self) -> String {
+        let name = self.patient.borrow().as_ref()
+            .map(|patient| patient.name.clone())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "New Prescription".to_string());
+        if self.read_only.get() {
+            format!("{} ({})", name, hadocrx::pdf::format_date(&self.editor.borrow().prescription().date))
+        } else {
+            name
+        }
+    }
+
+    /// Binds the tab to `patient`, warning about lines already written that are unsafe for them.
+    pub fn set_patient(&self, patient: Option<Patient>) {
+        {
+            // a saved prescription stays with the patient it was written for
+            let mut editor = self.editor.borrow_mut();
+            let patient_id = patient.as_ref().map(|patient| patient.id).unwrap_or_default();
+            let record = editor.prescription();
+            if record.id != 0 && record.patient_id != patient_id {
+                editor.set_record(&Prescription::default());
+            }
+        }
+        self.patient.replace(patient);
+        self.update_title();
+        self.update_patient_info();
+        self.update_dose_warning();
+
+        let patient = self.patient.borrow();
+        let Some(patient) = patient.as_ref() else { return; };
+        let messages = self.editor.borrow().prescription().items.iter()
+            .map(|item| {
+                let allergies = hadocrx::ffi::db::check_allergies(item, &patient.allergies)?;
+                let contraindications = hadocrx::ffi::db::check_contraindications(item, &patient.conditions)?;
+                Ok(allergies.iter().map(|warning| warning.message())
+                    .chain(contraindications.iter().map(|warning| warning.message()))
+                    .collect::<Vec<String>>())
+            })
+            .collect::<DbResult<Vec<Vec<String>>>>();
+        match messages {
+            Ok(messages) => {
+                let detail = messages.concat().join("\n");
+                if !detail.is_empty() {
+                    self.show_alert("Check the medicines already added!", &detail);
+                }
+            }
+            Err(err) => self.show_alert("Unable to check the medicines already added!", &err.to_string()),
+        }
+    }
+
+    /// `true` while nothing has been written that closing the tab would lose.
+    pub fn is_empty(&self) -> bool {
+        if self.read_only.get() {
+            return true;
+        }
+        let prescription = self.prescription();
+        (prescription.items.is_empty() && prescription.advice.is_empty())
+            || self.saved.borrow().as_ref() == Some(&prescription)
+    }
+
+    /// Reverts the last change to the medicine lines or advice.
+    pub fn undo(self: &Rc<Self>) {
+        if self.read_only.get() { return; }
+        let undone = self.editor.borrow_mut().undo().is_some();
+        if undone {
+            self.sync_widgets();
+        }
+    }
+
+    /// Applies the last undone change again.
+    pub fn redo(self: &Rc<Self>) {
+        if self.read_only.get() { return; }
+        let redone = self.editor.borrow_mut().redo().is_some();
+        if redone {
+            self.sync_widgets();
+        }
+    }
+
+    /// Updates the medicine rows and advice to match the editor.
+    fn sync_widgets(self: &Rc<Self>) {
+        let (items, advice) = {
+            let editor = self.editor.borrow();
+            (editor.prescription().items.clone(), editor.prescription().advice.clone())
+        };
+        self.widgets.medicine_box.set_items(&items);
+        if self.widgets.advice_box.text() != advice {
+            self.syncing.set(true);
+            self.widgets.advice_box.set_text(&advice);
+            self.syncing.set(false);
+        }
+    }
+
+    fn on_row_action(self: &Rc<Self>, id: u64, action: RowAction) {
+        let mut edited_dose = None;
+        let changed = {
+            let mut editor = self.editor.borrow_mut();
+            let Some(index) = editor.position(id) else { return; };
+            match action {
+                RowAction::Remove => editor.remove_item(id),
+                RowAction::MoveUp => index > 0 && editor.move_item(id, index - 1),
+                RowAction::MoveDown => editor.move_item(id, index + 1),
+                RowAction::MoveTo(to) => editor.move_item(id, to),
+                RowAction::Replace(medicine_data) => {
+                    if editor.prescription().items[index].dosing != medicine_data.dosing {
+                        edited_dose = Some(medicine_data.clone());
+                    }
+                    editor.replace_item(id, medicine_data)
+                }
+            }
+        };
+        if changed {
+            self.sync_widgets();
+        }
+        if let Some(medicine_data) = edited_dose {
+            self.warn_about_dose(&medicine_data);
+        }
+    }
+
+    /// Fills the tab with a stored prescription, rebuilding its medicine rows. Read-only tabs keep the
+    /// entry form, rows and advice locked.
+    pub fn load_prescription(self: &Rc<Self>, prescription: Prescription, read_only: bool) {
+        self.editor.replace(PrescriptionEditor::new(prescription));
+        self.saved.replace(None);
+        self.sync_widgets();
+        self.read_only.set(read_only);
+        for widget in [
+            self.widgets.brand_name_search_box.upcast_ref::<gtk::Widget>(),
+            self.widgets.generic_name_search_box.upcast_ref(),
+            self.widgets.strength_dropdown_box.entry.upcast_ref(),
+            self.widgets.formulation_dropdown_box.entry.upcast_ref(),
+            self.widgets.manufacturer_dropdown_box.entry.upcast_ref(),
+            self.widgets.dosing_box.upcast_ref(),
+            self.widgets.instructions_box.upcast_ref(),
+            self.widgets.duration_box.upcast_ref(),
+            self.widgets.btn_dose_calculator.upcast_ref(),
+            self.widgets.btn_add.upcast_ref(),
+        ] {
+            widget.set_sensitive(!read_only);
+        }
+        gtk::prelude::TextViewExt::set_editable(&self.widgets.advice_box, !read_only);
+        self.widgets.medicine_box.set_editable(!read_only);
+        self.update_title();
+    }
+
+    /// Writes the prescription to the draft store when it changed since the last autosave. A tab
+    /// with nothing to lose drops its draft instead.
+    pub fn autosave(&self) -> DbResult<()> {
+        if self.is_empty() {
+            return self.discard_draft();
+        }
+        let prescription = self.prescription();
+        if self.autosaved.borrow().as_ref() == Some(&prescription) {
+            return Ok(());
+        }
+        let draft = Draft::new(self.draft_id.get(), prescription.clone());
+        let id = hadocrx::patient_db::patient_db()?.save_draft(&draft)?;
+        self.draft_id.set(id);
+        self.autosaved.replace(Some(prescription));
+        Ok(())
+    }
+
+    /// Records the prescription as stored and deletes its draft. Autosave resumes once it is changed again.
+    pub fn mark_saved(&self) -> DbResult<()> {
+        self.saved.replace(Some(self.prescription()));
+        self.discard_draft()
+    }
+
+    /// Deletes the autosaved draft of this tab, e.g. when it is closed.
+    pub fn discard_draft(&self) -> DbResult<()> {
+        let id = self.draft_id.replace(0);
+        self.autosaved.replace(None);
+        if id == 0 {
+            return Ok(());
+        }
+        hadocrx::patient_db::patient_db()?.delete_draft(id)
+    }
+
+    /// Fills the tab with a draft left by a previous run. The draft is kept and overwritten by the
+    /// next autosave of this tab.
+    pub fn restore_draft(self: &Rc<Self>, draft: Draft, patient: Option<Patient>) {
+        self.load_prescription(draft.prescription, false);
+        // after the rows are in, so that lines unsafe for the patient are flagged
+        self.set_patient(patient);
+        self.draft_id.set(draft.id);
+    }
+
+    /// The prescription as written so far. New prescriptions are undated until they are saved.
+    pub fn prescription(&self) -> Prescription {
+        let patient_id = self.patient.borrow().as_ref().map(|patient| patient.id).unwrap_or_default();
+        let mut prescription = Prescription { patient_id, ..self.editor.borrow().prescription().clone() };
+        prescription.advice = prescription.advice.trim().to_string();
+        prescription
+    }
+
+    fn update_patient_info(&self) {
+        let patient = self.patient.borrow();
+        let Some(patient) = patient.as_ref() else {
+            self.widgets.patient_info.set_visible(false);
+            return;
+        };
+        let mut markup = format!("<b>{}</b>", gtk::glib::markup_escape_text(&patient.name));
+        for (_, value) in hadocrx::pdf::patient_fields(patient) {
+            markup.push_str(&format!(" · {}", gtk::glib::markup_escape_text(&value)));
+        }
+        for (heading, values) in [("Allergies", &patient.allergies), ("Conditions", &patient.conditions)] {
+            if values.is_empty() { continue; }
+            markup.push_str(&format!(" · {}: {}", heading, gtk::glib::markup_escape_text(&values.join(", "))));
+        }
+        self.widgets.patient_info.set_markup(&markup);
+        self.widgets.patient_info.set_visible(true);
+    }
+
+    fn update_title(&self) {
+        let title = self.title();
+        self.widgets.tab_label.set_label(&title);
+        self.widgets.tab_label.set_tooltip_text(Some(&title));
+    }
+
+    fn setup_layout(&self) {
+        let grid = hadocrx_macros::gtk_grid!(
+            @margin_top 16, @margin_bottom 16, @margin_start 16, @margin_end 16,
+            @column_spacing 16, @row_spacing 8,
+            @halign gtk::Align::Center,
+            @width_request 800, [ 
+                &Self::right_aligned_label("Brand Name"), 
+                &self.widgets.brand_name_search_box,
+                &Self::right_aligned_label("Manufacturer"),
+                &self.widgets.manufacturer_dropdown_box.entry,
+            ], [ 
+                &Self::right_aligned_label("Generic Name"), 
+                &self.widgets.generic_name_search_box,
+                &Self::right_aligned_label("Dosing"),
+                &hadocrx_macros::gtk_box!(
+                    @orientation gtk::Orientation::Horizontal, @spacing 4,
+                    self.widgets.dosing_box.clone(),
+                    self.widgets.btn_dose_calculator.clone()
+                ),
+                &self.widgets.btn_add,
+                &self.widgets.dosing_warning
+            ], [
+                &Self::right_aligned_label("Strength"), 
+                &self.widgets.strength_dropdown_box.entry,
+                &Self::right_aligned_label("Instructions"),
+                &self.widgets.instructions_box
+            ], [
+                &Self::right_aligned_label("Formulation"), 
+                &self.widgets.formulation_dropdown_box.entry,
+                &Self::right_aligned_label("Duration"),
+                &self.widgets.duration_box
+            ]
+        );
+        
+        let advice_grid = hadocrx_macros::gtk_grid!(
+            @margin_bottom 16, @margin_start 16, @margin_end 16,
+            @column_spacing 16,
+            @halign gtk::Align::Center,
+            @width_request 800, [
+                &Self::right_aligned_label("Advice"),
+                &gtk::ScrolledWindow::builder()
+                    .child(&self.widgets.advice_box)
+                    .min_content_height(80)
+                    .hexpand(true)
+                    .has_frame(true)
+                    .build(),
+            ]
+        );
+
+        self.widgets.container.append(&self.widgets.patient_info);
+        self.widgets.container.append(&grid);
+        self.widgets.container.append(&self.widgets.medicine_box.container);    
+        self.widgets.container.append(&advice_grid);
+        self.widgets.page.set_child(Some(&self.widgets.container));
+    }
+
+    fn right_aligned_label(label: &str) -> gtk::Label {
+        gtk::Label::builder().label(label).halign(gtk::Align::End).build()
+    }
+
+    fn prepare_widgets(self: &Rc<Self>, generic_names: Rc<[String]>, brand_names: Rc<[String]>) {
+        self.widgets.generic_name_search_box.initialize(generic_names);
+        self.widgets.brand_name_search_box.initialize(brand_names);
+        self.widgets.strength_dropdown_box.initialize(Vec::new()); 
+        self.widgets.formulation_dropdown_box.initialize(Vec::new());
+        self.widgets.manufacturer_dropdown_box.initialize(Vec::new());
+        self.widgets.strength_dropdown_box.entry.set_secondary_icon_sensitive(false);
+        self.widgets.formulation_dropdown_box.entry.set_secondary_icon_sensitive(false);
+        self.widgets.manufacturer_dropdown_box.entry.set_secondary_icon_sensitive(false);
+        
+        self.widgets.brand_name_search_box.entry().connect_activate(glib::clone!(
+            #[weak(rename_to = session)]
+            self,
+            move |entry| {
+                if entry.text().is_empty() { return; }
+                let brand_name = entry.text().to_string();
+            
+                // get generic_name from the db
+                // update the generic_name box
+                let generic_name = hadocrx::ffi::db::get_generic_name_by_brand_name(brand_name.clone());
+                session.widgets.generic_name_search_box.update_entry_text(&generic_name);
+                session.widgets.generic_name_search_box.entry().emit_activate();
+
+                // get the manufacturer for this brand_name
+                // get all available manufacturers for this generic_name
+                // update the manufacturer box
+                let manufacturer = hadocrx::ffi::db::get_manufacturer_by_brand_name(brand_name); 
+            
+                session.widgets.manufacturer_dropdown_box.update_entry_text(manufacturer.clone()); 
+                // enable the manufacturer dropdown
+                if !session.widgets.manufacturer_dropdown_box.entry.is_secondary_icon_sensitive() {
+                    session.widgets.manufacturer_dropdown_box.entry.set_secondary_icon_sensitive(true);
+                }
+            
+                // get available strengths for this generic_name
+                let strengths = hadocrx::ffi::db::get_strengths_by_generic_name(generic_name);
+                let count_strengths = strengths.len();
+                session.widgets.strength_dropdown_box.update(strengths);
+                // enable the strength dropdown
+                session.widgets.strength_dropdown_box.entry.set_secondary_icon_sensitive(true);
+                // if only one strength is available, set it on the strength box
+                if count_strengths == 1 {
+                    session.widgets.strength_dropdown_box.entry.emit_activate();
+                } else {
+                    session.widgets.formulation_dropdown_box.update(Vec::new());
+                }
+            }
+        ));
+
+        self.widgets.generic_name_search_box.entry().connect_activate(glib::clone!(
+            #[weak(rename_to = session)]
+            self,
+            move |entry| {
+                if entry.text().is_empty() { return; }
+                let generic_name = entry.text().to_string();
+                let manufacturers = hadocrx::ffi::db::get_manufacturers_by_generic_name(generic_name.clone());
+                session.widgets.manufacturer_dropdown_box.update(manufacturers);
+                session.update_generic_name_tooltip(&generic_name);
+            }
+        ));
+
+        self.widgets.strength_dropdown_box.entry.connect_activate(glib::clone!(
+            #[weak(rename_to = session)]
+            self,
+            move |entry| {
+                if entry.text().is_empty() { return; }
+                let strength = entry.text().to_string();
+                let generic_name = session.widgets.generic_name_search_box.entry().text().to_string();
+                let manufacturer = session.widgets.manufacturer_dropdown_box.entry.text().to_string();
+                // get the correct brand_name for the generic_name, strength and manufacturer 
+                let brand_name = hadocrx::ffi::db::get_brand_name_by_generic_name_manufacturer_and_strength(
+                    generic_name.clone(), manufacturer.clone(), strength.clone()
+                );
+            
+                if let Some(name) = brand_name {
+                    session.widgets.brand_name_search_box.update_entry_text(&name);
+                    // get available formulations for the brand_name and strength
+                    let formulations = hadocrx::ffi::db::get_formulations_by_brand_name_and_strength(name, strength);
+                    let count_formulations = formulations.len();
+                    session.widgets.formulation_dropdown_box.update(formulations);
+                    // enable the formulation box
+                    session.widgets.formulation_dropdown_box.entry.set_secondary_icon_sensitive(true);
+                    // if only one formulation is available, set it on the formulation box
+                    if count_formulations == 1 {
+                        session.widgets.formulation_dropdown_box.entry.emit_activate();
+                    }
+                } else {
+                    session.widgets.strength_dropdown_box.update_entry_text(String::new());
+                    session.widgets.formulation_dropdown_box.update_entry_text(String::new());
+                    session.dialog.set_message("Unavailable!");
+                    session.dialog.set_detail(&format!("{} - {} is not available from {}", generic_name, strength, manufacturer));
+                    session.dialog.show(Some(&session.window));
+                }
+            }
+        ));
+
+        self.widgets.manufacturer_dropdown_box.entry.connect_activate(glib::clone!(
+            #[weak(rename_to = session)]
+            self,
+            move |entry| {
+                if entry.text().is_empty() { return; }
+                let manufacturer = entry.text().to_string();
+                let generic_name = session.widgets.generic_name_search_box.entry().text().to_string();
+                // get brand_name for this manufacturer and generic_name
+                let brand_name = hadocrx::ffi::db::get_brand_name_by_generic_name_and_manufacturer(generic_name, manufacturer);
+                session.widgets.brand_name_search_box.update_entry_text(&brand_name);
+            }
+        ));
+
+        for entry in [
+            self.widgets.dosing_box.upcast_ref::<gtk::Editable>(),
+            self.widgets.instructions_box.upcast_ref::<gtk::Editable>(),
+            self.widgets.strength_dropdown_box.entry.upcast_ref::<gtk::Editable>(),
+            self.widgets.formulation_dropdown_box.entry.upcast_ref::<gtk::Editable>(),
+        ] {
+            entry.connect_changed(glib::clone!(
+                #[weak(rename_to = session)]
+                self,
+                move |_| session.update_dose_warning()
+            ));
+        }
+
+        self.widgets.medicine_box.connect_row_action(glib::clone!(
+            #[weak(rename_to = session)]
+            self,
+            move |id, action| session.on_row_action(id, action)
+        ));
+
+        // the window's Ctrl+Z undoes advice together with the medicine lines
+        let advice_buffer = gtk::prelude::TextViewExt::buffer(&self.widgets.advice_box);
+        advice_buffer.set_enable_undo(false);
+        advice_buffer.connect_changed(glib::clone!(
+            #[weak(rename_to = session)]
+            self,
+            move |_| {
+                if session.syncing.get() { return; }
+                let advice = session.widgets.advice_box.text();
+                session.editor.borrow_mut().set_text(TextField::Advice, &advice);
+            }
+        ));
+
+        self.widgets.btn_dose_calculator.connect_clicked(glib::clone!(
+            #[weak(rename_to = session)]
+            self,
+            move |_| {
+                let strength = session.widgets.strength_dropdown_box.entry.text().to_string();
+                let weight_kg = session.patient.borrow().as_ref().and_then(|patient| patient.weight_kg);
+                let dialog = widgets::dose_calculator_dialog::DoseCalculatorDialog::new(&session.window, strength, weight_kg);
+                let dosing_box = session.widgets.dosing_box.clone();
+                dialog.present(move |dosing| {
+                    dosing_box.clear();
+                    dosing_box.set_text(&dosing);
+                });
+            }
+        ));
+
+        self.widgets.btn_add.connect_clicked(glib::clone!(
+            #[weak(rename_to = session)]
+            self,
+            move |_| {
+                let brand_name = session
+                    .widgets.brand_name_search_box.entry().text()
+                    .split_whitespace().collect::<Vec<&str>>().join(" ");
+                let generic_name = session.widgets.generic_name_search_box.entry().text().to_string();
+                let strength = session.widgets.strength_dropdown_box.entry.text().to_string();
+                let formulation = session.widgets.formulation_dropdown_box.entry.text().to_string();
+                let manufacturer = session.widgets.manufacturer_dropdown_box.entry.text().to_string();
+                // let dosing = session.widgets.dosing_box.entry.text().to_string();
+                // let duration = session.widgets.duration_box.entry.text().to_string();
+                // let instructions = session.widgets.instructions_box.entry.text().to_string();
+                let dosing = session.widgets.dosing_box.text().to_string();
+                let duration = session.widgets.duration_box.text().to_string();
+                let instructions = session.widgets.instructions_box.text().to_string();
+
+
+                let errors = widgets::utils::validation_errors!(brand_name, strength, formulation, dosing);
+                if let Some(message) = errors {
+                    session.dialog.set_message("Required fields are empty!");
+                    session.dialog.set_detail(&message);
+                    session.dialog.show(Some(&session.window));
+                } else {
+                    let medicine_data = hadocrx::ffi::prescription::MedicineData::new(brand_name, generic_name, strength, formulation, manufacturer, dosing, instructions, duration);
+                    if !session.warn_about_medicine(&medicine_data) { return; }
+                    session.editor.borrow_mut().add_item(medicine_data);
+                    session.sync_widgets();
+                
+                    // session.widgets.brand_name_search_box.entry.set_text("");
+                    // session.widgets.generic_name_search_box.entry.set_text("");
+                    session.widgets.strength_dropdown_box.update(Vec::new());
+                    session.widgets.formulation_dropdown_box.update(Vec::new());
+                    session.widgets.manufacturer_dropdown_box.entry.set_text("");
+
+                    session.widgets.dosing_box.clear();
+                    session.widgets.duration_box.clear();
+                    session.widgets.instructions_box.clear();
+                
+                    // session.widgets.brand_name_search_box.entry.grab_focus();
+                    session.widgets.brand_name_search_box.grab_focus();
+                }
+            }
+        ));
+    }
+
+    /// Appends the template's medicine lines and advice to the prescription being written. Every line
+    /// goes through the checks a line added by hand does; lines the current patient is allergic to, or
+    /// that could not be checked, are left out, and everything found is reported in one alert.
+    pub fn apply_template(self: &Rc<Self>, template: hadocrx::models::template::PrescriptionTemplate) {
+        let mut skipped = Vec::new();
+        let mut warnings = Vec::new();
+        for item in template.items {
+            // fresh ids, the same template may be applied more than once
+            let medicine_data = hadocrx::ffi::prescription::MedicineData::new(
+                item.brand_name, item.generic_name, item.strength, item.formulation,
+                item.manufacturer, item.dosing, item.instructions, item.duration,
+            );
+            let (allergies, contraindications) = match self.safety_warnings(&medicine_data) {
+                Ok(found) => found,
+                Err((_, err)) => {
+                    skipped.push(format!("{}: {}", medicine_data.brand_name, err));
+                    continue;
+                }
+            };
+            if allergies.iter().any(|warning| warning.is_blocking()) {
+                skipped.extend(allergies.iter().map(|warning| warning.message()));
+                continue;
+            }
+            warnings.extend(allergies.iter().map(|warning| warning.message()));
+            warnings.extend(contraindications.iter().map(|warning| warning.message()));
+            match self.dose_warnings(&medicine_data) {
+                Ok(doses) => warnings.extend(doses.iter().map(|warning| warning.message())),
+                Err(err) => warnings.push(format!("{}: unable to check the dose, {}", medicine_data.brand_name, err)),
+            }
+            self.editor.borrow_mut().add_item(medicine_data);
+        }
+
+        {
+            let mut editor = self.editor.borrow_mut();
+            let advice = editor.prescription().advice.clone();
+            if !template.advice.is_empty() && !advice.contains(&template.advice) {
+                if advice.trim().is_empty() {
+                    editor.replace_text(TextField::Advice, &template.advice);
+                } else {
+                    editor.replace_text(TextField::Advice, &format!("{}\n{}", advice.trim_end(), template.advice));
+                }
+            }
+        }
+        self.sync_widgets();
+
+        let mut detail = Vec::new();
+        if !skipped.is_empty() {
+            detail.push(format!("Not added:\n{}", skipped.join("\n")));
+        }
+        if !warnings.is_empty() {
+            detail.push(format!("Check:\n{}", warnings.join("\n")));
+        }
+        if !detail.is_empty() {
+            let message = if skipped.is_empty() { "Check the medicines added from the template!" } else { "Some medicines were not added!" };
+            self.show_alert(message, &detail.join("\n\n"));
+        }
+    }
+
+    /// Allergy and contraindication warnings for the current patient, none without a patient.
+    /// Errs with the alert heading when a check could not run.
+    fn safety_warnings(
+        &self, medicine_data: &hadocrx::ffi::prescription::MedicineData,
+    ) -> Result<SafetyWarnings, (&'static str, DbError)> {
+        let patient = self.patient.borrow();
+        let Some(patient) = patient.as_ref() else { return Ok(Default::default()); };
+        let allergies = hadocrx::ffi::db::check_allergies(medicine_data, &patient.allergies)
+            .map_err(|err| ("Unable to check for allergies! The medicine was not added.", err))?;
+        let contraindications = hadocrx::ffi::db::check_contraindications(medicine_data, &patient.conditions)
+            .map_err(|err| ("Unable to check for contraindications! The medicine was not added.", err))?;
+        Ok((allergies, contraindications))
+    }
+
+    /// Warns when the medicine is unsafe for the current patient. Returns `false` when the patient is
+    /// allergic to it or it could not be checked, in which case the medicine must not be added.
+    fn warn_about_medicine(&self, medicine_data: &hadocrx::ffi::prescription::MedicineData) -> bool {
+        let (allergies, contraindications) = match self.safety_warnings(medicine_data) {
+            Ok(found) => found,
+            Err((message, err)) => {
+                self.show_alert(message, &err.to_string());
+                return false;
+            }
+        };
+        let blocked = allergies.iter().any(|warning| warning.is_blocking());
+        let detail = allergies.iter().map(|warning| warning.message())
+            .chain(contraindications.iter().map(|warning| warning.message()))
+            .collect::<Vec<String>>()
+            .join("\n");
+        if detail.is_empty() { return true; }
+        let message = if blocked {
+            "Allergy! The medicine was not added."
+        } else if !allergies.is_empty() {
+            "Possible allergy!"
+        } else {
+            "Contraindicated!"
+        };
+        self.dialog.set_message(message);
+        self.dialog.set_detail(&detail);
+        self.dialog.show(Some(&self.window));
+        !blocked
+    }
+
+    /// Weight and age the dose checks use, the age is taken on the prescription date so a patient saved years
+    /// ago is checked at their current age rather than the one entered then.
+    fn patient_weight_and_age(&self) -> (Option<f64>, Option<u32>) {
+        let mut date = self.editor.borrow().prescription().date.clone();
+        if date.is_empty() {
+            date = gtk::glib::DateTime::now_local()
+                .and_then(|now| now.format("%Y-%m-%d"))
+                .map(|date| date.to_string())
+                .unwrap_or_default();
+        }
+        self.patient.borrow().as_ref()
+            .map(|patient| (patient.weight_kg, patient.age_years_on(&date)))
+            .unwrap_or_default()
+    }
+
+    fn dose_warnings(&self, medicine_data: &hadocrx::ffi::prescription::MedicineData) -> DbResult<Vec<DoseWarning>> {
+        let (weight_kg, age_years) = self.patient_weight_and_age();
+        hadocrx::ffi::db::check_dose(medicine_data, weight_kg, age_years)
+    }
+
+    /// Warns when the dosing of a line edited in place goes over the maximum daily dose.
+    fn warn_about_dose(&self, medicine_data: &hadocrx::ffi::prescription::MedicineData) {
+        let warnings = match self.dose_warnings(medicine_data) {
+            Ok(warnings) => warnings,
+            Err(err) => {
+                self.show_alert("Unable to check the dose!", &err.to_string());
+                return;
+            }
+        };
+        if warnings.is_empty() { return; }
+        let detail = warnings.iter().map(|warning| warning.message()).collect::<Vec<String>>().join("\n");
+        self.show_alert("Check the dose!", &detail);
+    }
+
+    /// Shows the maximum daily dose warning next to the dosing box while the line is being written.
+    fn update_dose_warning(&self) {
+        let medicine_data = hadocrx::ffi::prescription::MedicineData::new(
+            self.widgets.brand_name_search_box.entry().text().to_string(),
+            self.widgets.generic_name_search_box.entry().text().to_string(),
+            self.widgets.strength_dropdown_box.entry.text().to_string(),
+            self.widgets.formulation_dropdown_box.entry.text().to_string(),
+            String::new(),
+            self.widgets.dosing_box.text().to_string(),
+            self.widgets.instructions_box.text().to_string(),
+            String::new(),
+        );
+        let text = match self.dose_warnings(&medicine_data) {
+            Ok(warnings) => warnings.iter().map(|warning| warning.message()).collect::<Vec<String>>().join("\n"),
+            Err(err) => format!("Unable to check the dose: {}", err),
+        };
+        self.widgets.dosing_warning.set_label(&text);
+        self.widgets.dosing_warning.set_visible(!text.is_empty());
+        if text.is_empty() {
+            self.widgets.dosing_box.remove_css_class("error");
+        } else {
+            self.widgets.dosing_box.add_css_class("error");
+        }
+    }
+
+    fn update_generic_name_tooltip(&self, generic_name: &str) {
+        let indications = hadocrx::ffi::db::get_indications_by_generic_name(generic_name.to_string());
+        let contraindications = hadocrx::ffi::db::get_contraindications_by_generic_name(generic_name.to_string());
+        if indications.is_empty() && contraindications.is_empty() {
+            self.widgets.generic_name_search_box.set_tooltip_markup(None);
+            return;
+        }
+        let mut markup = format!("<b>{}</b>", gtk::glib::markup_escape_text(generic_name));
+        for (heading, values) in [("Indications", indications), ("Contraindications", contraindications)] {
+            if values.is_empty() { continue; }
+            markup.push_str(&format!("\n\n<b>{}</b>", heading));
+            for value in values {
+                markup.push_str(&format!("\n• {}", gtk::glib::markup_escape_text(&value)));
+            }
+        }
+        self.widgets.generic_name_search_box.set_tooltip_markup(Some(&markup));
+    }
+
+    fn show_alert(&self, message: &str, detail: &str) {
+        self.dialog.set_message(message);
+        self.dialog.set_detail(detail);
+        self.dialog.show(Some(&self.window));
+    }
+}
+
+pub struct SessionWidgets {
+    pub page: gtk::ScrolledWindow,
+    pub container: gtk::Box,
+    pub tab: gtk::Box,
+    pub tab_label: gtk::Label,
+    pub btn_close: gtk::Button,
+    /// Name, age, sex, weight, allergies and conditions of the patient the tab is bound to.
+    pub patient_info: gtk::Label,
+    pub brand_name_search_box: widgets::search_box::SearchBox,
+    pub generic_name_search_box: widgets::search_box::SearchBox,
+    pub strength_dropdown_box: Rc<widgets::dropdown_box::DropdownBox>,
+    pub formulation_dropdown_box: Rc<widgets::dropdown_box::DropdownBox>,
+    pub manufacturer_dropdown_box: Rc<widgets::dropdown_box::DropdownBox>,
+    pub dosing_box: widgets::avro_phonetic_entry::AvroPhoneticEntry,
+    pub instructions_box: widgets::avro_phonetic_entry::AvroPhoneticEntry,
+    pub duration_box: widgets::avro_phonetic_entry::AvroPhoneticEntry,
+    pub btn_dose_calculator: gtk::Button,
+    pub btn_add: gtk::Button,
+    pub dosing_warning: gtk::Label,
+    pub medicine_box: Rc<widgets::medicine_box::MedicineBox>,
+    pub advice_box: widgets::avro_phonetic_textview::AvroPhoneticTextView,
+}
+
+impl SessionWidgets {
+    pub fn new() -> Self {
+        let page = gtk::ScrolledWindow::builder().hexpand(true).vexpand(true).build();
+        let container = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .margin_start(0).margin_end(0).margin_top(0).margin_bottom(0)
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+        container.set_size_request(800, 600); 
+        
+        let brand_name_search_box = widgets::search_box::SearchBox::new();
+        brand_name_search_box.entry().set_placeholder_text(Some("Brand Name"));
+        brand_name_search_box.entry().set_size_request(250, -1);
+        
+        let generic_name_search_box = widgets::search_box::SearchBox::new();
+        generic_name_search_box.entry().set_placeholder_text(Some("Generic Name"));
+        
+        let strength_dropdown_box = widgets::dropdown_box::DropdownBox::new();
+        strength_dropdown_box.entry.set_placeholder_text(Some("Strength"));
+        
+        let formulation_dropdown_box = widgets::dropdown_box::DropdownBox::new();
+        formulation_dropdown_box.entry.set_placeholder_text(Some("Dosage Formulation"));
+
+        let manufacturer_dropdown_box = widgets::dropdown_box::DropdownBox::new();
+        manufacturer_dropdown_box.entry.set_placeholder_text(Some("Manufacturer"));
+        manufacturer_dropdown_box.entry.set_size_request(250, -1);
+
+        let dosing_box = widgets::avro_phonetic_entry::AvroPhoneticEntry::new();
+        let instructions_box = widgets::avro_phonetic_entry::AvroPhoneticEntry::new();
+        let duration_box = widgets::avro_phonetic_entry::AvroPhoneticEntry::new();
+        
+        let btn_add = widgets::combo_button!(
+            gtk::Orientation::Horizontal,
+            widgets::label("Add Drug"),
+            gtk::Image::from_icon_name("list-add")
+        );
+        
+        let btn_dose_calculator = gtk::Button::builder()
+            .icon_name("accessories-calculator")
+            .tooltip_text("Pediatric dose by weight")
+            .build();
+
+        let dosing_warning = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .wrap(true).max_width_chars(40)
+            .css_classes(["error"])
+            .visible(false)
+            .build();
+
+        let medicine_box = widgets::medicine_box::MedicineBox::new();
+        let advice_box = widgets::avro_phonetic_textview::AvroPhoneticTextView::new();
+
+        let patient_info = gtk::Label::builder()
+            .halign(gtk::Align::Center)
+            .wrap(true)
+            .margin_top(16)
+            .visible(false)
+            .build();
+
+        let tab_label = gtk::Label::builder()
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .max_width_chars(24)
+            .build();
+        let btn_close = gtk::Button::builder()
+            .icon_name("window-close-symbolic")
+            .tooltip_text("Close (Ctrl+W)")
+            .css_classes(["flat", "circular"])
+            .build();
+        let tab = hadocrx_macros::gtk_box!(
+            @orientation gtk::Orientation::Horizontal, @spacing 4,
+            tab_label.clone(), btn_close.clone()
+        );
+
+        Self {
+            page, container, tab, tab_label, btn_close, patient_info,
+            brand_name_search_box, generic_name_search_box,
+            manufacturer_dropdown_box,
+            strength_dropdown_box, formulation_dropdown_box,
+            dosing_box, instructions_box, duration_box,
+            btn_dose_calculator, btn_add, dosing_warning, medicine_box,
+            advice_box
+        }
+    }
+}
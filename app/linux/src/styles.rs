@@ -9,6 +9,14 @@ pub const CSS: &str = r#"
         border: 1px solid @borders;
     }  
 
+    .medicine_list {
+        background: transparent;
+    }
+
+    .interaction {
+        color: @error_color;
+    }
+
     .underline {
         text-decoration: underline;
     }
@@ -0,0 +1,169 @@
+use std::{cell::RefCell, ops::Not};
+use gtk::{
+    gdk::{Key, ModifierType},
+    glib::{self, Object},
+    prelude::*,
+    subclass::prelude::*,
+};
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct AvroPhoneticTextView {
+        pub is_bangla_mode: RefCell<bool>,
+        pub english_buffer: RefCell<String>,
+        /// Character offset where the word being typed in Bangla starts.
+        pub word_start: RefCell<i32>,
+        pub mode_icon: gtk::Image,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AvroPhoneticTextView {
+        const NAME: &'static str = "AvroPhoneticTextView";
+        type Type = super::AvroPhoneticTextView;
+        type ParentType = gtk::TextView;
+    }
+
+    impl ObjectImpl for AvroPhoneticTextView {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let key_controller = gtk::EventControllerKey::new();
+            key_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+
+            let obj = self.obj();
+            key_controller.connect_key_pressed(glib::clone!(
+                #[weak] obj,
+                #[upgrade_or] glib::Propagation::Proceed,
+                move |_, keyval, _, state| {
+                    obj.handle_key_press(keyval, state)
+                }
+            ));
+
+            obj.add_controller(key_controller);
+
+            // moving the cursor with the mouse starts a new word
+            let click_gesture = gtk::GestureClick::new();
+            click_gesture.connect_pressed(glib::clone!(
+                #[weak] obj,
+                move |_, _, _, _| {
+                    obj.imp().english_buffer.borrow_mut().clear();
+                }
+            ));
+            obj.add_controller(click_gesture);
+
+            self.mode_icon.set_valign(gtk::Align::Start);
+            self.mode_icon.set_margin_top(4);
+            self.mode_icon.set_margin_start(4);
+            self.mode_icon.set_margin_end(4);
+            obj.set_gutter(gtk::TextWindowType::Right, Some(&self.mode_icon));
+            obj.update_mode_icon();
+        }
+    }
+
+    impl WidgetImpl for AvroPhoneticTextView {}
+    impl TextViewImpl for AvroPhoneticTextView {}
+}
+
+glib::wrapper! {
+    /// Multi-line counterpart of `AvroPhoneticEntry`, Ctrl+M switches between English and Bangla.
+    pub struct AvroPhoneticTextView(ObjectSubclass<imp::AvroPhoneticTextView>)
+        @extends gtk::TextView, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Scrollable;
+}
+
+impl AvroPhoneticTextView {
+    pub fn new() -> Self {
+        Object::builder()
+            .property("wrap-mode", gtk::WrapMode::WordChar)
+            .property("accepts-tab", false)
+            .build()
+    }
+
+    pub fn text(&self) -> String {
+        let buffer = self.buffer();
+        buffer.text(&buffer.start_iter(), &buffer.end_iter(), false).to_string()
+    }
+
+    pub fn set_text(&self, text: &str) {
+        self.imp().english_buffer.borrow_mut().clear();
+        self.buffer().set_text(text);
+    }
+
+    pub fn clear(&self) {
+        self.set_text("");
+    }
+
+    fn update_mode_icon(&self) {
+        let imp = self.imp();
+        let icon_name = if *imp.is_bangla_mode.borrow() { "bn" } else { "en" };
+        imp.mode_icon.set_icon_name(Some(&crate::widgets::utils::get_theme_aware_icon_name(icon_name)));
+    }
+
+    fn cursor_offset(&self) -> i32 {
+        let buffer = self.buffer();
+        buffer.iter_at_mark(&buffer.get_insert()).offset()
+    }
+
+    /// Replaces the word typed so far with its Bangla conversion.
+    fn replace_word(&self) {
+        let imp = self.imp();
+        let buffer = self.buffer();
+        let mut start = buffer.iter_at_offset(*imp.word_start.borrow());
+        let mut end = buffer.iter_at_mark(&buffer.get_insert());
+        buffer.delete(&mut start, &mut end);
+        buffer.insert_at_cursor(&hadocrx::ffi::avro_phonetic::convert(&imp.english_buffer.borrow()));
+        self.scroll_mark_onscreen(&buffer.get_insert());
+    }
+
+    fn handle_key_press(&self, keyval: Key, state: ModifierType) -> glib::Propagation {
+        let imp = self.imp();
+        match state {
+            ModifierType::CONTROL_MASK | ModifierType::ALT_MASK | ModifierType::SUPER_MASK => {
+                imp.english_buffer.borrow_mut().clear();
+                if state == ModifierType::CONTROL_MASK && keyval == Key::m {
+                    let mut mutable_borrow = imp.is_bangla_mode.borrow_mut();
+                    *mutable_borrow = mutable_borrow.not();
+                    drop(mutable_borrow);
+                    self.update_mode_icon();
+                    return glib::Propagation::Stop;
+                }
+                return glib::Propagation::Proceed;
+            }
+            _ => {}
+        }
+        if !*imp.is_bangla_mode.borrow() {
+            return glib::Propagation::Proceed;
+        }
+
+        if keyval == Key::BackSpace {
+            if imp.english_buffer.borrow().is_empty() || self.buffer().has_selection() {
+                imp.english_buffer.borrow_mut().clear();
+                return glib::Propagation::Proceed;
+            }
+            imp.english_buffer.borrow_mut().pop();
+            self.replace_word();
+            return glib::Propagation::Stop;
+        }
+
+        // space, enter and keys that do not type a character end the word
+        let Some(ch) = keyval.to_unicode().filter(|ch| ch.is_ascii_graphic()) else {
+            imp.english_buffer.borrow_mut().clear();
+            return glib::Propagation::Proceed;
+        };
+        if imp.english_buffer.borrow().is_empty() {
+            self.buffer().delete_selection(true, self.is_editable());
+            imp.word_start.replace(self.cursor_offset());
+        }
+        imp.english_buffer.borrow_mut().push(ch);
+        self.replace_word();
+        glib::Propagation::Stop
+    }
+}
+
+impl Default for AvroPhoneticTextView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
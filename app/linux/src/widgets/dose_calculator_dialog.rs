@@ -0,0 +1,123 @@
+use std::rc::Rc;
+
+use gtk::prelude::{BoxExt, ButtonExt, Cast, GridExt, GtkWindowExt, WidgetExt};
+
+use hadocrx::dose_calculator::{calculate_weight_based_dose, DoseCalculationError, WeightBasedDose};
+
+const DOSES_PER_DAY: [&str; 3] = ["Once daily", "Twice daily", "Three times daily"];
+
+/// Calculates the volume per dose of a syrup or suspension from the child's weight and pre-fills the dosing entry.
+pub struct DoseCalculatorDialog {
+    pub window: gtk::Window,
+    pub weight: gtk::SpinButton,
+    pub mg_per_kg_day: gtk::SpinButton,
+    pub doses_per_day: gtk::DropDown,
+    pub label_result: gtk::Label,
+    pub btn_apply: gtk::Button,
+    strength: String,
+}
+
+impl DoseCalculatorDialog {
+    pub fn new(parent: &impl gtk::prelude::IsA<gtk::Window>, strength: String, weight_kg: Option<f64>) -> Rc<Self> {
+        let window = gtk::Window::builder()
+            .title("Pediatric Dose")
+            .transient_for(parent)
+            .modal(true)
+            .resizable(false)
+            .build();
+        let weight = gtk::SpinButton::with_range(0.5, 150.0, 0.5);
+        weight.set_digits(1);
+        weight.set_value(weight_kg.unwrap_or(10.0));
+        let mg_per_kg_day = gtk::SpinButton::with_range(0.1, 200.0, 0.5);
+        mg_per_kg_day.set_digits(1);
+        mg_per_kg_day.set_value(30.0);
+        let doses_per_day = gtk::DropDown::from_strings(&DOSES_PER_DAY);
+        doses_per_day.set_selected(2);
+        let label_result = gtk::Label::builder().halign(gtk::Align::Start).wrap(true).build();
+        let btn_apply = gtk::Button::builder().label("Apply").css_classes(["suggested-action"]).build();
+
+        let grid = hadocrx_macros::gtk_grid!(
+            @margin_top 16, @margin_bottom 16, @margin_start 16, @margin_end 16,
+            @column_spacing 16, @row_spacing 8, [
+                &super::label_left_aligned("Strength"),
+                &super::label_left_aligned(&strength),
+            ], [
+                &super::label_left_aligned("Weight (kg)"),
+                &weight,
+            ], [
+                &super::label_left_aligned("Dose (mg/kg/day)"),
+                &mg_per_kg_day,
+            ], [
+                &super::label_left_aligned("Frequency"),
+                &doses_per_day,
+            ]
+        );
+        let vbox = super::vbox();
+        vbox.set_spacing(8);
+        vbox.append(&grid);
+        vbox.append(&label_result);
+        vbox.append(&btn_apply);
+        super::set_margins(vbox.upcast_ref(), 16);
+        window.set_child(Some(&vbox));
+
+        let dialog = Rc::new(Self { window, weight, mg_per_kg_day, doses_per_day, label_result, btn_apply, strength });
+        dialog.connect_signals();
+        dialog.update_result();
+        dialog
+    }
+
+    /// Shows the dialog, `on_apply` receives the dosing text when the user applies the result.
+    pub fn present<F: Fn(String) + 'static>(self: &Rc<Self>, on_apply: F) {
+        let self_clone = self.clone();
+        self.btn_apply.connect_clicked(move |_| {
+            if let Ok(dose) = self_clone.calculate() {
+                on_apply(dose.dosing_text());
+            }
+            self_clone.window.close();
+        });
+        self.window.present();
+    }
+
+    fn connect_signals(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        self.weight.connect_value_changed(move |_| self_clone.update_result());
+        let self_clone = self.clone();
+        self.mg_per_kg_day.connect_value_changed(move |_| self_clone.update_result());
+        let self_clone = self.clone();
+        self.doses_per_day.connect_selected_notify(move |_| self_clone.update_result());
+    }
+
+    fn calculate(&self) -> Result<WeightBasedDose, DoseCalculationError> {
+        calculate_weight_based_dose(
+            self.weight.value(),
+            self.mg_per_kg_day.value(),
+            self.doses_per_day.selected() as usize + 1,
+            &self.strength,
+        )
+    }
+
+    fn update_result(&self) {
+        match self.calculate() {
+            Ok(dose) => {
+                self.label_result.set_label(&format!(
+                    "{:.2} ml ({:.1} mg) per dose, {:.1} mg/kg/day\n{}",
+                    dose.ml_per_dose, dose.mg_per_dose, dose.mg_per_kg_day, dose.schedule.render_bangla()
+                ));
+                self.btn_apply.set_sensitive(true);
+            }
+            Err(err) => {
+                let message = match err {
+                    DoseCalculationError::TooSmallToMeasure { ml_per_dose } => format!(
+                        "{:.2} ml per dose is too small to measure with a syringe, use a more dilute formulation",
+                        ml_per_dose
+                    ),
+                    DoseCalculationError::InvalidInput | DoseCalculationError::UnsupportedStrength => {
+                        "Select a liquid formulation with a strength such as \"125 mg/5 ml\"".to_string()
+                    }
+                };
+                self.label_result.set_label(&message);
+                self.btn_apply.set_sensitive(false);
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use gtk::prelude::{BoxExt, Cast, EditableExt, GtkWindowExt, ListBoxRowExt, WidgetExt};
+
+use hadocrx::models::patient::Patient;
+
+use super::search_box::SearchBox;
+
+/// Patient picker: fuzzy search over name and phone number, with every patient listed below,
+/// most recent visit first.
+pub struct LoadPatientDialog {
+    pub window: gtk::Window,
+    pub search_box: SearchBox,
+    pub list_box: gtk::ListBox,
+    /// Search labels in the same order as `patients`.
+    labels: Vec<String>,
+    patients: Vec<Patient>,
+}
+
+impl LoadPatientDialog {
+    /// `patients` pairs each patient with the date of their last visit, most recent first.
+    pub fn new(parent: &impl gtk::prelude::IsA<gtk::Window>, patients: Vec<(Patient, Option<String>)>) -> Rc<Self> {
+        let window = gtk::Window::builder()
+            .title("Load Patient")
+            .transient_for(parent)
+            .modal(true)
+            .default_width(480)
+            .default_height(520)
+            .build();
+        let search_box = SearchBox::new();
+        search_box.entry().set_placeholder_text(Some("Name or phone number"));
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Browse)
+            .activate_on_single_click(false)
+            .css_classes(["boxed-list"])
+            .build();
+        list_box.set_placeholder(Some(&gtk::Label::builder()
+            .label("No patients yet")
+            .css_classes(["dim-label"])
+            .margin_top(8).margin_bottom(8)
+            .build()));
+
+        let labels: Vec<String> = patients.iter().map(|(patient, last_visit)| Self::search_label(patient, last_visit.as_deref())).collect();
+        for (patient, last_visit) in patients.iter() {
+            list_box.append(&Self::patient_row(patient, last_visit.as_deref()));
+        }
+        search_box.initialize(labels.clone().into());
+
+        let vbox = super::vbox();
+        vbox.set_spacing(8);
+        vbox.append(&search_box);
+        vbox.append(&super::label_with_class("Recent visits", Some("heading")));
+        vbox.append(&gtk::ScrolledWindow::builder().child(&list_box).vexpand(true).build());
+        super::set_margins(vbox.upcast_ref(), 16);
+        window.set_child(Some(&vbox));
+
+        let patients = patients.into_iter().map(|(patient, _)| patient).collect();
+        Rc::new(Self { window, search_box, list_box, labels, patients })
+    }
+
+    /// Shows the dialog, `on_select` receives the patient picked from the search or the list.
+    pub fn present<F: Fn(Patient) + 'static>(self: &Rc<Self>, on_select: F) {
+        let on_select = Rc::new(on_select);
+        let self_clone = self.clone();
+        let on_select_clone = on_select.clone();
+        // runs after the search box has put the chosen label into the entry
+        self.search_box.entry().connect_activate(move |entry| {
+            let text = entry.text();
+            let Some(index) = self_clone.labels.iter().position(|label| *label == text) else { return; };
+            on_select_clone(self_clone.patients[index].clone());
+            self_clone.window.close();
+        });
+        let self_clone = self.clone();
+        self.list_box.connect_row_activated(move |_, row| {
+            let Some(patient) = self_clone.patients.get(row.index() as usize) else { return; };
+            on_select(patient.clone());
+            self_clone.window.close();
+        });
+        self.window.present();
+        self.search_box.grab_focus();
+    }
+
+    /// `"Karim Ahmed · 01711-000000 · #12"`. The record number keeps patients with the same name apart.
+    fn search_label(patient: &Patient, last_visit: Option<&str>) -> String {
+        let mut parts = vec![patient.name.clone()];
+        parts.extend(patient.phone.clone());
+        parts.extend(last_visit.map(|date| format!("last visit {}", hadocrx::pdf::format_date(date))));
+        parts.push(format!("#{}", patient.id));
+        parts.join(" · ")
+    }
+
+    fn patient_row(patient: &Patient, last_visit: Option<&str>) -> gtk::Box {
+        let mut details: Vec<String> = hadocrx::pdf::patient_fields(patient).into_iter().map(|(_, value)| value).collect();
+        details.push(match last_visit {
+            Some(date) => format!("Last visit {}", hadocrx::pdf::format_date(date)),
+            None => "No visits".to_string(),
+        });
+        let label_name = gtk::Label::builder().label(&patient.name).css_classes(["heading"]).halign(gtk::Align::Start).build();
+        let label_details = gtk::Label::builder()
+            .label(details.join(" · "))
+            .css_classes(["caption", "dim-label"])
+            .halign(gtk::Align::Start)
+            .build();
+        hadocrx_macros::gtk_box!(
+            @orientation gtk::Orientation::Vertical,
+            @margin_top 6, @margin_bottom 6, @margin_start 8, @margin_end 8,
+            label_name, label_details
+        )
+    }
+}
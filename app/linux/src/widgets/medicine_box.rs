@@ -1,122 +1,414 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::{Cell, RefCell}, rc::Rc};
 
-use gtk::prelude::{ButtonExt, GridExt, WidgetExt};
+use gtk::glib;
+use gtk::prelude::{
+    BoxExt, ButtonExt, Cast, CastNone, EntryExt, ListItemExt, ListModelExt,
+    ListBoxRowExt, PopoverExt, StaticType, ToValue, WidgetExt,
+};
 
+use hadocrx::ffi::prescription::MedicineData;
+
+use super::medicine_object::MedicineObject;
 use super::medicine_row::MedicineRow;
 
+type RowActionCallback = Box<dyn Fn(u64, RowAction)>;
+
+const COLUMNS: [&str; 7] = ["Formulation", "Brand Name", "Generic Name", "Strength", "Dosing", "Instructions", "Duration"];
+
+/// A change asked for from a row's buttons, context popover, drag and drop or the keyboard. The list is
+/// not changed until the owner applies it to the prescription and passes the new lines to
+/// [`MedicineBox::set_items`].
+pub enum RowAction {
+    Remove,
+    MoveUp,
+    MoveDown,
+    /// Moves the line to the given position, e.g. where it was dropped.
+    MoveTo(usize),
+    Replace(MedicineData),
+}
+
+/// The medicine lines of a prescription, kept in a `gio::ListStore` and shown by a list view.
+/// Lines can be reordered by dragging them or with Alt+Up/Down.
 pub struct MedicineBox {
-    pub container: gtk::Grid,
-    pub rows: RefCell<Vec<MedicineRow>>,
+    pub container: gtk::Box,
+    pub header: gtk::Box,
+    pub store: gtk::gio::ListStore,
+    pub selection: gtk::SingleSelection,
+    pub list_view: gtk::ListView,
+    pub footer: gtk::Label,
+    /// `false` for read-only views of stored prescriptions, the rows cannot be moved, removed or swapped.
+    pub editable: Cell<bool>,
+    /// Row widgets made by the list view, bound to a line or waiting to be reused.
+    rows: RefCell<Vec<MedicineRow>>,
+    /// The drag handle column followed by one group per column, keeping the header and the rows aligned.
+    size_groups: Vec<gtk::SizeGroup>,
+    on_row_action: RefCell<Option<RowActionCallback>>,
 }
 
 impl MedicineBox {
     pub fn new() -> Rc<Self> {
-        let container = gtk::Grid::builder()
-            .margin_top(16).margin_bottom(16)
-            .margin_start(16).margin_end(16)
-            .column_spacing(16)
-            .row_spacing(8)
-            .halign(gtk::Align::Center)
+        let store = gtk::gio::ListStore::new::<MedicineObject>();
+        let selection = gtk::SingleSelection::builder()
+            .model(&store)
+            .autoselect(false)
+            .can_unselect(true)
             .build();
-        Rc::new(Self {
-            container,
-            rows: RefCell::new(Vec::new()),
-        })
-    }
-
-    pub fn append(self: &Rc<Self>, medicine_row: MedicineRow) {
-        let index = self.rows.borrow().len();
-        
-        self.rows.borrow_mut().push(medicine_row);
-        self.setup_row(index); 
-        self.connect_btn_signals(index);
-    }
-    
-    fn setup_header_row(&self) {
-        self.container.attach(&Self::custom_label("Formulation"), 0, 0, 1, 1);
-        self.container.attach(&Self::custom_label("Brand Name"), 1, 0, 1, 1);
-        self.container.attach(&Self::custom_label("Generic Name"), 2, 0, 1, 1);
-        self.container.attach(&Self::custom_label("Strength"), 3, 0, 1, 1);
-        self.container.attach(&Self::custom_label("Dosing"), 4, 0, 1, 1);
-        self.container.attach(&Self::custom_label("Instructions"), 5, 0, 1, 1);
-        self.container.attach(&Self::custom_label("Duration"), 6, 0, 1, 1);
-    }
-
-    fn setup_row(self: &Rc<Self>, index: usize) {
-        if index == 0 {
-            self.setup_header_row();
+        let list_view = gtk::ListView::builder()
+            .model(&selection)
+            .factory(&gtk::SignalListItemFactory::new())
+            .css_classes(["medicine_list"])
+            .build();
+        let size_groups: Vec<gtk::SizeGroup> = (0..=COLUMNS.len()).map(|_| gtk::SizeGroup::new(gtk::SizeGroupMode::Horizontal)).collect();
+
+        let header = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(16)
+            .visible(false)
+            .build();
+        let handle_spacer = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        size_groups[0].add_widget(&handle_spacer);
+        header.append(&handle_spacer);
+        for (size_group, column) in size_groups[1..].iter().zip(COLUMNS) {
+            let label = Self::custom_label(column);
+            size_group.add_widget(&label);
+            header.append(&label);
         }
-        let row = index as i32;
-        let rows_borrowed = self.rows.borrow();
-        let item = rows_borrowed.get(index).unwrap();
-        self.container.attach(&item.label_formulation, 0, row+1, 1, 1);
-        self.container.attach(&item.label_brand_name, 1, row+1, 1, 1);
-        self.container.attach(&item.label_generic_name, 2, row+1, 1, 1);
-        self.container.attach(&item.label_strength, 3, row+1, 1, 1);
-        self.container.attach(&item.label_dosing, 4, row+1, 1, 1);
-        self.container.attach(&item.label_instructions, 5, row+1, 1, 1);
-        self.container.attach(&item.label_duration, 6, row+1, 1, 1);
-      
-        self.container.attach(&item.btn_up, 7, row+1, 1, 1);
-        self.container.attach(&item.btn_down, 8, row+1, 1, 1);
-        self.container.attach(&item.btn_delete, 9, row+1, 1, 1);
-    }
-
-    fn connect_btn_signals(self: &Rc<Self>, index: usize) {
-        let rows_borrowed = self.rows.borrow();
-        let item = rows_borrowed.get(index).unwrap();
-        let id = item.data.id;
-        
-        let self_clone = self.clone(); 
-        item.btn_delete.connect_clicked(move |btn| {
-            btn.set_sensitive(false);
-            {
-                let mut mutable_borrow = self_clone.rows.borrow_mut();
-                if let Some(index) = mutable_borrow.iter().position(|row| row.data.id == id) {
-                    mutable_borrow.remove(index);
-                }
-            }
-            self_clone.refresh_ui();
+        let footer = gtk::Label::builder().halign(gtk::Align::End).margin_top(8).visible(false).build();
+
+        let container = hadocrx_macros::gtk_box!(
+            @orientation gtk::Orientation::Vertical, @spacing 4,
+            @margin_top 16, @margin_bottom 16, @margin_start 16, @margin_end 16,
+            @halign gtk::Align::Center,
+            header.clone(), list_view.clone(), footer.clone()
+        );
+        let medicine_box = Rc::new(Self {
+            container, header, store, selection, list_view, footer,
+            editable: Cell::new(true),
+            rows: RefCell::new(Vec::new()),
+            size_groups,
+            on_row_action: RefCell::new(None),
         });
+        medicine_box.connect_signals();
+        medicine_box
+    }
+
+    /// Calls `on_row_action` with the id of the line and the change asked for.
+    pub fn connect_row_action<F: Fn(u64, RowAction) + 'static>(&self, on_row_action: F) {
+        self.on_row_action.replace(Some(Box::new(on_row_action)));
+    }
 
-        let self_clone = self.clone(); 
-        item.btn_up.connect_clicked(move |_| {
-            {
-                let mut mutable_borrow = self_clone.rows.borrow_mut();
-                if let Some(index) = mutable_borrow.iter().position(|row| row.data.id == id) {
-                    if index > 0 {
-                        mutable_borrow.swap(index, index-1);
+    /// Shows `items` in order, changing only the lines that were added, removed, moved or edited.
+    pub fn set_items(&self, items: &[MedicineData]) {
+        let selected = self.selected_id();
+        for (index, data) in items.iter().enumerate() {
+            let index = index as u32;
+            match (index..self.store.n_items()).find(|position| self.object_at(*position).is_some_and(|object| object.id() == data.id)) {
+                Some(position) => {
+                    let Some(object) = self.object_at(position) else { continue; };
+                    if position != index {
+                        self.store.remove(position);
+                        self.store.insert(index, &object);
+                    }
+                    if object.data() != *data {
+                        object.set_data(data.clone());
                     }
                 }
+                None => self.store.insert(index, &MedicineObject::new(data.clone())),
+            }
+        }
+        // drop stale objects left after the last line
+        let count = items.len() as u32;
+        if self.store.n_items() > count {
+            self.store.splice(count, self.store.n_items() - count, &[] as &[MedicineObject]);
+        }
+
+        self.update_interactions();
+        for row in self.rows.borrow().iter() {
+            row.update();
+        }
+        self.update_footer();
+        self.header.set_visible(!items.is_empty());
+        if let Some(position) = selected.and_then(|id| self.position(id)) {
+            self.selection.set_selected(position);
+        }
+    }
+
+    pub fn set_editable(&self, editable: bool) {
+        self.editable.set(editable);
+        for row in self.rows.borrow().iter() {
+            row.set_editable(editable);
+        }
+    }
+
+    fn connect_signals(self: &Rc<Self>) {
+        let factory = self.list_view.factory().and_downcast::<gtk::SignalListItemFactory>().expect("set in new()");
+        let self_clone = self.clone();
+        factory.connect_setup(move |_, list_item| {
+            let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else { return; };
+            let row = MedicineRow::new();
+            self_clone.setup_row(&row);
+            list_item.set_child(Some(&row));
+        });
+        let self_clone = self.clone();
+        factory.connect_bind(move |_, list_item| {
+            let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else { return; };
+            let Some(row) = list_item.child().and_downcast::<MedicineRow>() else { return; };
+            let Some(object) = list_item.item().and_downcast::<MedicineObject>() else { return; };
+            row.bind(&object, self_clone.editable.get());
+        });
+        factory.connect_unbind(|_, list_item| {
+            let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else { return; };
+            if let Some(row) = list_item.child().and_downcast::<MedicineRow>() {
+                row.unbind();
             }
-            self_clone.refresh_ui();
         });
-        
-        let self_clone = self.clone(); 
-        item.btn_down.connect_clicked(move |_| {
-            {
-                let mut mutable_borrow = self_clone.rows.borrow_mut();
-                if let Some(index) = mutable_borrow.iter().position(|row| row.data.id == id) {
-                    if index+1 < mutable_borrow.len() {
-                        mutable_borrow.swap(index, index+1);
+        let self_clone = self.clone();
+        factory.connect_teardown(move |_, list_item| {
+            let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else { return; };
+            let Some(row) = list_item.child().and_downcast::<MedicineRow>() else { return; };
+            self_clone.rows.borrow_mut().retain(|other| *other != row);
+        });
+
+        // double click or Enter on a row
+        let self_clone = self.clone();
+        self.list_view.connect_activate(move |_, position| {
+            let Some(id) = self_clone.object_at(position).map(|object| object.id()) else { return; };
+            if let Some(row) = self_clone.row(id) {
+                self_clone.begin_edit(&row);
+            }
+        });
+
+        let key_controller = gtk::EventControllerKey::new();
+        let self_clone = self.clone();
+        key_controller.connect_key_pressed(move |_, key, _, state| {
+            if !state.contains(gtk::gdk::ModifierType::ALT_MASK) { return glib::Propagation::Proceed; }
+            let action = match key {
+                gtk::gdk::Key::Up => RowAction::MoveUp,
+                gtk::gdk::Key::Down => RowAction::MoveDown,
+                _ => return glib::Propagation::Proceed,
+            };
+            let Some(id) = self_clone.selected_id() else { return glib::Propagation::Proceed; };
+            self_clone.emit_row_action(id, action);
+            if let Some(position) = self_clone.position(id) {
+                self_clone.list_view.scroll_to(position, gtk::ListScrollFlags::FOCUS | gtk::ListScrollFlags::SELECT, None);
+            }
+            glib::Propagation::Stop
+        });
+        self.list_view.add_controller(key_controller);
+    }
+
+    /// Connects a row widget made by the list view, whichever line it shows later.
+    fn setup_row(self: &Rc<Self>, row: &MedicineRow) {
+        self.size_groups[0].add_widget(row.drag_handle());
+        for (size_group, cell) in self.size_groups[1..].iter().zip(row.cells()) {
+            size_group.add_widget(cell);
+        }
+        self.rows.borrow_mut().push(row.clone());
+
+        let gesture = gtk::GestureClick::builder().button(gtk::gdk::BUTTON_SECONDARY).build();
+        let self_clone = self.clone();
+        gesture.connect_pressed(glib::clone!(
+            #[weak] row,
+            move |_, _, _, _| {
+                if let Some(object) = row.item() {
+                    self_clone.show_alternatives(object.id(), row.upcast_ref());
+                }
+            }
+        ));
+        row.add_controller(gesture);
+
+        let self_clone = self.clone();
+        row.btn_edit().connect_clicked(glib::clone!(
+            #[weak] row,
+            move |_| {
+                if row.is_editing() {
+                    self_clone.finish_edit(&row);
+                } else {
+                    self_clone.begin_edit(&row);
+                }
+            }
+        ));
+        for entry in row.entries() {
+            let self_clone = self.clone();
+            entry.connect_activate(glib::clone!(
+                #[weak] row,
+                move |_| self_clone.finish_edit(&row)
+            ));
+            let key_controller = gtk::EventControllerKey::new();
+            key_controller.connect_key_pressed(glib::clone!(
+                #[weak] row,
+                #[upgrade_or] glib::Propagation::Proceed,
+                move |_, key, _, _| {
+                    if key != gtk::gdk::Key::Escape { return glib::Propagation::Proceed; }
+                    row.end_edit();
+                    glib::Propagation::Stop
+                }
+            ));
+            entry.add_controller(key_controller);
+        }
+
+        for (button, action) in [
+            (row.btn_delete(), (|| RowAction::Remove) as fn() -> RowAction),
+            (row.btn_up(), || RowAction::MoveUp),
+            (row.btn_down(), || RowAction::MoveDown),
+        ] {
+            let self_clone = self.clone();
+            button.connect_clicked(glib::clone!(
+                #[weak] row,
+                move |_| {
+                    if let Some(object) = row.item() {
+                        self_clone.emit_row_action(object.id(), action());
                     }
                 }
+            ));
+        }
+
+        self.setup_drag_and_drop(row);
+    }
+
+    /// Rows are dragged onto another row, the dragged line takes its place.
+    fn setup_drag_and_drop(self: &Rc<Self>, row: &MedicineRow) {
+        let drag_source = gtk::DragSource::builder().actions(gtk::gdk::DragAction::MOVE).build();
+        let self_clone = self.clone();
+        drag_source.connect_prepare(glib::clone!(
+            #[weak] row,
+            #[upgrade_or] None,
+            move |_, _, _| {
+                if !self_clone.editable.get() || row.is_editing() { return None; }
+                let id = row.item()?.id();
+                Some(gtk::gdk::ContentProvider::for_value(&id.to_value()))
             }
-            self_clone.refresh_ui();
+        ));
+        drag_source.connect_drag_begin(glib::clone!(
+            #[weak] row,
+            move |drag_source, _| {
+                drag_source.set_icon(Some(&gtk::WidgetPaintable::new(Some(&row))), 0, 0);
+            }
+        ));
+        row.add_controller(drag_source);
+
+        let drop_target = gtk::DropTarget::new(u64::static_type(), gtk::gdk::DragAction::MOVE);
+        let self_clone = self.clone();
+        drop_target.connect_drop(glib::clone!(
+            #[weak] row,
+            #[upgrade_or] false,
+            move |_, value, _, _| {
+                let Ok(id) = value.get::<u64>() else { return false; };
+                let Some(to) = row.item().and_then(|object| self_clone.position(object.id())) else { return false; };
+                self_clone.emit_row_action(id, RowAction::MoveTo(to as usize));
+                true
+            }
+        ));
+        row.add_controller(drop_target);
+    }
+
+    fn begin_edit(&self, row: &MedicineRow) {
+        if !self.editable.get() { return; }
+        row.begin_edit();
+    }
+
+    /// Puts the typed dosing, instructions and duration back into the line, keeping its id and position.
+    fn finish_edit(&self, row: &MedicineRow) {
+        let Some(object) = row.item() else { return; };
+        let Some(edited) = row.edited_data() else { return; };
+        row.end_edit();
+        if edited != object.data() {
+            self.emit_row_action(object.id(), RowAction::Replace(edited));
+        }
+    }
+
+    fn emit_row_action(&self, id: u64, action: RowAction) {
+        if !self.editable.get() { return; }
+        if let Some(on_row_action) = self.on_row_action.borrow().as_ref() {
+            on_row_action(id, action);
+        }
+    }
+
+    /// Context popover listing cheaper (or any other) brands of the same generic, strength and dosage form.
+    fn show_alternatives(self: &Rc<Self>, id: u64, parent: &gtk::Widget) {
+        if !self.editable.get() { return; }
+        let Some(data) = self.position(id).and_then(|position| self.object_at(position)).map(|object| object.data()) else { return; };
+        let alternatives = hadocrx::ffi::db::get_alternatives(data.brand_name.clone(), data.strength.clone(), data.formulation.clone());
+
+        let popover = gtk::Popover::new();
+        popover.set_parent(parent);
+        popover.connect_closed(|popover| popover.unparent());
+        let list_box = gtk::ListBox::builder().selection_mode(gtk::SelectionMode::None).build();
+        if alternatives.is_empty() {
+            list_box.append(&gtk::Label::builder().label("No alternatives available").margin_top(4).margin_bottom(4).build());
+        }
+        for alternative in alternatives.iter() {
+            let price = alternative.unit_price.map(|price| format!("৳ {:.2}", price)).unwrap_or_else(|| "no price".to_string());
+            let label = gtk::Label::builder()
+                .label(format!("{} — {} — {}", alternative.drug.brand_name, alternative.drug.manufacturer, price))
+                .halign(gtk::Align::Start)
+                .margin_top(4).margin_bottom(4)
+                .build();
+            list_box.append(&label);
+        }
+        let self_clone = self.clone();
+        let popover_clone = popover.clone();
+        list_box.connect_row_activated(move |_, row| {
+            let Some(alternative) = alternatives.get(row.index() as usize) else { return; };
+            popover_clone.popdown();
+            self_clone.emit_row_action(id, RowAction::Replace(data.with_drug(&alternative.drug)));
         });
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .child(&list_box)
+            .propagate_natural_height(true)
+            .max_content_height(400)
+            .build();
+        popover.set_child(Some(&scrolled_window));
+        popover.popup();
+    }
+
+    fn object_at(&self, position: u32) -> Option<MedicineObject> {
+        self.store.item(position).and_downcast::<MedicineObject>()
+    }
+
+    fn position(&self, id: u64) -> Option<u32> {
+        (0..self.store.n_items()).find(|position| self.object_at(*position).is_some_and(|object| object.id() == id))
+    }
+
+    /// The row widget currently showing the line with `id`.
+    fn row(&self, id: u64) -> Option<MedicineRow> {
+        self.rows.borrow().iter()
+            .find(|row| row.item().is_some_and(|object| object.id() == id))
+            .cloned()
+    }
+
+    fn selected_id(&self) -> Option<u64> {
+        self.selection.selected_item().and_downcast::<MedicineObject>().map(|object| object.id())
     }
 
-    fn refresh_ui(self: &Rc<Self>) {
-        while let Some(child) = self.container.first_child() {
-            self.container.remove(&child);
-            drop(child);
+    /// Lines in the order they are shown.
+    fn items(&self) -> Vec<MedicineData> {
+        (0..self.store.n_items()).filter_map(|position| self.object_at(position)).map(|object| object.data()).collect()
+    }
+
+    fn update_footer(&self) {
+        let items = self.items();
+        self.footer.set_visible(!items.is_empty());
+        if items.is_empty() { return; }
+
+        let estimate = hadocrx::ffi::db::estimate_prescription_cost(&items);
+        let mut text = format!("Estimated cost: ৳ {:.2}", estimate.total);
+        if estimate.unpriced_lines > 0 {
+            text.push_str(&format!(" ({} not priced)", estimate.unpriced_lines));
         }
-        for index in 0..self.rows.borrow().len() {
-            self.setup_row(index); 
+        self.footer.set_label(&text);
+    }
+
+    fn update_interactions(&self) {
+        let warnings = hadocrx::ffi::db::check_interactions(&self.items());
+        for position in 0..self.store.n_items() {
+            let Some(object) = self.object_at(position) else { continue; };
+            let id = object.id();
+            object.set_interactions(warnings.iter().filter(|warning| warning.involves(id)).map(|warning| warning.message()).collect());
         }
     }
 
     fn custom_label(text: &str) -> gtk::Label {
-        gtk::Label::builder().label(text).halign(gtk::Align::Start).css_classes(["underline"]).build()
+        gtk::Label::builder().label(text).halign(gtk::Align::Start).xalign(0.0).css_classes(["underline"]).build()
     }
 }
@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+use gtk::{
+    glib::{self, Object},
+    subclass::prelude::*,
+};
+
+use hadocrx::ffi::prescription::MedicineData;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct MedicineObject {
+        pub data: RefCell<Option<MedicineData>>,
+        /// Interactions with other lines of the prescription, as shown in the row tooltip.
+        pub interactions: RefCell<Vec<String>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MedicineObject {
+        const NAME: &'static str = "MedicineObject";
+        type Type = super::MedicineObject;
+    }
+
+    impl ObjectImpl for MedicineObject {}
+}
+
+glib::wrapper! {
+    /// A medicine line as an item of the `gio::ListStore` behind `MedicineBox`.
+    pub struct MedicineObject(ObjectSubclass<imp::MedicineObject>);
+}
+
+impl MedicineObject {
+    pub fn new(data: MedicineData) -> Self {
+        let object: Self = Object::builder().build();
+        object.set_data(data);
+        object
+    }
+
+    pub fn data(&self) -> MedicineData {
+        self.imp().data.borrow().clone().expect("MedicineObject is created with data")
+    }
+
+    pub fn id(&self) -> u64 {
+        self.imp().data.borrow().as_ref().map(|data| data.id).unwrap_or_default()
+    }
+
+    pub fn set_data(&self, data: MedicineData) {
+        self.imp().data.replace(Some(data));
+    }
+
+    pub fn interactions(&self) -> Vec<String> {
+        self.imp().interactions.borrow().clone()
+    }
+
+    pub fn set_interactions(&self, interactions: Vec<String>) {
+        self.imp().interactions.replace(interactions);
+    }
+}
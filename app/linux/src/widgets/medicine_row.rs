@@ -1,49 +1,278 @@
+use std::cell::RefCell;
+use gtk::{
+    glib::{self, Object},
+    prelude::*,
+    subclass::prelude::*,
+};
+
 use hadocrx::ffi::prescription::MedicineData;
-use super::custom_icon_button;
-
-#[derive(Debug)]
-pub struct MedicineRow {
-    pub data: MedicineData,
-    pub label_formulation: gtk::Label,
-    pub label_brand_name: gtk::Label,
-    pub label_generic_name: gtk::Label,
-    pub label_strength: gtk::Label,
-    pub label_dosing: gtk::Label,
-    pub label_duration: gtk::Label,
-    pub label_instructions: gtk::Label,
-    
-    pub btn_up: gtk::Button,
-    pub btn_down: gtk::Button,
-    pub btn_delete: gtk::Button,
+use super::avro_phonetic_entry::AvroPhoneticEntry;
+use super::medicine_object::MedicineObject;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct MedicineRow {
+        pub drag_handle: gtk::Image,
+        pub label_formulation: gtk::Label,
+        pub label_brand_name: gtk::Label,
+        pub label_generic_name: gtk::Label,
+        pub label_strength: gtk::Label,
+        pub label_dosing: gtk::Label,
+        pub label_instructions: gtk::Label,
+        pub label_duration: gtk::Label,
+        /// Shown in place of the dosing, instructions and duration labels while the row is being edited.
+        pub entry_dosing: AvroPhoneticEntry,
+        pub entry_instructions: AvroPhoneticEntry,
+        pub entry_duration: AvroPhoneticEntry,
+        pub cell_dosing: gtk::Box,
+        pub cell_instructions: gtk::Box,
+        pub cell_duration: gtk::Box,
+
+        pub btn_edit: gtk::Button,
+        pub btn_up: gtk::Button,
+        pub btn_down: gtk::Button,
+        pub btn_delete: gtk::Button,
+        /// The line shown, `None` while the row is not bound to an item of the list.
+        pub item: RefCell<Option<MedicineObject>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MedicineRow {
+        const NAME: &'static str = "MedicineRow";
+        type Type = super::MedicineRow;
+        type ParentType = gtk::Box;
+    }
+
+    impl ObjectImpl for MedicineRow {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            obj.set_orientation(gtk::Orientation::Horizontal);
+            obj.set_spacing(16);
+            obj.set_margin_top(4);
+            obj.set_margin_bottom(4);
+
+            self.drag_handle.set_icon_name(Some("list-drag-handle-symbolic"));
+            self.drag_handle.set_tooltip_text(Some("Drag to reorder, or Alt+Up/Down"));
+            self.label_brand_name.add_css_class("heading");
+            self.label_generic_name.add_css_class("caption");
+            for label in obj.labels() {
+                label.set_halign(gtk::Align::Start);
+                label.set_xalign(0.0);
+            }
+            for (cell, label, entry) in [
+                (&self.cell_dosing, &self.label_dosing, &self.entry_dosing),
+                (&self.cell_instructions, &self.label_instructions, &self.entry_instructions),
+                (&self.cell_duration, &self.label_duration, &self.entry_duration),
+            ] {
+                entry.set_width_chars(10);
+                entry.set_visible(false);
+                cell.append(label);
+                cell.append(entry);
+            }
+
+            self.btn_edit.set_icon_name("document-edit-symbolic");
+            self.btn_edit.set_tooltip_text(Some("Edit"));
+            self.btn_up.set_icon_name(&crate::widgets::utils::get_theme_aware_icon_name("fa-arrow-up"));
+            self.btn_down.set_icon_name(&crate::widgets::utils::get_theme_aware_icon_name("fa-arrow-down"));
+            self.btn_delete.set_icon_name(&crate::widgets::utils::get_theme_aware_icon_name("fa-arrow-delete"));
+
+            obj.append(&self.drag_handle);
+            for cell in obj.cells() {
+                obj.append(cell);
+            }
+            for button in [&self.btn_edit, &self.btn_up, &self.btn_down, &self.btn_delete] {
+                button.set_valign(gtk::Align::Center);
+                obj.append(button);
+            }
+        }
+    }
+
+    impl WidgetImpl for MedicineRow {}
+    impl BoxImpl for MedicineRow {}
+}
+
+glib::wrapper! {
+    /// Row widget of `MedicineBox`, recycled by its list view for whichever line scrolls into view.
+    pub struct MedicineRow(ObjectSubclass<imp::MedicineRow>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
 }
 
 impl MedicineRow {
-    pub fn new(medicine_data: MedicineData) -> Self {
-        let data = medicine_data;
-        let label_formulation = gtk::Label::builder().label(&data.formulation).halign(gtk::Align::Start).build();
-        let label_brand_name = gtk::Label::builder().label(&data.brand_name).css_classes(["heading"]).halign(gtk::Align::Start).build();
-        let label_generic_name = gtk::Label::builder().label(&data.generic_name).css_classes(["caption"]).halign(gtk::Align::Start).build();
-        let label_strength = gtk::Label::builder().label(&data.strength).halign(gtk::Align::Start).build();
-        let label_dosing = gtk::Label::builder().label(&data.dosing).selectable(true).halign(gtk::Align::Start).build();
-        let label_duration = gtk::Label::builder().label(&data.duration).selectable(true).halign(gtk::Align::Start).build();
-        let label_instructions = gtk::Label::builder().label(&data.instructions).selectable(true).halign(gtk::Align::Start).build();
-       
-        let btn_up = custom_icon_button("fa-arrow-up"); 
-        let btn_down = custom_icon_button("fa-arrow-down");
-        let btn_delete = custom_icon_button("fa-arrow-delete");
-
-        Self { 
-            data,
-            label_formulation, 
-            label_brand_name, 
-            label_generic_name, 
-            label_strength,
-            label_dosing,
-            label_duration,
-            label_instructions,
-            btn_up, 
-            btn_down,
-            btn_delete 
+    pub fn new() -> Self {
+        Object::builder().build()
+    }
+
+    pub fn item(&self) -> Option<MedicineObject> {
+        self.imp().item.borrow().clone()
+    }
+
+    /// Shows `item`, leaving any edit of the line shown before.
+    pub fn bind(&self, item: &MedicineObject, editable: bool) {
+        self.imp().item.replace(Some(item.clone()));
+        self.end_edit();
+        self.update();
+        self.set_editable(editable);
+    }
+
+    pub fn unbind(&self) {
+        self.end_edit();
+        self.imp().item.replace(None);
+    }
+
+    /// Shows the current data and interactions of the bound item.
+    pub fn update(&self) {
+        let Some(item) = self.item() else { return; };
+        let data = item.data();
+        let imp = self.imp();
+        imp.label_formulation.set_label(&data.formulation);
+        imp.label_brand_name.set_label(&data.brand_name);
+        imp.label_generic_name.set_label(&data.generic_name);
+        imp.label_strength.set_label(&data.strength);
+        imp.label_dosing.set_label(&data.dosing);
+        imp.label_instructions.set_label(&data.instructions);
+        imp.label_duration.set_label(&data.duration);
+        self.set_interactions(&item.interactions());
+    }
+
+    pub fn labels(&self) -> [&gtk::Label; 7] {
+        let imp = self.imp();
+        [
+            &imp.label_formulation,
+            &imp.label_brand_name,
+            &imp.label_generic_name,
+            &imp.label_strength,
+            &imp.label_dosing,
+            &imp.label_instructions,
+            &imp.label_duration,
+        ]
+    }
+
+    /// One widget per column, in the order of the column headers.
+    pub fn cells(&self) -> [&gtk::Widget; 7] {
+        let imp = self.imp();
+        [
+            imp.label_formulation.upcast_ref(),
+            imp.label_brand_name.upcast_ref(),
+            imp.label_generic_name.upcast_ref(),
+            imp.label_strength.upcast_ref(),
+            imp.cell_dosing.upcast_ref(),
+            imp.cell_instructions.upcast_ref(),
+            imp.cell_duration.upcast_ref(),
+        ]
+    }
+
+    pub fn entries(&self) -> [&AvroPhoneticEntry; 3] {
+        let imp = self.imp();
+        [&imp.entry_dosing, &imp.entry_instructions, &imp.entry_duration]
+    }
+
+    pub fn drag_handle(&self) -> &gtk::Image {
+        &self.imp().drag_handle
+    }
+
+    pub fn btn_edit(&self) -> &gtk::Button {
+        &self.imp().btn_edit
+    }
+
+    pub fn btn_up(&self) -> &gtk::Button {
+        &self.imp().btn_up
+    }
+
+    pub fn btn_down(&self) -> &gtk::Button {
+        &self.imp().btn_down
+    }
+
+    pub fn btn_delete(&self) -> &gtk::Button {
+        &self.imp().btn_delete
+    }
+
+    pub fn set_editable(&self, editable: bool) {
+        let imp = self.imp();
+        for button in [&imp.btn_edit, &imp.btn_up, &imp.btn_down, &imp.btn_delete] {
+            button.set_sensitive(editable);
+        }
+        imp.drag_handle.set_sensitive(editable);
+        if !editable {
+            self.end_edit();
         }
     }
+
+    pub fn is_editing(&self) -> bool {
+        WidgetExt::is_visible(&self.imp().entry_dosing)
+    }
+
+    /// Swaps the dosing, instructions and duration labels for entries holding the current values.
+    pub fn begin_edit(&self) {
+        let Some(data) = self.item().map(|item| item.data()) else { return; };
+        let imp = self.imp();
+        for (entry, text) in self.entries().into_iter().zip([&data.dosing, &data.instructions, &data.duration]) {
+            entry.clear();
+            entry.set_text(text);
+            entry.set_visible(true);
+        }
+        for label in [&imp.label_dosing, &imp.label_instructions, &imp.label_duration] {
+            label.set_visible(false);
+        }
+        imp.entry_dosing.remove_css_class("error");
+        imp.btn_edit.set_icon_name("object-select-symbolic");
+        imp.btn_edit.set_tooltip_text(Some("Save (Enter), Esc to cancel"));
+        imp.entry_dosing.grab_focus();
+    }
+
+    /// Shows the labels again, dropping whatever was typed into the entries.
+    pub fn end_edit(&self) {
+        let imp = self.imp();
+        for entry in self.entries() {
+            entry.set_visible(false);
+        }
+        for label in [&imp.label_dosing, &imp.label_instructions, &imp.label_duration] {
+            label.set_visible(true);
+        }
+        imp.btn_edit.set_icon_name("document-edit-symbolic");
+        imp.btn_edit.set_tooltip_text(Some("Edit"));
+    }
+
+    /// The line with the values typed into the entries, keeping its id. Marks the dosing entry and
+    /// returns `None` when the dosing was left empty, it is required as when the line was added.
+    pub fn edited_data(&self) -> Option<MedicineData> {
+        let data = self.item()?.data();
+        let imp = self.imp();
+        let edited = MedicineData {
+            dosing: imp.entry_dosing.text().trim().to_string(),
+            instructions: imp.entry_instructions.text().trim().to_string(),
+            duration: imp.entry_duration.text().trim().to_string(),
+            ..data
+        };
+        if edited.dosing.is_empty() {
+            imp.entry_dosing.add_css_class("error");
+            imp.entry_dosing.grab_focus();
+            return None;
+        }
+        Some(edited)
+    }
+
+    /// Highlights the row when it interacts with another line, listing the interactions in the tooltip.
+    fn set_interactions(&self, interactions: &[String]) {
+        let tooltip = interactions.join("\n");
+        for label in self.labels() {
+            if interactions.is_empty() {
+                label.remove_css_class("interaction");
+                label.set_tooltip_text(None);
+            } else {
+                label.add_css_class("interaction");
+                label.set_tooltip_text(Some(&tooltip));
+            }
+        }
+    }
+}
+
+impl Default for MedicineRow {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -2,9 +2,19 @@ use gtk::{glib::{self, object::IsA}, prelude::{BoxExt, ButtonExt, FrameExt, Widg
 pub mod search_box; 
 pub mod dropdown_box; 
 pub mod utils;
+pub mod medicine_object;
 pub mod medicine_row;
 pub mod medicine_box;
 pub mod avro_phonetic_entry;
+pub mod avro_phonetic_textview;
+pub mod dose_calculator_dialog;
+pub mod page_layout_popover;
+pub mod template_popover;
+pub mod save_template_dialog;
+pub mod new_patient_dialog;
+pub mod load_patient_dialog;
+pub mod prescription_history_dialog;
+pub mod restore_drafts_dialog;
 
 const DEFAULT_MARGIN: i32 = 4;
 
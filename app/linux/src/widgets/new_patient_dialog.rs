@@ -0,0 +1,171 @@
+use std::rc::Rc;
+
+use gtk::prelude::{BoxExt, ButtonExt, Cast, EditableExt, EntryExt, GridExt, GtkWindowExt, WidgetExt};
+
+use hadocrx::error::DbResult;
+use hadocrx::models::patient::{Patient, Sex};
+
+use super::avro_phonetic_entry::AvroPhoneticEntry;
+
+const SEXES: [&str; 4] = ["Not specified", "Male", "Female", "Other"];
+
+/// Form for registering a patient. The create button stays disabled until the entered details are valid.
+pub struct NewPatientDialog {
+    pub window: gtk::Window,
+    pub entry_name: AvroPhoneticEntry,
+    pub entry_date_of_birth: gtk::Entry,
+    pub age_years: gtk::SpinButton,
+    pub sex: gtk::DropDown,
+    pub entry_phone: gtk::Entry,
+    pub entry_address: AvroPhoneticEntry,
+    pub weight_kg: gtk::SpinButton,
+    pub entry_allergies: gtk::Entry,
+    pub entry_conditions: gtk::Entry,
+    pub label_error: gtk::Label,
+    pub btn_create: gtk::Button,
+}
+
+impl NewPatientDialog {
+    pub fn new(parent: &impl gtk::prelude::IsA<gtk::Window>) -> Rc<Self> {
+        let window = gtk::Window::builder()
+            .title("New Patient")
+            .transient_for(parent)
+            .modal(true)
+            .resizable(false)
+            .build();
+        let entry_name = AvroPhoneticEntry::new();
+        entry_name.set_placeholder_text(Some("Required"));
+        entry_name.set_width_chars(32);
+        let entry_date_of_birth = gtk::Entry::builder().placeholder_text("YYYY-MM-DD").build();
+        let age_years = gtk::SpinButton::with_range(0.0, 150.0, 1.0);
+        age_years.set_tooltip_text(Some("Leave at 0 when the date of birth is given"));
+        let sex = gtk::DropDown::from_strings(&SEXES);
+        let entry_phone = gtk::Entry::builder().placeholder_text("01XXX-XXXXXX").input_purpose(gtk::InputPurpose::Phone).build();
+        let entry_address = AvroPhoneticEntry::new();
+        let weight_kg = gtk::SpinButton::with_range(0.0, 300.0, 0.5);
+        weight_kg.set_digits(1);
+        weight_kg.set_tooltip_text(Some("Leave at 0 when unknown"));
+        let entry_allergies = gtk::Entry::builder().placeholder_text("Comma separated, e.g. Penicillin, Sulfa").build();
+        let entry_conditions = gtk::Entry::builder().placeholder_text("Comma separated, e.g. Asthma, Pregnancy").build();
+        let label_error = gtk::Label::builder()
+            .css_classes(["error"])
+            .halign(gtk::Align::Start)
+            .wrap(true).max_width_chars(48)
+            .visible(false)
+            .build();
+        let btn_create = gtk::Button::builder().label("Create").css_classes(["suggested-action"]).sensitive(false).build();
+
+        let grid = hadocrx_macros::gtk_grid!(
+            @column_spacing 16, @row_spacing 8, [
+                &super::label_left_aligned("Name"),
+                &entry_name,
+            ], [
+                &super::label_left_aligned("Date of birth"),
+                &entry_date_of_birth,
+            ], [
+                &super::label_left_aligned("Age (years)"),
+                &age_years,
+            ], [
+                &super::label_left_aligned("Sex"),
+                &sex,
+            ], [
+                &super::label_left_aligned("Phone"),
+                &entry_phone,
+            ], [
+                &super::label_left_aligned("Address"),
+                &entry_address,
+            ], [
+                &super::label_left_aligned("Weight (kg)"),
+                &weight_kg,
+            ], [
+                &super::label_left_aligned("Allergies"),
+                &entry_allergies,
+            ], [
+                &super::label_left_aligned("Conditions"),
+                &entry_conditions,
+            ]
+        );
+        let vbox = super::vbox();
+        vbox.set_spacing(8);
+        vbox.append(&grid);
+        vbox.append(&label_error);
+        vbox.append(&btn_create);
+        super::set_margins(vbox.upcast_ref(), 16);
+        window.set_child(Some(&vbox));
+
+        let dialog = Rc::new(Self {
+            window, entry_name, entry_date_of_birth, age_years, sex, entry_phone, entry_address,
+            weight_kg, entry_allergies, entry_conditions, label_error, btn_create,
+        });
+        dialog.connect_signals();
+        dialog
+    }
+
+    /// Shows the dialog. `on_create` receives the new patient and saves it, the dialog stays open
+    /// with the error shown when that fails.
+    pub fn present<F: Fn(Patient) -> DbResult<()> + 'static>(self: &Rc<Self>, on_create: F) {
+        let self_clone = self.clone();
+        self.btn_create.connect_clicked(move |_| {
+            let patient = self_clone.patient();
+            if patient.validate().is_err() { return; }
+            match on_create(patient) {
+                Ok(()) => self_clone.window.close(),
+                Err(err) => self_clone.show_error(Some(&err.to_string())),
+            }
+        });
+        self.window.present();
+    }
+
+    /// The patient described by the form, empty fields left unset.
+    pub fn patient(&self) -> Patient {
+        let optional_text = |text: String| Some(text.trim().to_string()).filter(|text| !text.is_empty());
+        let list = |text: String| text.split(',')
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<String>>();
+        let mut patient = Patient::new(self.entry_name.text().trim().to_string());
+        patient.date_of_birth = optional_text(self.entry_date_of_birth.text().to_string());
+        patient.age_years = Some(self.age_years.value() as u32).filter(|age| *age > 0);
+        patient.sex = match self.sex.selected() {
+            1 => Some(Sex::Male),
+            2 => Some(Sex::Female),
+            3 => Some(Sex::Other),
+            _ => None,
+        };
+        patient.phone = optional_text(self.entry_phone.text().to_string());
+        patient.address = optional_text(self.entry_address.text().to_string());
+        patient.weight_kg = Some(self.weight_kg.value()).filter(|weight| *weight > 0.0);
+        patient.allergies = list(self.entry_allergies.text().to_string());
+        patient.conditions = list(self.entry_conditions.text().to_string());
+        patient
+    }
+
+    fn connect_signals(self: &Rc<Self>) {
+        for entry in [
+            self.entry_name.upcast_ref::<gtk::Editable>(),
+            self.entry_date_of_birth.upcast_ref::<gtk::Editable>(),
+            self.entry_phone.upcast_ref::<gtk::Editable>(),
+        ] {
+            let self_clone = self.clone();
+            entry.connect_changed(move |_| self_clone.update_validation());
+        }
+        let self_clone = self.clone();
+        self.age_years.connect_value_changed(move |_| self_clone.update_validation());
+    }
+
+    fn update_validation(&self) {
+        let result = self.patient().validate();
+        self.btn_create.set_sensitive(result.is_ok());
+        // an empty form is not an error yet
+        let untouched = self.entry_name.text().trim().is_empty()
+            && self.entry_date_of_birth.text().is_empty()
+            && self.entry_phone.text().is_empty();
+        let message = result.err().filter(|_| !untouched).map(|err| err.to_string());
+        self.show_error(message.as_deref());
+    }
+
+    fn show_error(&self, message: Option<&str>) {
+        self.label_error.set_label(message.unwrap_or_default());
+        self.label_error.set_visible(message.is_some());
+    }
+}
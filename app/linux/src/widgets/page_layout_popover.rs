@@ -0,0 +1,107 @@
+use std::rc::Rc;
+
+use gtk::prelude::{CheckButtonExt, GridExt, PopoverExt, WidgetExt};
+
+use hadocrx::pdf::{Margins, PageLayout, PaperSize};
+
+/// Header bar menu for the paper size and margins prescriptions are printed with. Unchecking the
+/// letterhead and widening the top margin lines the print up with pre-printed pads.
+pub struct PageLayoutPopover {
+    pub button: gtk::MenuButton,
+    pub paper: gtk::DropDown,
+    pub margin_top: gtk::SpinButton,
+    pub margin_bottom: gtk::SpinButton,
+    pub margin_left: gtk::SpinButton,
+    pub margin_right: gtk::SpinButton,
+    pub print_letterhead: gtk::CheckButton,
+}
+
+impl PageLayoutPopover {
+    pub fn new(layout: &PageLayout) -> Rc<Self> {
+        let paper_names: Vec<&str> = PaperSize::ALL.iter().map(|paper| paper.as_str()).collect();
+        let paper = gtk::DropDown::from_strings(&paper_names);
+        let margin_spin_button = || {
+            let spin_button = gtk::SpinButton::with_range(0.0, 150.0, 1.0);
+            spin_button.set_digits(0);
+            spin_button
+        };
+        let margin_top = margin_spin_button();
+        let margin_bottom = margin_spin_button();
+        let margin_left = margin_spin_button();
+        let margin_right = margin_spin_button();
+        let print_letterhead = gtk::CheckButton::with_label("Print letterhead");
+        print_letterhead.set_tooltip_text(Some("Uncheck when printing on pre-printed pads"));
+
+        let grid = hadocrx_macros::gtk_grid!(
+            @margin_top 8, @margin_bottom 8, @margin_start 8, @margin_end 8,
+            @column_spacing 16, @row_spacing 8, [
+                &super::label_left_aligned("Paper"),
+                &paper,
+            ], [
+                &super::label_left_aligned("Top margin (mm)"),
+                &margin_top,
+            ], [
+                &super::label_left_aligned("Bottom margin (mm)"),
+                &margin_bottom,
+            ], [
+                &super::label_left_aligned("Left margin (mm)"),
+                &margin_left,
+            ], [
+                &super::label_left_aligned("Right margin (mm)"),
+                &margin_right,
+            ], [
+                &print_letterhead,
+            ]
+        );
+        let popover = gtk::Popover::new();
+        popover.set_child(Some(&grid));
+        let button = gtk::MenuButton::builder()
+            .icon_name("document-page-setup")
+            .tooltip_text("Page setup")
+            .popover(&popover)
+            .build();
+
+        let page_layout_popover = Rc::new(Self {
+            button, paper, margin_top, margin_bottom, margin_left, margin_right, print_letterhead
+        });
+        page_layout_popover.set_layout(layout);
+        page_layout_popover
+    }
+
+    pub fn layout(&self) -> PageLayout {
+        PageLayout {
+            paper: PaperSize::ALL.get(self.paper.selected() as usize).copied().unwrap_or_default(),
+            margins: Margins {
+                top: self.margin_top.value() as f32,
+                bottom: self.margin_bottom.value() as f32,
+                left: self.margin_left.value() as f32,
+                right: self.margin_right.value() as f32,
+            },
+            print_letterhead: self.print_letterhead.is_active(),
+        }
+    }
+
+    pub fn set_layout(&self, layout: &PageLayout) {
+        let paper_index = PaperSize::ALL.iter().position(|paper| *paper == layout.paper).unwrap_or_default();
+        self.paper.set_selected(paper_index as u32);
+        self.margin_top.set_value(layout.margins.top as f64);
+        self.margin_bottom.set_value(layout.margins.bottom as f64);
+        self.margin_left.set_value(layout.margins.left as f64);
+        self.margin_right.set_value(layout.margins.right as f64);
+        self.print_letterhead.set_active(layout.print_letterhead);
+    }
+
+    /// Calls `on_changed` with the new layout whenever any setting changes.
+    pub fn connect_changed<F: Fn(PageLayout) + 'static>(self: &Rc<Self>, on_changed: F) {
+        let self_clone = self.clone();
+        let notify = Rc::new(move || on_changed(self_clone.layout()));
+        let notify_clone = notify.clone();
+        self.paper.connect_selected_notify(move |_| notify_clone());
+        for spin_button in [&self.margin_top, &self.margin_bottom, &self.margin_left, &self.margin_right] {
+            let notify_clone = notify.clone();
+            spin_button.connect_value_changed(move |_| notify_clone());
+        }
+        let notify_clone = notify.clone();
+        self.print_letterhead.connect_toggled(move |_| notify_clone());
+    }
+}
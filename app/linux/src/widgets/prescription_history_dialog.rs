@@ -0,0 +1,173 @@
+use std::rc::Rc;
+
+use gtk::prelude::{BoxExt, ButtonExt, Cast, GtkWindowExt, ListBoxRowExt, WidgetExt};
+
+use hadocrx::models::prescription::Prescription;
+use hadocrx::pdf::{format_date, medicine_title};
+
+/// Past prescriptions of a patient, newest first, with a preview of the selected one. A prescription
+/// can be opened read-only or repeated as a new prescription dated today.
+pub struct PrescriptionHistoryDialog {
+    pub window: gtk::Window,
+    pub list_box: gtk::ListBox,
+    pub preview: gtk::Label,
+    pub btn_open: gtk::Button,
+    pub btn_repeat: gtk::Button,
+    prescriptions: Vec<Prescription>,
+}
+
+impl PrescriptionHistoryDialog {
+    pub fn new(parent: &impl gtk::prelude::IsA<gtk::Window>, patient_name: &str, prescriptions: Vec<Prescription>) -> Rc<Self> {
+        let window = gtk::Window::builder()
+            .title(format!("Prescriptions of {}", patient_name))
+            .transient_for(parent)
+            .modal(true)
+            .default_width(720)
+            .default_height(480)
+            .build();
+        let list_box = gtk::ListBox::builder().selection_mode(gtk::SelectionMode::Browse).build();
+        list_box.set_placeholder(Some(&gtk::Label::builder()
+            .label("No prescriptions yet")
+            .css_classes(["dim-label"])
+            .margin_top(8).margin_bottom(8)
+            .build()));
+        for prescription in prescriptions.iter() {
+            list_box.append(&Self::prescription_row(prescription));
+        }
+        let preview = gtk::Label::builder()
+            .halign(gtk::Align::Start)
+            .valign(gtk::Align::Start)
+            .wrap(true)
+            .selectable(true)
+            .margin_top(8).margin_bottom(8).margin_start(12).margin_end(12)
+            .build();
+        let btn_open = gtk::Button::builder()
+            .label("Open")
+            .tooltip_text("Show the prescription in a new read-only tab")
+            .sensitive(false)
+            .build();
+        let btn_repeat = gtk::Button::builder()
+            .label("Repeat")
+            .tooltip_text("Copy the prescription into a new tab dated today")
+            .css_classes(["suggested-action"])
+            .sensitive(false)
+            .build();
+
+        let paned = gtk::Paned::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .start_child(&gtk::ScrolledWindow::builder().child(&list_box).min_content_width(200).build())
+            .end_child(&gtk::ScrolledWindow::builder().child(&preview).hexpand(true).build())
+            .position(220)
+            .vexpand(true)
+            .build();
+        let buttons = hadocrx_macros::gtk_box!(
+            @orientation gtk::Orientation::Horizontal, @spacing 8, @halign gtk::Align::End,
+            btn_open.clone(), btn_repeat.clone()
+        );
+        let vbox = super::vbox();
+        vbox.set_spacing(8);
+        vbox.append(&paned);
+        vbox.append(&buttons);
+        super::set_margins(vbox.upcast_ref(), 16);
+        window.set_child(Some(&vbox));
+
+        let dialog = Rc::new(Self { window, list_box, preview, btn_open, btn_repeat, prescriptions });
+        let self_clone = dialog.clone();
+        dialog.list_box.connect_row_selected(move |_, row| self_clone.update_preview(row.map(|row| row.index())));
+        if let Some(row) = dialog.list_box.row_at_index(0) {
+            dialog.list_box.select_row(Some(&row));
+        }
+        dialog
+    }
+
+    /// Shows the dialog. `on_open` receives the prescription to show read-only, `on_repeat` the one to copy.
+    pub fn present<O: Fn(Prescription) + 'static, R: Fn(Prescription) + 'static>(self: &Rc<Self>, on_open: O, on_repeat: R) {
+        let on_open = Rc::new(on_open);
+        let self_clone = self.clone();
+        let on_open_clone = on_open.clone();
+        self.btn_open.connect_clicked(move |_| {
+            let Some(prescription) = self_clone.selected() else { return; };
+            self_clone.window.close();
+            on_open_clone(prescription);
+        });
+        let self_clone = self.clone();
+        self.list_box.connect_row_activated(move |_, _| {
+            let Some(prescription) = self_clone.selected() else { return; };
+            self_clone.window.close();
+            on_open(prescription);
+        });
+        let self_clone = self.clone();
+        self.btn_repeat.connect_clicked(move |_| {
+            let Some(prescription) = self_clone.selected() else { return; };
+            self_clone.window.close();
+            on_repeat(prescription);
+        });
+        self.window.present();
+    }
+
+    fn selected(&self) -> Option<Prescription> {
+        let row = self.list_box.selected_row()?;
+        self.prescriptions.get(row.index() as usize).cloned()
+    }
+
+    fn update_preview(&self, index: Option<i32>) {
+        let prescription = index.and_then(|index| self.prescriptions.get(index as usize));
+        self.btn_open.set_sensitive(prescription.is_some());
+        self.btn_repeat.set_sensitive(prescription.is_some());
+        self.preview.set_markup(&prescription.map(Self::preview_markup).unwrap_or_default());
+    }
+
+    fn prescription_row(prescription: &Prescription) -> gtk::Box {
+        let medicines = prescription.items.iter()
+            .map(|item| item.brand_name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ");
+        let label_date = gtk::Label::builder()
+            .label(format_date(&prescription.date))
+            .css_classes(["heading"])
+            .halign(gtk::Align::Start)
+            .build();
+        let label_medicines = gtk::Label::builder()
+            .label(if medicines.is_empty() { "No medicines".to_string() } else { medicines })
+            .css_classes(["caption", "dim-label"])
+            .halign(gtk::Align::Start)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .max_width_chars(28)
+            .build();
+        hadocrx_macros::gtk_box!(
+            @orientation gtk::Orientation::Vertical,
+            @margin_top 6, @margin_bottom 6, @margin_start 8, @margin_end 8,
+            label_date, label_medicines
+        )
+    }
+
+    /// The prescription laid out roughly as printed.
+    fn preview_markup(prescription: &Prescription) -> String {
+        let escape = |text: &str| gtk::glib::markup_escape_text(text).to_string();
+        let mut markup = format!("<b>Date:</b> {}", format_date(&prescription.date));
+        for (heading, text) in [("C/C", &prescription.chief_complaints), ("Dx", &prescription.diagnosis)] {
+            if !text.trim().is_empty() {
+                markup.push_str(&format!("\n<b>{}:</b> {}", heading, escape(text.trim())));
+            }
+        }
+        markup.push_str("\n\n<b>Rx</b>");
+        for (index, item) in prescription.items.iter().enumerate() {
+            markup.push_str(&format!("\n{}. {}", index + 1, escape(&medicine_title(item))));
+            let details = [item.dosing.trim(), item.instructions.trim(), item.duration.trim()]
+                .into_iter()
+                .filter(|detail| !detail.is_empty())
+                .collect::<Vec<&str>>()
+                .join(" · ");
+            if !details.is_empty() {
+                markup.push_str(&format!("\n    {}", escape(&details)));
+            }
+        }
+        if !prescription.advice.trim().is_empty() {
+            markup.push_str(&format!("\n\n<b>Advice</b>\n{}", escape(prescription.advice.trim())));
+        }
+        if let Some(follow_up_date) = prescription.follow_up_date.as_deref() {
+            markup.push_str(&format!("\n\n<b>Follow-up:</b> {}", format_date(follow_up_date)));
+        }
+        markup
+    }
+}
@@ -0,0 +1,117 @@
+use std::rc::Rc;
+
+use gtk::prelude::{BoxExt, ButtonExt, Cast, CheckButtonExt, GtkWindowExt, WidgetExt};
+
+use hadocrx::models::draft::Draft;
+use hadocrx::pdf::format_date;
+
+/// Shown on launch when the last run left prescriptions unsaved, whether it was closed with tabs open
+/// or ended by a crash or a power cut. Lists the autosaved prescriptions with their patient and time,
+/// all ticked for restoring.
+pub struct RestoreDraftsDialog {
+    pub window: gtk::Window,
+    pub btn_discard: gtk::Button,
+    pub btn_restore: gtk::Button,
+    /// One per draft, in the same order as `drafts`.
+    check_buttons: Vec<gtk::CheckButton>,
+    drafts: Vec<Draft>,
+}
+
+impl RestoreDraftsDialog {
+    pub fn new(parent: &impl gtk::prelude::IsA<gtk::Window>, drafts: Vec<Draft>) -> Rc<Self> {
+        let window = gtk::Window::builder()
+            .title("Restore Unsaved Prescriptions")
+            .transient_for(parent)
+            .modal(true)
+            .deletable(false)
+            .default_width(480)
+            .build();
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        let check_buttons: Vec<gtk::CheckButton> = drafts.iter().map(|draft| {
+            let check_button = gtk::CheckButton::builder().active(true).child(&Self::draft_row(draft)).build();
+            list_box.append(&check_button);
+            check_button
+        }).collect();
+        let btn_discard = gtk::Button::builder()
+            .label("Discard All")
+            .tooltip_text("Delete every unsaved prescription")
+            .css_classes(["destructive-action"])
+            .build();
+        let btn_restore = gtk::Button::builder()
+            .label("Restore")
+            .tooltip_text("Open the ticked prescriptions in tabs, the others are deleted")
+            .css_classes(["suggested-action"])
+            .build();
+
+        let buttons = hadocrx_macros::gtk_box!(
+            @orientation gtk::Orientation::Horizontal, @spacing 8, @halign gtk::Align::End,
+            btn_discard.clone(), btn_restore.clone()
+        );
+        let vbox = super::vbox();
+        vbox.set_spacing(8);
+        vbox.append(&super::label_with_class("These prescriptions were not saved when HadocRx was last closed:", None));
+        vbox.append(&gtk::ScrolledWindow::builder()
+            .child(&list_box)
+            .propagate_natural_height(true)
+            .max_content_height(360)
+            .build());
+        vbox.append(&buttons);
+        super::set_margins(vbox.upcast_ref(), 16);
+        window.set_child(Some(&vbox));
+
+        Rc::new(Self { window, btn_discard, btn_restore, check_buttons, drafts })
+    }
+
+    /// Shows the dialog. `on_close` receives the drafts to restore and the drafts to delete.
+    pub fn present<F: Fn(Vec<Draft>, Vec<Draft>) + 'static>(self: &Rc<Self>, on_close: F) {
+        let on_close = Rc::new(on_close);
+        let self_clone = self.clone();
+        let on_close_clone = on_close.clone();
+        self.btn_restore.connect_clicked(move |_| {
+            let (restore, discard) = self_clone.drafts.iter().cloned()
+                .zip(self_clone.check_buttons.iter())
+                .partition::<Vec<(Draft, &gtk::CheckButton)>, _>(|(_, check_button)| check_button.is_active());
+            self_clone.window.close();
+            on_close_clone(
+                restore.into_iter().map(|(draft, _)| draft).collect(),
+                discard.into_iter().map(|(draft, _)| draft).collect(),
+            );
+        });
+        let self_clone = self.clone();
+        self.btn_discard.connect_clicked(move |_| {
+            self_clone.window.close();
+            on_close(Vec::new(), self_clone.drafts.clone());
+        });
+        self.window.present();
+        self.btn_restore.grab_focus();
+    }
+
+    fn draft_row(draft: &Draft) -> gtk::Box {
+        let name = if draft.patient_name.is_empty() { "No patient" } else { draft.patient_name.as_str() };
+        let time = draft.saved_at.get(11..16).unwrap_or_default();
+        let medicines = draft.prescription.items.iter()
+            .map(|item| item.brand_name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ");
+        let label_name = gtk::Label::builder()
+            .label(format!("{} · {} {}", name, format_date(&draft.saved_at), time))
+            .css_classes(["heading"])
+            .halign(gtk::Align::Start)
+            .build();
+        let label_medicines = gtk::Label::builder()
+            .label(if medicines.is_empty() { "No medicines".to_string() } else { medicines })
+            .css_classes(["caption", "dim-label"])
+            .halign(gtk::Align::Start)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .max_width_chars(48)
+            .build();
+        hadocrx_macros::gtk_box!(
+            @orientation gtk::Orientation::Vertical,
+            @margin_top 6, @margin_bottom 6, @margin_start 8, @margin_end 8,
+            label_name, label_medicines
+        )
+    }
+}
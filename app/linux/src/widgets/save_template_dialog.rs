@@ -0,0 +1,76 @@
+use std::rc::Rc;
+
+use gtk::prelude::{BoxExt, ButtonExt, Cast, EditableExt, GtkWindowExt, WidgetExt};
+
+/// Asks for the name the current prescription is saved under as a template.
+pub struct SaveTemplateDialog {
+    pub window: gtk::Window,
+    pub entry_name: gtk::Entry,
+    pub label_note: gtk::Label,
+    pub btn_save: gtk::Button,
+    existing_names: Vec<String>,
+}
+
+impl SaveTemplateDialog {
+    /// `existing_names` are the templates already saved, the user is told when a name would replace one.
+    pub fn new(parent: &impl gtk::prelude::IsA<gtk::Window>, summary: &str, existing_names: Vec<String>) -> Rc<Self> {
+        let window = gtk::Window::builder()
+            .title("Save Template")
+            .transient_for(parent)
+            .modal(true)
+            .resizable(false)
+            .build();
+        let entry_name = gtk::Entry::builder()
+            .placeholder_text("Template name, e.g. Acute gastroenteritis")
+            .activates_default(true)
+            .width_chars(36)
+            .build();
+        let label_summary = gtk::Label::builder()
+            .label(summary)
+            .css_classes(["caption"])
+            .halign(gtk::Align::Start)
+            .wrap(true).max_width_chars(48)
+            .build();
+        let label_note = gtk::Label::builder()
+            .label("A template with this name exists and will be replaced")
+            .css_classes(["warning"])
+            .halign(gtk::Align::Start)
+            .visible(false)
+            .build();
+        let btn_save = gtk::Button::builder().label("Save").css_classes(["suggested-action"]).sensitive(false).build();
+
+        let vbox = super::vbox();
+        vbox.set_spacing(8);
+        vbox.append(&entry_name);
+        vbox.append(&label_summary);
+        vbox.append(&label_note);
+        vbox.append(&btn_save);
+        super::set_margins(vbox.upcast_ref(), 16);
+        window.set_child(Some(&vbox));
+        window.set_default_widget(Some(&btn_save));
+
+        let dialog = Rc::new(Self { window, entry_name, label_note, btn_save, existing_names });
+        let self_clone = dialog.clone();
+        dialog.entry_name.connect_changed(move |_| self_clone.update_note());
+        dialog
+    }
+
+    /// Shows the dialog, `on_save` receives the trimmed template name.
+    pub fn present<F: Fn(String) + 'static>(self: &Rc<Self>, on_save: F) {
+        let self_clone = self.clone();
+        self.btn_save.connect_clicked(move |_| {
+            let name = self_clone.entry_name.text().trim().to_string();
+            if name.is_empty() { return; }
+            on_save(name);
+            self_clone.window.close();
+        });
+        self.window.present();
+    }
+
+    fn update_note(&self) {
+        let name = self.entry_name.text().trim().to_lowercase();
+        self.btn_save.set_sensitive(!name.is_empty());
+        let replaces = self.existing_names.iter().any(|existing| existing.to_lowercase() == name);
+        self.label_note.set_visible(replaces);
+    }
+}
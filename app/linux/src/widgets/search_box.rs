@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use gtk::{glib::{self, subclass::types::ObjectSubclassIsExt}, prelude::{EditableExt, PopoverExt, WidgetExt}};
 
 mod imp {
@@ -8,6 +10,7 @@ mod imp {
     use gtk::subclass::prelude::*;
 
     use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
 
     #[derive(Debug, Default, gtk::CompositeTemplate)]
     #[template(string = r#"
@@ -33,7 +36,8 @@ mod imp {
         #[template_child]
         pub popover: TemplateChild<gtk::Popover>,
         
-        pub data: RefCell<Vec<String>>,
+        /// Shared rather than copied, every tab searches the same drug names.
+        pub data: RefCell<Rc<[String]>>,
         pub expected_programmatic_change: RefCell<Option<String>>,
         pub signals_connected: Cell<bool>,
     }
@@ -235,7 +239,7 @@ impl SearchBox {
         glib::Object::builder().build()
     }
 
-    pub fn initialize(&self, data: Vec<String>) {
+    pub fn initialize(&self, data: Rc<[String]>) {
         let imp = self.imp();
         imp.data.replace(data);
         imp.setup_signals();
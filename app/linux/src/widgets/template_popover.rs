@@ -0,0 +1,174 @@
+use std::{cell::RefCell, rc::Rc};
+
+use gtk::prelude::{BoxExt, ButtonExt, EditableExt, ListBoxRowExt, PopoverExt, WidgetExt};
+
+use hadocrx::models::template::PrescriptionTemplate;
+
+type ApplyCallback = Box<dyn Fn(PrescriptionTemplate)>;
+type ErrorCallback = Box<dyn Fn(&str, String)>;
+
+/// Header bar menu for searching the saved prescription templates and applying one, along with
+/// saving the current prescription as a template and sharing templates as JSON files.
+pub struct TemplatePopover {
+    pub button: gtk::MenuButton,
+    pub popover: gtk::Popover,
+    pub search_entry: gtk::SearchEntry,
+    pub list_box: gtk::ListBox,
+    pub btn_save: gtk::Button,
+    pub btn_import: gtk::Button,
+    pub btn_export: gtk::Button,
+    templates: RefCell<Vec<PrescriptionTemplate>>,
+    on_apply: RefCell<Option<ApplyCallback>>,
+    on_error: RefCell<Option<ErrorCallback>>,
+}
+
+impl TemplatePopover {
+    pub fn new() -> Rc<Self> {
+        let search_entry = gtk::SearchEntry::builder().placeholder_text("Search templates").build();
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Browse)
+            .activate_on_single_click(true)
+            .build();
+        list_box.set_placeholder(Some(&gtk::Label::builder()
+            .label("No templates")
+            .css_classes(["dim-label"])
+            .margin_top(8).margin_bottom(8)
+            .build()));
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .child(&list_box)
+            .propagate_natural_height(true)
+            .max_content_height(360)
+            .min_content_width(320)
+            .build();
+        let btn_save = gtk::Button::builder().label("Save Current…").tooltip_text("Save the medicines and advice as a template").build();
+        let btn_import = gtk::Button::builder().label("Import…").build();
+        let btn_export = gtk::Button::builder().label("Export…").build();
+
+        let vbox = super::vbox();
+        vbox.set_spacing(8);
+        vbox.append(&search_entry);
+        vbox.append(&scrolled_window);
+        vbox.append(&hadocrx_macros::gtk_box!(
+            @orientation gtk::Orientation::Horizontal, @spacing 4, @homogeneous true,
+            btn_save.clone(), btn_import.clone(), btn_export.clone()
+        ));
+        let popover = gtk::Popover::new();
+        popover.set_child(Some(&vbox));
+        let button = gtk::MenuButton::builder()
+            .icon_name("view-list")
+            .tooltip_text("Templates")
+            .popover(&popover)
+            .build();
+
+        let template_popover = Rc::new(Self {
+            button, popover, search_entry, list_box, btn_save, btn_import, btn_export,
+            templates: RefCell::new(Vec::new()),
+            on_apply: RefCell::new(None),
+            on_error: RefCell::new(None),
+        });
+        template_popover.connect_signals();
+        template_popover
+    }
+
+    /// Calls `on_apply` with the template the user picks from the list.
+    pub fn connect_apply<F: Fn(PrescriptionTemplate) + 'static>(&self, on_apply: F) {
+        self.on_apply.replace(Some(Box::new(on_apply)));
+    }
+
+    /// Calls `on_error` with a message and detail when the templates cannot be loaded or deleted.
+    pub fn connect_error<F: Fn(&str, String) + 'static>(&self, on_error: F) {
+        self.on_error.replace(Some(Box::new(on_error)));
+    }
+
+    fn connect_signals(self: &Rc<Self>) {
+        let self_clone = self.clone();
+        self.popover.connect_show(move |_| {
+            self_clone.search_entry.set_text("");
+            self_clone.refresh();
+            self_clone.search_entry.grab_focus();
+        });
+
+        let self_clone = self.clone();
+        self.search_entry.connect_search_changed(move |_| self_clone.refresh());
+
+        let self_clone = self.clone();
+        self.search_entry.connect_activate(move |_| {
+            if let Some(row) = self_clone.list_box.row_at_index(0) {
+                row.activate();
+            }
+        });
+
+        for button in [&self.btn_save, &self.btn_import, &self.btn_export] {
+            let popover = self.popover.clone();
+            button.connect_clicked(move |_| popover.popdown());
+        }
+
+        let self_clone = self.clone();
+        self.list_box.connect_row_activated(move |_, row| {
+            let Some(template) = self_clone.templates.borrow().get(row.index() as usize).cloned() else { return; };
+            self_clone.popover.popdown();
+            if let Some(on_apply) = self_clone.on_apply.borrow().as_ref() {
+                on_apply(template);
+            }
+        });
+    }
+
+    /// Reloads the templates matching the search text, best match first.
+    fn refresh(self: &Rc<Self>) {
+        let query = self.search_entry.text().to_string();
+        let templates = match hadocrx::patient_db::patient_db().and_then(|db| db.find_templates(&query)) {
+            Ok(templates) => templates,
+            Err(err) => {
+                self.report_error("Unable to load the templates!", err.to_string());
+                Vec::new()
+            }
+        };
+        self.list_box.remove_all();
+        for template in templates.iter() {
+            self.list_box.append(&self.template_row(template));
+        }
+        self.templates.replace(templates);
+    }
+
+    fn template_row(self: &Rc<Self>, template: &PrescriptionTemplate) -> gtk::Box {
+        let medicines = template.items.iter()
+            .map(|item| item.brand_name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ");
+        let label_name = gtk::Label::builder().label(&template.name).css_classes(["heading"]).halign(gtk::Align::Start).build();
+        let label_medicines = gtk::Label::builder()
+            .label(&medicines)
+            .css_classes(["caption", "dim-label"])
+            .halign(gtk::Align::Start)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .max_width_chars(40)
+            .build();
+        let btn_delete = super::custom_icon_button("fa-arrow-delete");
+        btn_delete.set_valign(gtk::Align::Center);
+        btn_delete.set_tooltip_text(Some("Delete template"));
+        let id = template.id;
+        let self_clone = self.clone();
+        btn_delete.connect_clicked(move |_| {
+            if let Err(err) = hadocrx::patient_db::patient_db().and_then(|db| db.delete_template(id)) {
+                self_clone.report_error("Unable to delete the template!", err.to_string());
+            }
+            self_clone.refresh();
+        });
+
+        let vbox = hadocrx_macros::gtk_box!(
+            @orientation gtk::Orientation::Vertical, @hexpand true,
+            label_name, label_medicines
+        );
+        hadocrx_macros::gtk_box!(
+            @orientation gtk::Orientation::Horizontal, @spacing 8,
+            @margin_top 4, @margin_bottom 4, @margin_start 4, @margin_end 4,
+            vbox, btn_delete
+        )
+    }
+
+    fn report_error(&self, message: &str, detail: String) {
+        if let Some(on_error) = self.on_error.borrow().as_ref() {
+            on_error(message, detail);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::{env, path::PathBuf, sync::OnceLock};
+
+use crate::error::{DbError, DbResult};
+
+/// Directory holding every database, overrides the XDG default.
+pub const DATA_DIR_ENV: &str = "HADOCRX_DATA_DIR";
+/// Path of the drugs database, overrides `HADOCRX_DATA_DIR` for this file only.
+pub const DRUGS_DB_ENV: &str = "HADOCRX_DRUGS_DB";
+/// Path of the patients database, overrides `HADOCRX_DATA_DIR` for this file only.
+pub const PATIENTS_DB_ENV: &str = "HADOCRX_PATIENTS_DB";
+
+const APP_DIR_NAME: &str = "hadocrx";
+
+static DB_CONFIG: OnceLock<DbConfig> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbConfig {
+    pub drugs_db_path: PathBuf,
+    pub patients_db_path: PathBuf,
+    /// JSON file holding the clinic letterhead printed on prescriptions.
+    pub letterhead_path: PathBuf,
+    /// JSON file holding the paper size and margins prescriptions are printed with.
+    pub page_layout_path: PathBuf,
+}
+
+impl DbConfig {
+    /// Keeps both databases and the print settings in `data_dir`.
+    pub fn with_data_dir(data_dir: PathBuf) -> Self {
+        Self {
+            drugs_db_path: data_dir.join("drugs.db"),
+            patients_db_path: data_dir.join("patients.db"),
+            letterhead_path: data_dir.join("letterhead.json"),
+            page_layout_path: data_dir.join("page_layout.json"),
+        }
+    }
+
+    /// Resolves the database paths from the environment, in order of precedence:
+    /// the per-file variables, `HADOCRX_DATA_DIR`, then the XDG data directory.
+    pub fn from_env() -> DbResult<Self> {
+        let data_dir = match env_path(DATA_DIR_ENV) {
+            Some(data_dir) => data_dir,
+            None => default_data_dir()?,
+        };
+        let mut config = Self::with_data_dir(data_dir);
+        if let Some(path) = env_path(DRUGS_DB_ENV) {
+            config.drugs_db_path = path;
+        }
+        if let Some(path) = env_path(PATIENTS_DB_ENV) {
+            config.patients_db_path = path;
+        }
+        Ok(config)
+    }
+}
+
+/// Sets the paths used by the default repositories. Must be called before the first database
+/// is opened; returns the rejected config if one has already been set.
+pub fn init(config: DbConfig) -> Result<(), DbConfig> {
+    DB_CONFIG.set(config)
+}
+
+/// The config passed to [`init`], or the one resolved from the environment otherwise.
+pub fn current() -> DbResult<&'static DbConfig> {
+    if let Some(config) = DB_CONFIG.get() {
+        return Ok(config);
+    }
+    let config = DbConfig::from_env()?;
+    Ok(DB_CONFIG.get_or_init(|| config))
+}
+
+/// `$XDG_DATA_HOME/hadocrx`, falling back to `~/.local/share/hadocrx` (or `%APPDATA%\hadocrx` on Windows).
+pub fn default_data_dir() -> DbResult<PathBuf> {
+    if let Some(data_home) = env_path("XDG_DATA_HOME").filter(|path| path.is_absolute()) {
+        return Ok(data_home.join(APP_DIR_NAME));
+    }
+    if let Some(home) = env_path("HOME") {
+        return Ok(home.join(".local").join("share").join(APP_DIR_NAME));
+    }
+    if let Some(app_data) = env_path("APPDATA") {
+        return Ok(app_data.join(APP_DIR_NAME));
+    }
+    Err(DbError::InvalidInput("unable to determine a data directory, set HADOCRX_DATA_DIR".to_string()))
+}
+
+fn env_path(key: &str) -> Option<PathBuf> {
+    env::var_os(key).filter(|value| !value.is_empty()).map(PathBuf::from)
+}
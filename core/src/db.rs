@@ -1,17 +1,41 @@
 #![allow(dead_code)]
 
-use std::{cell::RefCell, env::current_exe, ffi::{c_char, CStr, CString}, fs, sync::{Arc, Mutex, OnceLock}};
+use std::{cell::RefCell, ffi::{c_char, CStr, CString}, fs, path::{Path, PathBuf}, ptr::NonNull, sync::{Mutex, MutexGuard, OnceLock}, thread::LocalKey};
 use ffi_convert::{CReprOf, CStringArray};
-use rusqlite::{Connection, OpenFlags};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Row};
 use rusqlite_migration::{Migrations, M};
 
+use crate::config;
+use crate::dose_limits::{self, DoseLimit, DoseWarning};
+use crate::dosing::DosingSchedule;
+use crate::error::{DbError, DbResult};
+use crate::ffi::prescription::MedicineData;
+use crate::interactions::{self, Interaction, InteractionWarning, Severity};
+use crate::models::drug::{Drug, DrugAlternative, DrugClass};
+use crate::pricing::{self, CostEstimate};
+use crate::safety::{self, AllergyWarning, ContraindicationWarning};
+use crate::strength::{self, Strength};
+
 const DRUGS_MIGRATION_SLICE: &[M] = &[
     M::up(include_str!("./migrations/drugs_V01.sql")),
+    M::up(include_str!("./migrations/drugs_V02.sql")),
+    M::up(include_str!("./migrations/drugs_V03.sql")),
+    M::up(include_str!("./migrations/drugs_V04.sql")),
+    M::up(include_str!("./migrations/drugs_V05.sql")),
 ];
 
 const DRUGS_MIGRATIONS: Migrations = Migrations::from_slice(DRUGS_MIGRATION_SLICE);
 
-static DRUGS_DB_CONN: OnceLock<Arc<Mutex<Connection>>> = OnceLock::new();
+const DRUG_SELECT: &str = "
+    SELECT d.id, d.brand_name, g.name, s.value, do.value, m.name, p.value, d.dar FROM Drugs d
+    LEFT JOIN Generics g ON g.id == d.generic_id
+    LEFT JOIN Strengths s ON s.id == d.strength_id
+    LEFT JOIN Dosages do ON do.id == d.dosage_id
+    LEFT JOIN Manufacturers m ON m.id == d.manufacturer_id
+    LEFT JOIN Prices p ON p.id == d.price_id
+";
+
+static DRUG_REPOSITORY: OnceLock<Mutex<DrugRepository>> = OnceLock::new();
 
 thread_local! {
     static LAST_GENERIC_NAME: RefCell<Option<CString>> = RefCell::new(None);
@@ -19,202 +43,547 @@ thread_local! {
     static LAST_MANUFACTURER_NAME: RefCell<Option<CString>> = RefCell::new(None);
 }
 
-fn get_drugs_db_connection() -> &'static Arc<Mutex<Connection>> {
-    let exe_path = current_exe().unwrap();
-    let exe_dir = exe_path.parent().unwrap();
-    let db_dir = exe_dir.join("database");
-    let db_path = db_dir.join("drugs.db");
-    
-    if !db_path.exists() {
-        println!("Initializing drugs database... Please wait...");
-        fs::create_dir_all(&db_dir).expect("Error creating directory");
-        fs::File::create(&db_path).expect("Error creating file");
+/// Read-only access to `drugs.db`. Lookups that find nothing return `None` or an empty list
+/// instead of an error, so unknown input typed by the user is never fatal.
+pub struct DrugRepository {
+    conn: Connection,
+}
+
+impl DrugRepository {
+    pub fn default_path() -> DbResult<PathBuf> {
+        Ok(config::current()?.drugs_db_path.clone())
+    }
+
+    pub fn open_default() -> DbResult<Self> {
+        Self::open(&Self::default_path()?)
+    }
+
+    /// Creates and migrates the database if needed, then reopens it read only.
+    pub fn open(path: &Path) -> DbResult<Self> {
+        if !path.exists() {
+            println!("Initializing drugs database... Please wait...");
+            if let Some(db_dir) = path.parent() {
+                fs::create_dir_all(db_dir)?;
+            }
+        }
+
+        // apply pending migrations
+        let mut conn = Connection::open(path)?;
+        conn.pragma_update(None, "foreign_keys", "on")?;
+        conn.pragma_update(None, "journal_mode", "wal")?;
+        DRUGS_MIGRATIONS.to_latest(&mut conn)?;
+        conn.close().map_err(|(_, err)| err)?;
+
+        // open the database read only
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        Ok(Self { conn })
+    }
+
+    /// A migrated database that only lives as long as the repository, e.g. for tests.
+    pub fn open_in_memory() -> DbResult<Self> {
+        let mut conn = Connection::open_in_memory()?;
+        conn.pragma_update(None, "foreign_keys", "on")?;
+        DRUGS_MIGRATIONS.to_latest(&mut conn)?;
+        Ok(Self { conn })
+    }
+
+    pub fn get_drug(&self, id: i64) -> DbResult<Option<Drug>> {
+        let drug = self.conn
+            .query_row(&format!("{} WHERE d.id = ?1", DRUG_SELECT), [id], Self::drug_from_row)
+            .optional()?;
+        Ok(drug)
+    }
+
+    pub fn get_drugs_by_brand_name(&self, brand_name: &str) -> DbResult<Vec<Drug>> {
+        self.query_drugs(&format!("{} WHERE d.brand_name = ?1 ORDER BY s.value, do.value", DRUG_SELECT), [brand_name])
+    }
+
+    pub fn get_drugs_by_generic_name(&self, generic_name: &str) -> DbResult<Vec<Drug>> {
+        self.query_drugs(&format!("{} WHERE g.name = ?1 ORDER BY d.brand_name, s.value", DRUG_SELECT), [generic_name])
+    }
+
+    /// Full-text search over brand, generic, manufacturer, strength and dosage form, best matches first.
+    /// Every word of `query` must match the start of a word in the drug, so `"napa 500 tab"` finds Napa 500 mg tablets.
+    pub fn search_drugs(&self, query: &str, limit: usize) -> DbResult<Vec<Drug>> {
+        let Some(match_expr) = Self::fts_match_expression(query) else { return Ok(Vec::new()); };
+        // brands whose first word is exactly the first query word rank above mere prefix matches
+        let first_word = Self::like_literal(query.split_whitespace().next().unwrap_or_default());
+        self.query_drugs(&format!("
+            {}
+            JOIN Drugs_Search ON Drugs_Search.rowid == d.id
+            WHERE Drugs_Search MATCH ?1
+            ORDER BY
+                d.brand_name LIKE ?3 ESCAPE '\\' OR d.brand_name LIKE ?3 || ' %' ESCAPE '\\' DESC,
+                bm25(Drugs_Search, 10.0, 5.0, 1.0, 2.0, 2.0),
+                d.brand_name
+            LIMIT ?2
+        ", DRUG_SELECT), params![match_expr, limit as i64, first_word])
+    }
+
+    /// Every other brand with the same generic, an equivalent strength and the same dosage form, cheapest first.
+    /// Brands without a price go last.
+    pub fn get_alternatives(&self, brand_name: &str, strength: &str, formulation: &str) -> DbResult<Vec<DrugAlternative>> {
+        let Some(generic_name) = self.query_string("
+            SELECT g.name FROM Drugs d
+            JOIN Generics g ON g.id == d.generic_id
+            JOIN Strengths s ON s.id == d.strength_id
+            JOIN Dosages do ON do.id == d.dosage_id
+            WHERE d.brand_name = ?1 AND s.value = ?2 AND do.value = ?3
+        ", [brand_name, strength, formulation])? else {
+            return Ok(Vec::new());
+        };
+        let parsed_strength = Strength::parse(strength);
+        let candidates = self.query_drugs(
+            &format!("{} WHERE g.name = ?1 AND do.value = ?2 AND d.brand_name != ?3", DRUG_SELECT),
+            [&generic_name, formulation, brand_name],
+        )?;
+        let mut alternatives: Vec<DrugAlternative> = candidates
+            .into_iter()
+            .filter(|drug| match (&parsed_strength, Strength::parse(&drug.strength)) {
+                (Some(a), Some(b)) => a.is_equivalent(&b),
+                _ => drug.strength == strength,
+            })
+            .map(|drug| DrugAlternative { unit_price: pricing::unit_price(&drug.price), drug })
+            .collect();
+        alternatives.sort_by(|a, b| match (a.unit_price, b.unit_price) {
+            (Some(a_price), Some(b_price)) => a_price.total_cmp(&b_price),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }.then_with(|| a.drug.brand_name.cmp(&b.drug.brand_name)));
+        Ok(alternatives)
+    }
+
+    pub fn get_indications_by_generic_name(&self, generic_name: &str) -> DbResult<Vec<String>> {
+        self.query_strings("
+            SELECT i.name FROM Generics g
+            JOIN Generic_Indications gi ON gi.generic_id == g.id
+            JOIN Indications i ON i.id == gi.indication_id
+            WHERE g.name = ?1
+            ORDER BY i.name
+        ", [generic_name])
+    }
+
+    pub fn get_contraindications_by_generic_name(&self, generic_name: &str) -> DbResult<Vec<String>> {
+        self.query_strings("
+            SELECT c.name FROM Generics g
+            JOIN Generic_Contraindications gc ON gc.generic_id == g.id
+            JOIN Contraindications c ON c.id == gc.contraindication_id
+            WHERE g.name = ?1
+            ORDER BY c.name
+        ", [generic_name])
+    }
+
+    /// Warns about every contraindication of `medicine`'s generic that matches one of the patient's `conditions`.
+    pub fn check_contraindications(&self, medicine: &MedicineData, conditions: &[String]) -> DbResult<Vec<ContraindicationWarning>> {
+        if conditions.is_empty() {
+            return Ok(Vec::new());
+        }
+        let contraindications = self.get_contraindications_by_generic_name(&medicine.generic_name)?;
+        Ok(safety::check_contraindications(medicine, &contraindications, conditions))
+    }
+
+    pub fn get_interactions(&self) -> DbResult<Vec<Interaction>> {
+        let mut stmt = self.conn.prepare("SELECT generic_a, generic_b, severity, description FROM Interactions ORDER BY id")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get::<usize, String>(2)?, row.get(3)?)))?
+            .collect::<Result<Vec<(String, String, String, String)>, _>>()?;
+        rows.into_iter()
+            .map(|(generic_a, generic_b, severity, description)| {
+                let severity = Severity::parse(&severity)
+                    .ok_or_else(|| DbError::InvalidInput(format!("unknown interaction severity \"{}\"", severity)))?;
+                Ok(Interaction { generic_a, generic_b, severity, description })
+            })
+            .collect()
+    }
+
+    /// Every interacting pair of lines in `items`, most severe first.
+    pub fn check_interactions(&self, items: &[MedicineData]) -> DbResult<Vec<InteractionWarning>> {
+        if items.len() < 2 {
+            return Ok(Vec::new());
+        }
+        Ok(interactions::find_interactions(items, &self.get_interactions()?))
+    }
+
+    pub fn get_drug_classes(&self) -> DbResult<Vec<DrugClass>> {
+        let mut stmt = self.conn.prepare("SELECT id, name FROM Drug_Classes ORDER BY name")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<usize, i64>(0)?, row.get::<usize, String>(1)?)))?
+            .collect::<Result<Vec<(i64, String)>, _>>()?;
+        let mut classes = Vec::with_capacity(rows.len());
+        for (id, name) in rows {
+            classes.push(DrugClass {
+                name,
+                aliases: self.query_strings("SELECT alias FROM Drug_Class_Aliases WHERE class_id = ?1 ORDER BY alias", [id])?,
+                members: self.query_strings("SELECT generic FROM Drug_Class_Members WHERE class_id = ?1 ORDER BY generic", [id])?,
+                cross_reactive_classes: self.query_strings("
+                    SELECT c.name FROM Drug_Class_Cross_Reactions r
+                    JOIN Drug_Classes c ON c.id == r.related_class_id
+                    WHERE r.class_id = ?1
+                    ORDER BY c.name
+                ", [id])?,
+            });
+        }
+        Ok(classes)
+    }
+
+    /// Warns about every component of `medicine`'s generic the patient is allergic to, directly or through its class.
+    pub fn check_allergies(&self, medicine: &MedicineData, allergies: &[String]) -> DbResult<Vec<AllergyWarning>> {
+        if allergies.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(safety::check_allergies(medicine, allergies, &self.get_drug_classes()?))
+    }
+
+    pub fn get_dose_limits(&self) -> DbResult<Vec<DoseLimit>> {
+        let mut stmt = self.conn.prepare("
+            SELECT generic, strength, adult_max_daily_mg, pediatric_max_mg_per_kg_day, pediatric_max_daily_mg
+            FROM Dose_Limits ORDER BY id
+        ")?;
+        let limits = stmt
+            .query_map([], |row| {
+                Ok(DoseLimit {
+                    generic: row.get(0)?,
+                    strength: row.get(1)?,
+                    adult_max_daily_mg: row.get(2)?,
+                    pediatric_max_mg_per_kg_day: row.get(3)?,
+                    pediatric_max_daily_mg: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<DoseLimit>, _>>()?;
+        Ok(limits)
+    }
+
+    /// Warns when the daily dose of `medicine` exceeds the reference limit for an adult, or for a child
+    /// when `age_years` is below [`dose_limits::PEDIATRIC_AGE_LIMIT`]. Nothing is checked when the dosing does not parse.
+    pub fn check_dose(&self, medicine: &MedicineData, weight_kg: Option<f64>, age_years: Option<u32>) -> DbResult<Vec<DoseWarning>> {
+        let Some(schedule) = DosingSchedule::from_medicine(medicine) else { return Ok(Vec::new()); };
+        Ok(dose_limits::check_daily_dose(medicine, &schedule, &self.get_dose_limits()?, weight_kg, age_years))
+    }
+
+    pub fn get_indications(&self) -> DbResult<Vec<String>> {
+        self.query_strings("SELECT DISTINCT name FROM Indications ORDER BY name", [])
+    }
+
+    /// Generics indicated for `indication`, matched case-insensitively.
+    pub fn get_generic_names_by_indication(&self, indication: &str) -> DbResult<Vec<String>> {
+        self.query_strings("
+            SELECT DISTINCT g.name FROM Indications i
+            JOIN Generic_Indications gi ON gi.indication_id == i.id
+            JOIN Generics g ON g.id == gi.generic_id
+            WHERE i.name = ?1 COLLATE NOCASE
+            ORDER BY g.name
+        ", [indication.trim()])
+    }
+
+    /// Lowest listed price of the drug, `None` when the brand is unknown or has no price on record.
+    pub fn get_unit_price(&self, brand_name: &str, strength: &str, formulation: &str) -> DbResult<Option<f64>> {
+        let price = self.query_string("
+            SELECT p.value FROM Drugs d
+            JOIN Strengths s ON s.id == d.strength_id
+            JOIN Dosages do ON do.id == d.dosage_id
+            JOIN Prices p ON p.id == d.price_id
+            WHERE d.brand_name = ?1 AND s.value = ?2 AND do.value = ?3
+        ", [brand_name, strength, formulation])?;
+        Ok(price.as_deref().and_then(pricing::unit_price))
+    }
+
+    pub fn estimate_prescription_cost(&self, items: &[MedicineData]) -> DbResult<CostEstimate> {
+        let mut prices = Vec::with_capacity(items.len());
+        for item in items {
+            prices.push(self.get_unit_price(&item.brand_name, &item.strength, &item.formulation)?);
+        }
+        let mut prices = prices.into_iter();
+        Ok(pricing::estimate_cost(items, |_| prices.next().flatten()))
+    }
+
+    pub fn get_generic_names(&self) -> DbResult<Vec<String>> {
+        self.query_strings("SELECT DISTINCT name FROM Generics ORDER BY name", [])
+    }
+
+    pub fn get_brand_names(&self) -> DbResult<Vec<String>> {
+        self.query_strings("SELECT DISTINCT brand_name FROM Drugs", [])
+    }
+
+    pub fn get_manufacturers(&self) -> DbResult<Vec<String>> {
+        self.query_strings("SELECT DISTINCT name FROM Manufacturers ORDER BY name", [])
+    }
+
+    pub fn get_generic_name_by_brand_name(&self, brand_name: &str) -> DbResult<Option<String>> {
+        self.query_string(
+            "SELECT Generics.name FROM Drugs JOIN Generics ON Generics.id == Drugs.generic_id WHERE Drugs.brand_name = ?1",
+            [brand_name],
+        )
+    }
+
+    pub fn get_manufacturer_by_brand_name(&self, brand_name: &str) -> DbResult<Option<String>> {
+        self.query_string("
+            SELECT m.name FROM Drugs d
+            JOIN Manufacturers m ON m.id == d.manufacturer_id
+            WHERE d.brand_name = ?1
+        ", [brand_name])
+    }
+
+    pub fn get_brand_name_by_generic_name_manufacturer_and_strength(
+        &self, generic_name: &str, manufacturer: &str, strength: &str
+    ) -> DbResult<Option<String>> {
+        self.query_string("
+            SELECT d.brand_name FROM Drugs d
+            JOIN Generics g ON g.id == d.generic_id
+            JOIN Manufacturers m ON m.id == d.manufacturer_id
+            JOIN Strengths s ON s.id == d.strength_id
+            WHERE g.name = ?1 AND m.name = ?2 AND s.value = ?3
+        ", [generic_name, manufacturer, strength])
+    }
+
+    pub fn get_brand_name_by_generic_name_and_manufacturer(&self, generic_name: &str, manufacturer: &str) -> DbResult<Option<String>> {
+        self.query_string("
+            SELECT d.brand_name FROM Drugs d
+            JOIN Generics g ON g.id == d.generic_id
+            JOIN Manufacturers m ON m.id == d.manufacturer_id
+            WHERE g.name = ?1 AND m.name = ?2
+        ", [generic_name, manufacturer])
+    }
+
+    /// Strengths available for the generic, sorted numerically (`"5 mg"` before `"10 mg"`).
+    pub fn get_strengths_by_generic_name(&self, generic_name: &str) -> DbResult<Vec<String>> {
+        let mut strengths = self.query_strings(
+            "SELECT DISTINCT s.value FROM Drugs d JOIN Generics g ON g.id == d.generic_id JOIN Strengths s ON s.id == d.strength_id WHERE g.name = ?1",
+            [generic_name],
+        )?;
+        strength::sort_strengths(&mut strengths);
+        Ok(strengths)
+    }
+
+    pub fn get_formulations_by_brand_name_and_strength(&self, brand_name: &str, strength: &str) -> DbResult<Vec<String>> {
+        self.query_strings("
+            SELECT DISTINCT do.value FROM Drugs d
+            JOIN Strengths s ON s.id == d.strength_id
+            JOIN Dosages do ON do.id == d.dosage_id
+            WHERE d.brand_name = ?1 AND s.value = ?2
+        ", [brand_name, strength])
+    }
+
+    pub fn get_manufacturers_by_generic_name(&self, generic_name: &str) -> DbResult<Vec<String>> {
+        self.query_strings("
+            SELECT DISTINCT m.name FROM Drugs d
+            JOIN Manufacturers m ON m.id == d.manufacturer_id
+            JOIN Generics g ON g.id == d.generic_id
+            WHERE g.name = ?1
+            ORDER BY m.name
+        ", [generic_name])
+    }
+
+    fn query_drugs<P: rusqlite::Params>(&self, sql: &str, params: P) -> DbResult<Vec<Drug>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let drugs = stmt
+            .query_map(params, Self::drug_from_row)?
+            .collect::<Result<Vec<Drug>, _>>()?;
+        Ok(drugs)
+    }
+
+    fn query_strings<P: rusqlite::Params>(&self, sql: &str, params: P) -> DbResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let values = stmt
+            .query_map(params, |row| row.get::<usize, Option<String>>(0))?
+            .map(|value| value.map(Option::unwrap_or_default))
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(values)
+    }
+
+    fn query_string<P: rusqlite::Params>(&self, sql: &str, params: P) -> DbResult<Option<String>> {
+        let value = self.conn
+            .query_row(sql, params, |row| row.get::<usize, Option<String>>(0))
+            .optional()?;
+        Ok(value.flatten())
+    }
+
+    /// Turns free text into an FTS5 query of quoted prefix terms, so user input can never be a syntax error.
+    fn fts_match_expression(query: &str) -> Option<String> {
+        let terms: Vec<String> = query
+            .split(|c: char| !c.is_alphanumeric() && c != '.')
+            .map(|term| term.trim_matches('.'))
+            .filter(|term| !term.is_empty())
+            .map(|term| format!("\"{}\"*", term))
+            .collect();
+        if terms.is_empty() { None } else { Some(terms.join(" ")) }
+    }
+
+    /// Escapes `%`, `_` and `\` so that `text` only matches itself in a `LIKE ... ESCAPE '\'` pattern.
+    fn like_literal(text: &str) -> String {
+        text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+    }
+
+    fn drug_from_row(row: &Row) -> rusqlite::Result<Drug> {
+        Ok(Drug {
+            id: row.get(0)?,
+            brand_name: row.get::<usize, Option<String>>(1)?.unwrap_or_default(),
+            generic_name: row.get::<usize, Option<String>>(2)?.unwrap_or_default(),
+            strength: row.get::<usize, Option<String>>(3)?.unwrap_or_default(),
+            dosage_form: row.get::<usize, Option<String>>(4)?.unwrap_or_default(),
+            manufacturer: row.get::<usize, Option<String>>(5)?.unwrap_or_default(),
+            price: row.get::<usize, Option<String>>(6)?.unwrap_or_default(),
+            dar_number: row.get::<usize, Option<String>>(7)?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Shared repository for the default `drugs.db`, opened on first use.
+pub fn drug_repository() -> DbResult<MutexGuard<'static, DrugRepository>> {
+    let repository = match DRUG_REPOSITORY.get() {
+        Some(repository) => repository,
+        None => {
+            let repository = DrugRepository::open_default()?;
+            DRUG_REPOSITORY.get_or_init(|| Mutex::new(repository))
+        }
+    };
+    Ok(repository.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+}
+
+/// Runs `query` against the shared repository, logging failures and falling back to `T::default()`.
+pub(crate) fn query_or_default<T: Default>(query: impl FnOnce(&DrugRepository) -> DbResult<T>) -> T {
+    match drug_repository().and_then(|repository| query(&repository)) {
+        Ok(value) => value,
+        Err(err) => {
+            eprintln!("drugs database: {}", err);
+            T::default()
+        }
     }
+}
+
+fn c_str_arg<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() { return None; }
+    unsafe { CStr::from_ptr(ptr).to_str().ok() }
+}
 
-    // apply initial migrations
-    let mut conn = Connection::open(&db_path).unwrap();
-    conn.pragma_update(None, "foreign_keys", "on").unwrap();
-    conn.pragma_update(None, "journal_mode", "wal").unwrap();
-    DRUGS_MIGRATIONS.to_latest(&mut conn).unwrap();
-    conn.close().unwrap();
+fn c_string_array(values: Vec<String>) -> CStringArray {
+    let values: Vec<String> = values.into_iter().filter(|value| !value.contains('\0')).collect();
+    CStringArray::c_repr_of(values).unwrap_or_else(|_| CStringArray {
+        data: NonNull::<*const c_char>::dangling().as_ptr(),
+        size: 0,
+    })
+}
 
-    // open the database read only
-    let conn = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).unwrap();
-    DRUGS_DB_CONN.get_or_init(|| Arc::new(Mutex::new(conn)))
+fn c_string_ptr(slot: &'static LocalKey<RefCell<Option<CString>>>, value: Option<String>) -> *const c_char {
+    match value.and_then(|value| CString::new(value).ok()) {
+        Some(cstring) => {
+            let ptr = cstring.as_ptr();
+            slot.with(|last| {
+                *last.borrow_mut() = Some(cstring);
+            });
+            ptr
+        }
+        None => std::ptr::null(),
+    }
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn get_generic_names_c() -> CStringArray {
-    let conn_arc_mutex = get_drugs_db_connection();
-    let conn_guard = conn_arc_mutex.lock().unwrap();
-    let mut stmt = conn_guard.prepare("SELECT DISTINCT name FROM Generics ORDER BY name").unwrap();
-    let rows = stmt.query_map([], |row| row.get::<usize, Option<String>>(0)).unwrap(); 
-    let generic_names: Vec<String> = rows.map(|row| row.unwrap().unwrap_or_default()).collect();
-    CStringArray::c_repr_of(generic_names).unwrap()
+    c_string_array(query_or_default(|repository| repository.get_generic_names()))
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn get_generic_name_by_brand_name_c(brand_name: *const c_char) -> *const c_char {
-    let brand_name_str = unsafe { CStr::from_ptr(brand_name).to_str().unwrap() };
-    let conn_arc_mutex = get_drugs_db_connection();
-    let conn_guard = conn_arc_mutex.lock().unwrap();
-    let mut stmt = conn_guard.prepare("SELECT Generics.name FROM Drugs JOIN Generics ON Generics.id == Drugs.generic_id WHERE Drugs.brand_name = ?1").unwrap();
-    let mut rows = stmt.query_map([brand_name_str], |row| row.get::<usize, String>(0)).unwrap();
-    let generic_name = rows.next().unwrap().unwrap_or_default();
-    let cstring = CString::c_repr_of(generic_name).unwrap();
-    let ptr = cstring.as_ptr();
-
-    LAST_GENERIC_NAME.with(|last| {
-        *last.borrow_mut() = Some(cstring);
-    });
-    ptr
+    let Some(brand_name_str) = c_str_arg(brand_name) else { return std::ptr::null(); };
+    let generic_name = query_or_default(|repository| repository.get_generic_name_by_brand_name(brand_name_str));
+    c_string_ptr(&LAST_GENERIC_NAME, generic_name)
 }
 
-
 #[unsafe(no_mangle)]
 pub extern "C" fn get_manufacturer_by_brand_name_c(brand_name: *const c_char) -> *const c_char {
-    let brand_name_str = unsafe { CStr::from_ptr(brand_name).to_str().unwrap() };
-    let conn_arc_mutex = get_drugs_db_connection();
-    let conn_guard = conn_arc_mutex.lock().unwrap();
-    let mut stmt = conn_guard.prepare("
-        SELECT m.name FROM Drugs d
-        JOIN Manufacturers m ON m.id == d.manufacturer_id
-        WHERE d.brand_name = ?1
-    ").unwrap();
-    let mut rows = stmt.query_map([brand_name_str], |row| row.get::<usize, String>(0)).unwrap();
-    let manufacturer_name = rows.next().unwrap().unwrap_or_default();
-    
-    let cstring = CString::c_repr_of(manufacturer_name).unwrap();
-    let ptr = cstring.as_ptr();
-
-    LAST_MANUFACTURER_NAME.with(|last| {
-        *last.borrow_mut() = Some(cstring);
-    });
-    ptr
+    let Some(brand_name_str) = c_str_arg(brand_name) else { return std::ptr::null(); };
+    let manufacturer_name = query_or_default(|repository| repository.get_manufacturer_by_brand_name(brand_name_str));
+    c_string_ptr(&LAST_MANUFACTURER_NAME, manufacturer_name)
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn get_brand_name_by_generic_name_manufacturer_and_strength_c(
     generic_name: *const c_char, manufacturer: *const c_char, strength: *const c_char
 ) -> *const c_char {
-    let generic_name_str = unsafe { CStr::from_ptr(generic_name).to_str().unwrap() };
-    let manufacturer_str = unsafe { CStr::from_ptr(manufacturer).to_str().unwrap() };
-    let strength_str = unsafe { CStr::from_ptr(strength).to_str().unwrap() };
-    
-    let conn_arc_mutex = get_drugs_db_connection();
-    let conn_guard = conn_arc_mutex.lock().unwrap();
-    let mut stmt = conn_guard.prepare("
-        SELECT d.brand_name FROM Drugs d 
-        JOIN Generics g ON g.id == d.generic_id 
-        JOIN Manufacturers m ON m.id == d.manufacturer_id
-        JOIN Strengths s ON s.id == d.strength_id
-        WHERE g.name = ?1 AND m.name = ?2 AND s.value = ?3
-    ").unwrap();
-    let mut rows = stmt.query_map([generic_name_str, manufacturer_str, strength_str], |row| row.get::<usize, String>(0)).unwrap();
-    if let Some(row) = rows.next() {
-        let brand_name = row.unwrap_or_default();
-        let cstring = CString::c_repr_of(brand_name).unwrap();
-        let ptr = cstring.as_ptr();
-
-        LAST_BRAND_NAME.with(|last| {
-            *last.borrow_mut() = Some(cstring);
-        });
-        ptr
-    } else {
-        std::ptr::null()
-    }
+    let (Some(generic_name_str), Some(manufacturer_str), Some(strength_str)) =
+        (c_str_arg(generic_name), c_str_arg(manufacturer), c_str_arg(strength)) else { return std::ptr::null(); };
+    let brand_name = query_or_default(|repository| {
+        repository.get_brand_name_by_generic_name_manufacturer_and_strength(generic_name_str, manufacturer_str, strength_str)
+    });
+    c_string_ptr(&LAST_BRAND_NAME, brand_name)
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn get_brand_name_by_generic_name_and_manufacturer_c(
     generic_name: *const c_char, manufacturer: *const c_char
 ) -> *const c_char {
-    let generic_name_str = unsafe { CStr::from_ptr(generic_name).to_str().unwrap() };
-    let manufacturer_str = unsafe { CStr::from_ptr(manufacturer).to_str().unwrap() };
-    
-    let conn_arc_mutex = get_drugs_db_connection();
-    let conn_guard = conn_arc_mutex.lock().unwrap();
-    let mut stmt = conn_guard.prepare("
-        SELECT d.brand_name FROM Drugs d 
-        JOIN Generics g ON g.id == d.generic_id 
-        JOIN Manufacturers m ON m.id == d.manufacturer_id
-        WHERE g.name = ?1 AND m.name = ?2
-    ").unwrap();
-    let mut rows = stmt.query_map([generic_name_str, manufacturer_str], |row| row.get::<usize, String>(0)).unwrap();
-    let brand_name = rows.next().unwrap().unwrap_or_default();
-    
-    let cstring = CString::c_repr_of(brand_name).unwrap();
-    let ptr = cstring.as_ptr();
-
-    LAST_BRAND_NAME.with(|last| {
-        *last.borrow_mut() = Some(cstring);
+    let (Some(generic_name_str), Some(manufacturer_str)) =
+        (c_str_arg(generic_name), c_str_arg(manufacturer)) else { return std::ptr::null(); };
+    let brand_name = query_or_default(|repository| {
+        repository.get_brand_name_by_generic_name_and_manufacturer(generic_name_str, manufacturer_str)
     });
-    ptr
+    c_string_ptr(&LAST_BRAND_NAME, brand_name)
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn get_strengths_by_generic_name_c(generic_name: *const c_char) -> CStringArray {
-    let generic_name_str = unsafe { CStr::from_ptr(generic_name).to_str().unwrap() };
-    let conn_arc_mutex = get_drugs_db_connection();
-    let conn_guard = conn_arc_mutex.lock().unwrap();
-    let mut stmt = conn_guard.prepare(
-        "SELECT DISTINCT s.value FROM Drugs d JOIN Generics g ON g.id == d.generic_id JOIN Strengths s ON s.id == d.strength_id WHERE g.name = ?1"
-    ).unwrap();
-    let rows = stmt.query_map([generic_name_str], |row| row.get::<usize, Option<String>>(0)).unwrap(); 
-    let strengths: Vec<String> = rows.map(|row| row.unwrap().unwrap_or_default()).collect();
-    CStringArray::c_repr_of(strengths).unwrap()
+    let generic_name_str = c_str_arg(generic_name).unwrap_or_default();
+    c_string_array(query_or_default(|repository| repository.get_strengths_by_generic_name(generic_name_str)))
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn get_formulations_by_brand_name_and_strength_c(brand_name: *const c_char, strength: *const c_char) -> CStringArray {
-    let brand_name_str = unsafe { CStr::from_ptr(brand_name).to_str().unwrap() };
-    let strength_str = unsafe { CStr::from_ptr(strength).to_str().unwrap() };
-    let conn_arc_mutex = get_drugs_db_connection();
-    let conn_guard = conn_arc_mutex.lock().unwrap();
-    let mut stmt = conn_guard.prepare("
-        SELECT DISTINCT do.value FROM Drugs d
-        JOIN Strengths s ON s.id == d.strength_id
-        JOIN Dosages do ON do.id == d.dosage_id 
-        WHERE d.brand_name = ?1 AND s.value = ?2
-    ").unwrap();
-    let rows = stmt.query_map([brand_name_str, strength_str], |row| row.get::<usize, Option<String>>(0)).unwrap(); 
-    let formulations: Vec<String> = rows.map(|row| row.unwrap().unwrap_or_default()).collect();
-    CStringArray::c_repr_of(formulations).unwrap()
+    let brand_name_str = c_str_arg(brand_name).unwrap_or_default();
+    let strength_str = c_str_arg(strength).unwrap_or_default();
+    c_string_array(query_or_default(|repository| {
+        repository.get_formulations_by_brand_name_and_strength(brand_name_str, strength_str)
+    }))
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn get_brand_names_c() -> CStringArray {
-    let conn_arc_mutex = get_drugs_db_connection();
-    let conn_guard = conn_arc_mutex.lock().unwrap();
-    let mut stmt = conn_guard.prepare("SELECT DISTINCT brand_name FROM Drugs").unwrap();
-    let rows = stmt.query_map([], |row| row.get::<usize, Option<String>>(0)).unwrap(); 
-    let brand_names: Vec<String> = rows.map(|row| row.unwrap().unwrap_or_default()).collect();
-    CStringArray::c_repr_of(brand_names).unwrap()
+    c_string_array(query_or_default(|repository| repository.get_brand_names()))
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn get_manufacturers_c() -> CStringArray {
-    let conn_arc_mutex = get_drugs_db_connection();
-    let conn_guard = conn_arc_mutex.lock().unwrap();
-    let mut stmt = conn_guard.prepare("SELECT DISTINCT name FROM Manufacturers ORDER BY name").unwrap();
-    let rows = stmt.query_map([], |row| row.get::<usize, Option<String>>(0)).unwrap(); 
-    let brand_names: Vec<String> = rows.map(|row| row.unwrap().unwrap_or_default()).collect();
-    CStringArray::c_repr_of(brand_names).unwrap()
+    c_string_array(query_or_default(|repository| repository.get_manufacturers()))
 }
 
 #[unsafe(no_mangle)]
 pub extern "C" fn get_manufacturers_by_generic_name_c(generic_name: *const c_char) -> CStringArray {
-    let generic_name_str = unsafe { CStr::from_ptr(generic_name).to_str().unwrap() };
-    let conn_arc_mutex = get_drugs_db_connection();
-    let conn_guard = conn_arc_mutex.lock().unwrap();
-    let mut stmt = conn_guard.prepare("
-        SELECT DISTINCT m.name FROM Drugs d
-        JOIN Manufacturers m ON m.id == d.manufacturer_id
-        JOIN Generics g ON g.id == d.generic_id
-        WHERE g.name = ?1
-        ORDER BY m.name
-    ").unwrap();
-    let rows = stmt.query_map([generic_name_str], |row| row.get::<usize, Option<String>>(0)).unwrap(); 
-    let brand_names: Vec<String> = rows.map(|row| row.unwrap().unwrap_or_default()).collect();
-    CStringArray::c_repr_of(brand_names).unwrap()
+    let generic_name_str = c_str_arg(generic_name).unwrap_or_default();
+    c_string_array(query_or_default(|repository| repository.get_manufacturers_by_generic_name(generic_name_str)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fts_terms_are_quoted_prefixes() {
+        assert_eq!(DrugRepository::fts_match_expression("napa 500 tab").as_deref(), Some("\"napa\"* \"500\"* \"tab\"*"));
+        assert_eq!(DrugRepository::fts_match_expression("2.5 mg.").as_deref(), Some("\"2.5\"* \"mg\"*"));
+        // operators and quotes typed by the user are plain words
+        assert_eq!(
+            DrugRepository::fts_match_expression("\"napa\" OR -tab* NEAR(").as_deref(),
+            Some("\"napa\"* \"OR\"* \"tab\"* \"NEAR\"*")
+        );
+        assert_eq!(DrugRepository::fts_match_expression(" -*\" "), None);
+    }
+
+    #[test]
+    fn like_wildcards_are_escaped() {
+        assert_eq!(DrugRepository::like_literal("napa"), "napa");
+        assert_eq!(DrugRepository::like_literal("50%_a\\b"), "50\\%\\_a\\\\b");
+    }
+
+    #[test]
+    fn search_ranks_brand_word_matches_first() {
+        let repository = DrugRepository::open_in_memory().unwrap();
+        let drugs = repository.search_drugs("napa", 30).unwrap();
+        let word_match = |drug: &Drug| drug.brand_name.to_lowercase() == "napa" || drug.brand_name.to_lowercase().starts_with("napa ");
+        assert!(word_match(&drugs[0]));
+        assert!(drugs.iter().any(|drug| !word_match(drug)));
+        assert!(drugs.is_sorted_by_key(|drug| !word_match(drug)));
+
+        assert!(!repository.search_drugs("\"napa (500", 5).unwrap().is_empty());
+        assert!(repository.search_drugs("%", 5).unwrap().is_empty());
+        assert!(repository.search_drugs("napa%", 5).unwrap().iter().all(|drug| drug.brand_name.to_lowercase().starts_with("napa")));
+    }
 }
@@ -0,0 +1,126 @@
+use crate::dosing::{DoseUnit, DosingSchedule, Frequency};
+use crate::strength::Strength;
+
+/// Weight-based dose of a liquid formulation, rounded to what can be measured with a syringe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightBasedDose {
+    pub ml_per_dose: f64,
+    pub mg_per_dose: f64,
+    /// Daily dose the rounded volume actually delivers.
+    pub mg_per_kg_day: f64,
+    pub schedule: DosingSchedule,
+}
+
+/// Why no dose could be worked out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DoseCalculationError {
+    /// Weight or dose not above zero, or not 1 to 3 doses a day.
+    InvalidInput,
+    /// The strength is not a single mg per ml amount, e.g. a tablet or a combination.
+    UnsupportedStrength,
+    /// Each dose is `ml_per_dose`, less than half of the smallest syringe step, so it rounds to 0 ml.
+    TooSmallToMeasure { ml_per_dose: f64 },
+}
+
+impl WeightBasedDose {
+    /// Text for the dosing entry, e.g. `"2½+2½+2½ ml"`.
+    pub fn dosing_text(&self) -> String {
+        format!("{} ml", self.schedule.shorthand())
+    }
+}
+
+/// Splits `mg_per_kg_day` for a child weighing `weight_kg` into `doses_per_day` doses (1 to 3, given
+/// morning, morning and night, or morning, noon and night) of a liquid `strength` such as `"125 mg/5 ml"`.
+pub fn calculate_weight_based_dose(
+    weight_kg: f64, mg_per_kg_day: f64, doses_per_day: usize, strength: &str
+) -> Result<WeightBasedDose, DoseCalculationError> {
+    if weight_kg <= 0.0 || mg_per_kg_day <= 0.0 || !(1..=3).contains(&doses_per_day) {
+        return Err(DoseCalculationError::InvalidInput);
+    }
+    let mg_per_ml = Strength::parse(strength)
+        .and_then(|strength| match &strength.components[..] {
+            [component] => component.mg().zip(component.per_ml()),
+            _ => None,
+        })
+        .filter(|(mg, ml)| *mg > 0.0 && *ml > 0.0)
+        .map(|(mg, ml)| mg / ml)
+        .ok_or(DoseCalculationError::UnsupportedStrength)?;
+    let exact_ml_per_dose = weight_kg * mg_per_kg_day / doses_per_day as f64 / mg_per_ml;
+    let ml_per_dose = round_volume(exact_ml_per_dose);
+    if ml_per_dose <= 0.0 {
+        return Err(DoseCalculationError::TooSmallToMeasure { ml_per_dose: exact_ml_per_dose });
+    }
+    let (morning, noon, night) = match doses_per_day {
+        1 => (ml_per_dose, 0.0, 0.0),
+        2 => (ml_per_dose, 0.0, ml_per_dose),
+        _ => (ml_per_dose, ml_per_dose, ml_per_dose),
+    };
+    let mg_per_dose = ml_per_dose * mg_per_ml;
+    Ok(WeightBasedDose {
+        ml_per_dose,
+        mg_per_dose,
+        mg_per_kg_day: mg_per_dose * doses_per_day as f64 / weight_kg,
+        schedule: DosingSchedule {
+            morning,
+            noon,
+            night,
+            unit: Some(DoseUnit::Millilitre),
+            frequency: Frequency::Daily,
+            meal_timing: None,
+            duration: None,
+        },
+    })
+}
+
+/// Rounds to ¼ ml below 2.5 ml and to ½ ml above, the steps of an oral syringe.
+fn round_volume(ml: f64) -> f64 {
+    let step = if ml < 2.5 { 0.25 } else { 0.5 };
+    (ml / step).round() * step
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_per_dose() {
+        let dose = calculate_weight_based_dose(10.0, 45.0, 3, "125 mg/5 ml").unwrap();
+        assert_eq!(dose.ml_per_dose, 6.0);
+        assert_eq!(dose.mg_per_dose, 150.0);
+        assert_eq!(dose.mg_per_kg_day, 45.0);
+        assert_eq!(dose.dosing_text(), "6+6+6 ml");
+    }
+
+    #[test]
+    fn small_volumes_are_rounded_to_a_quarter_ml() {
+        // 20 mg of 120 mg/5 ml is 0.83 ml
+        let dose = calculate_weight_based_dose(4.0, 15.0, 3, "120 mg/5 ml").unwrap();
+        assert_eq!(dose.ml_per_dose, 0.75);
+        assert_eq!(dose.mg_per_dose, 18.0);
+        assert_eq!(dose.dosing_text(), "¾+¾+¾ ml");
+    }
+
+    #[test]
+    fn large_volumes_are_rounded_to_half_a_ml() {
+        // 2.6 ml a dose
+        let dose = calculate_weight_based_dose(13.0, 10.0, 2, "125 mg/5 ml").unwrap();
+        assert_eq!(dose.ml_per_dose, 2.5);
+        assert_eq!(dose.dosing_text(), "2½+0+2½ ml");
+    }
+
+    #[test]
+    fn doses_rounding_to_nothing_are_reported() {
+        // 2 mg of 125 mg/5 ml is 0.08 ml
+        let error = calculate_weight_based_dose(1.0, 6.0, 3, "125 mg/5 ml").unwrap_err();
+        let DoseCalculationError::TooSmallToMeasure { ml_per_dose } = error else { panic!("unexpected {:?}", error) };
+        assert!((ml_per_dose - 0.08).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(calculate_weight_based_dose(0.0, 45.0, 3, "125 mg/5 ml"), Err(DoseCalculationError::InvalidInput));
+        assert_eq!(calculate_weight_based_dose(10.0, 45.0, 4, "125 mg/5 ml"), Err(DoseCalculationError::InvalidInput));
+        assert_eq!(calculate_weight_based_dose(10.0, 45.0, 3, "500 mg"), Err(DoseCalculationError::UnsupportedStrength));
+        assert_eq!(calculate_weight_based_dose(10.0, 45.0, 3, "200 mg + 40 mg/5 ml"), Err(DoseCalculationError::UnsupportedStrength));
+    }
+}
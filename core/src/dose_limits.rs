@@ -0,0 +1,215 @@
+use crate::dosing::{DoseUnit, DosingSchedule};
+use crate::ffi::prescription::MedicineData;
+use crate::interactions::{generic_matches, split_generic_components};
+use crate::strength::{Strength, StrengthComponent};
+
+/// Patients younger than this are dosed by the pediatric limits.
+pub const PEDIATRIC_AGE_LIMIT: u32 = 12;
+
+/// A row of the `Dose_Limits` table. `generic` is stored without its salt, like the interactions table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DoseLimit {
+    pub generic: String,
+    /// Limit only applies to this `Strengths.value`, `None` for every strength.
+    pub strength: Option<String>,
+    pub adult_max_daily_mg: Option<f64>,
+    pub pediatric_max_mg_per_kg_day: Option<f64>,
+    /// Cap for children regardless of weight.
+    pub pediatric_max_daily_mg: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoseWarning {
+    pub medicine_id: u64,
+    pub brand_name: String,
+    /// Component of the generic that exceeds its limit.
+    pub generic: String,
+    pub daily_mg: f64,
+    pub max_daily_mg: f64,
+    /// Set when the limit was derived from the patient's weight.
+    pub mg_per_kg_day: Option<f64>,
+    pub pediatric: bool,
+}
+
+impl DoseWarning {
+    pub fn message(&self) -> String {
+        let limit = match self.mg_per_kg_day {
+            Some(mg_per_kg_day) => format!("{} mg/day ({} mg/kg/day)", format_mg(self.max_daily_mg), format_mg(mg_per_kg_day)),
+            None => format!("{} mg/day", format_mg(self.max_daily_mg)),
+        };
+        format!(
+            "{} ({}): {} mg/day exceeds the {} maximum of {}",
+            self.brand_name, self.generic, format_mg(self.daily_mg),
+            if self.pediatric { "pediatric" } else { "adult" }, limit
+        )
+    }
+}
+
+impl DoseLimit {
+    /// Highest daily dose for the patient, `None` when no limit applies.
+    /// Returns the limit and, when it comes from the weight, the mg/kg/day it was derived from.
+    pub fn max_daily_mg(&self, weight_kg: Option<f64>, age_years: Option<u32>) -> Option<(f64, Option<f64>)> {
+        if !is_pediatric(age_years) {
+            return self.adult_max_daily_mg.map(|max| (max, None));
+        }
+        let cap = self.pediatric_max_daily_mg.or(self.adult_max_daily_mg);
+        match (self.pediatric_max_mg_per_kg_day, weight_kg.filter(|weight| *weight > 0.0)) {
+            (Some(mg_per_kg_day), Some(weight)) => {
+                let by_weight = mg_per_kg_day * weight;
+                match cap {
+                    Some(cap) if cap < by_weight => Some((cap, None)),
+                    _ => Some((by_weight, Some(mg_per_kg_day))),
+                }
+            }
+            _ => cap.map(|max| (max, None)),
+        }
+    }
+}
+
+pub fn is_pediatric(age_years: Option<u32>) -> bool {
+    age_years.is_some_and(|age| age < PEDIATRIC_AGE_LIMIT)
+}
+
+/// Compares the daily dose of every component of `medicine` with the matching limit in `limits`.
+/// A strength specific limit takes precedence over the generic one.
+pub fn check_daily_dose(
+    medicine: &MedicineData, schedule: &DosingSchedule, limits: &[DoseLimit], weight_kg: Option<f64>, age_years: Option<u32>
+) -> Vec<DoseWarning> {
+    let components = split_generic_components(&medicine.generic_name);
+    let Some(strength) = Strength::parse(&medicine.strength) else { return Vec::new(); };
+    if components.len() != strength.components.len() {
+        return Vec::new();
+    }
+    let unit = schedule.unit.or_else(|| DoseUnit::from_formulation(&medicine.formulation));
+    let mut warnings = Vec::new();
+    for (component, amount) in components.iter().zip(&strength.components) {
+        let Some(mg_per_unit) = mg_per_unit(amount, unit) else { continue; };
+        let Some(limit) = find_limit(limits, component, &medicine.strength) else { continue; };
+        let Some((max_daily_mg, mg_per_kg_day)) = limit.max_daily_mg(weight_kg, age_years) else { continue; };
+        let daily_mg = schedule.units_per_day() * mg_per_unit;
+        if daily_mg > max_daily_mg + 1e-6 {
+            warnings.push(DoseWarning {
+                medicine_id: medicine.id,
+                brand_name: medicine.brand_name.clone(),
+                generic: component.clone(),
+                daily_mg,
+                max_daily_mg,
+                mg_per_kg_day,
+                pediatric: is_pediatric(age_years),
+            });
+        }
+    }
+    warnings
+}
+
+fn find_limit<'a>(limits: &'a [DoseLimit], component: &str, strength: &str) -> Option<&'a DoseLimit> {
+    let matching = || limits.iter().filter(|limit| generic_matches(component, &limit.generic));
+    matching()
+        .find(|limit| limit.strength.as_deref().is_some_and(|value| value.eq_ignore_ascii_case(strength.trim())))
+        .or_else(|| matching().find(|limit| limit.strength.is_none()))
+}
+
+/// Milligrams in one dose unit, `None` when the unit cannot be converted (drops, puffs, creams...).
+fn mg_per_unit(amount: &StrengthComponent, unit: Option<DoseUnit>) -> Option<f64> {
+    let mg = amount.mg()?;
+    if amount.per.is_some() && amount.per_ml().is_none() {
+        return None;
+    }
+    match (amount.per_ml(), unit) {
+        (None, Some(DoseUnit::Tablet | DoseUnit::Capsule | DoseUnit::Sachet | DoseUnit::Suppository | DoseUnit::Injection) | None) => Some(mg),
+        (Some(ml), Some(DoseUnit::Spoon)) => Some(mg / ml * 5.0),
+        (Some(ml), Some(DoseUnit::Millilitre)) => Some(mg / ml),
+        _ => None,
+    }
+}
+
+fn format_mg(value: f64) -> String {
+    format!("{:.1}", value).trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn medicine(generic_name: &str, strength: &str, formulation: &str, dosing: &str) -> MedicineData {
+        MedicineData { dosing: dosing.into(), ..MedicineData::for_test(generic_name, strength, formulation) }
+    }
+
+    fn check(medicine: &MedicineData, limits: &[DoseLimit], weight_kg: Option<f64>, age_years: Option<u32>) -> Vec<DoseWarning> {
+        let schedule = DosingSchedule::from_medicine(medicine).unwrap();
+        check_daily_dose(medicine, &schedule, limits, weight_kg, age_years)
+    }
+
+    fn paracetamol() -> DoseLimit {
+        DoseLimit {
+            generic: "Paracetamol".into(),
+            adult_max_daily_mg: Some(4000.0),
+            pediatric_max_mg_per_kg_day: Some(60.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn adult_dose_within_the_limit() {
+        let tablet = medicine("Paracetamol", "500 mg", "Tablet", "2+2+2");
+        assert!(check(&tablet, &[paracetamol()], None, Some(30)).is_empty());
+    }
+
+    #[test]
+    fn adult_dose_over_the_limit() {
+        let tablet = medicine("Paracetamol", "500 mg", "Tablet", "3+3+3");
+        let warnings = check(&tablet, &[paracetamol()], None, Some(30));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].daily_mg, 4500.0);
+        assert_eq!(warnings[0].max_daily_mg, 4000.0);
+        assert!(!warnings[0].pediatric);
+    }
+
+    #[test]
+    fn pediatric_limit_comes_from_the_weight() {
+        // 2 tsp three times a day of 120 mg/5 ml is 720 mg, over 60 mg/kg/day for 10 kg
+        let syrup = medicine("Paracetamol", "120 mg/5 ml", "Suspension", "2+2+2");
+        let warnings = check(&syrup, &[paracetamol()], Some(10.0), Some(2));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].daily_mg, 720.0);
+        assert_eq!(warnings[0].max_daily_mg, 600.0);
+        assert_eq!(warnings[0].mg_per_kg_day, Some(60.0));
+        assert!(warnings[0].pediatric);
+        assert!(check(&syrup, &[paracetamol()], Some(15.0), Some(2)).is_empty());
+    }
+
+    #[test]
+    fn pediatric_limit_is_capped() {
+        let limit = DoseLimit { pediatric_max_daily_mg: Some(1000.0), ..paracetamol() };
+        assert_eq!(limit.max_daily_mg(Some(40.0), Some(10)), Some((1000.0, None)));
+        assert_eq!(limit.max_daily_mg(Some(10.0), Some(10)), Some((600.0, Some(60.0))));
+        assert_eq!(limit.max_daily_mg(None, Some(10)), Some((1000.0, None)));
+        assert_eq!(limit.max_daily_mg(Some(10.0), Some(12)), Some((4000.0, None)));
+    }
+
+    #[test]
+    fn strength_specific_limit_takes_precedence() {
+        let limits = [
+            paracetamol(),
+            DoseLimit { strength: Some("1000 mg".into()), adult_max_daily_mg: Some(3000.0), ..paracetamol() },
+        ];
+        assert_eq!(check(&medicine("Paracetamol", "1000 mg", "Tablet", "1+1+1"), &limits, None, None).len(), 0);
+        assert_eq!(check(&medicine("Paracetamol", "1000 mg", "Tablet", "2+1+1"), &limits, None, None).len(), 1);
+    }
+
+    #[test]
+    fn combinations_are_checked_per_component() {
+        let limits = [paracetamol(), DoseLimit { generic: "Caffeine".into(), adult_max_daily_mg: Some(400.0), ..Default::default() }];
+        let tablet = medicine("Paracetamol + Caffeine", "500 mg + 65 mg", "Tablet", "3+3+3");
+        let generics: Vec<String> = check(&tablet, &limits, None, None).into_iter().map(|warning| warning.generic).collect();
+        assert_eq!(generics, ["Paracetamol", "Caffeine"]);
+    }
+
+    #[test]
+    fn unconvertible_doses_are_skipped() {
+        let drops = medicine("Paracetamol", "80 mg/ml", "Paediatric Drops", "10+10+10");
+        assert!(check(&drops, &[paracetamol()], Some(5.0), Some(1)).is_empty());
+        let vial = medicine("Paracetamol", "1 gm/vial", "IV Infusion", "5+5+5");
+        assert!(check(&vial, &[paracetamol()], None, None).is_empty());
+    }
+}
@@ -0,0 +1,620 @@
+use crate::ffi::prescription::MedicineData;
+use crate::utils::{bangla_digits, normalize_digits};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DoseUnit {
+    Tablet,
+    Capsule,
+    /// Teaspoon, 5 ml.
+    Spoon,
+    Millilitre,
+    Drop,
+    Puff,
+    Sachet,
+    Suppository,
+    Application,
+    Injection,
+}
+
+impl DoseUnit {
+    /// Words (English and Bangla) the unit is written as after a quantity, e.g. `"1 tab"` or `"২ চামচ"`.
+    const WORDS: &[(DoseUnit, &[&str])] = &[
+        (DoseUnit::Tablet, &["tablet", "tab", "ট্যাবলেট", "ট্যাব", "বড়ি"]),
+        (DoseUnit::Capsule, &["capsule", "cap", "ক্যাপসুল", "ক্যাপ"]),
+        (DoseUnit::Spoon, &["teaspoon", "spoon", "tsf", "tsp", "চামচ"]),
+        (DoseUnit::Millilitre, &["ml", "মিলি"]),
+        (DoseUnit::Drop, &["drop", "ফোঁটা", "ফোটা"]),
+        (DoseUnit::Puff, &["puff", "চাপ"]),
+        (DoseUnit::Sachet, &["sachet", "স্যাশে", "প্যাকেট"]),
+        (DoseUnit::Suppository, &["suppository", "supp", "সাপোজিটরি"]),
+        (DoseUnit::Application, &["application", "apply", "বার লাগাবেন", "লাগাবেন"]),
+        (DoseUnit::Injection, &["injection", "inj", "ইনজেকশন"]),
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase();
+        Self::WORDS
+            .iter()
+            .find(|(_, words)| words.iter().any(|word| value.starts_with(word)))
+            .map(|(unit, _)| *unit)
+    }
+
+    /// The unit a dose of `formulation` (a `Dosages.value`) is usually counted in.
+    pub fn from_formulation(formulation: &str) -> Option<Self> {
+        let formulation = formulation.to_lowercase();
+        let unit = if formulation.contains("tablet") {
+            DoseUnit::Tablet
+        } else if formulation.contains("inhalation capsule") || formulation.contains("inhaler") || formulation.contains("aerosol") || formulation.contains("spray") {
+            DoseUnit::Puff
+        } else if formulation.contains("capsule") || formulation.contains("cozycap") {
+            DoseUnit::Capsule
+        } else if formulation.contains("drop") {
+            DoseUnit::Drop
+        } else if formulation.contains("suppositor") || formulation.contains("pessary") {
+            DoseUnit::Suppository
+        } else if formulation.contains("sachet") || formulation.contains("sached") || formulation.contains("granules") {
+            DoseUnit::Sachet
+        } else if formulation.contains("injection") || formulation.contains("infusion") || formulation.contains("vaccine") {
+            DoseUnit::Injection
+        } else if ["syrup", "suspension", "elixir", "linctus", "emulsion"].iter().any(|form| formulation.contains(form)) {
+            DoseUnit::Spoon
+        } else if ["solution", "liquid", "saline"].iter().any(|form| formulation.contains(form)) {
+            DoseUnit::Millilitre
+        } else if ["cream", "ointment", "gel", "lotion", "shampoo", "paste"].iter().any(|form| formulation.contains(form)) {
+            DoseUnit::Application
+        } else {
+            return None;
+        };
+        Some(unit)
+    }
+
+    pub fn english(&self, quantity: f64) -> &'static str {
+        let singular = quantity <= 1.0;
+        match self {
+            DoseUnit::Tablet => if singular { "tablet" } else { "tablets" },
+            DoseUnit::Capsule => if singular { "capsule" } else { "capsules" },
+            DoseUnit::Spoon => if singular { "teaspoon" } else { "teaspoons" },
+            DoseUnit::Millilitre => "ml",
+            DoseUnit::Drop => if singular { "drop" } else { "drops" },
+            DoseUnit::Puff => if singular { "puff" } else { "puffs" },
+            DoseUnit::Sachet => if singular { "sachet" } else { "sachets" },
+            DoseUnit::Suppository => if singular { "suppository" } else { "suppositories" },
+            DoseUnit::Application => if singular { "application" } else { "applications" },
+            DoseUnit::Injection => if singular { "injection" } else { "injections" },
+        }
+    }
+
+    pub fn bangla(&self) -> &'static str {
+        match self {
+            DoseUnit::Tablet => "ট্যাবলেট",
+            DoseUnit::Capsule => "ক্যাপসুল",
+            DoseUnit::Spoon => "চামচ",
+            DoseUnit::Millilitre => "মিলি",
+            DoseUnit::Drop => "ফোঁটা",
+            DoseUnit::Puff => "চাপ",
+            DoseUnit::Sachet => "স্যাশে",
+            DoseUnit::Suppository => "সাপোজিটরি",
+            DoseUnit::Application => "বার",
+            DoseUnit::Injection => "ইনজেকশন",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Frequency {
+    #[default]
+    Daily,
+    EveryOtherDay,
+    Weekly,
+    /// Only when needed (SOS), e.g. for fever or pain.
+    AsNeeded,
+}
+
+impl Frequency {
+    const WORDS: &[(Frequency, &[&str])] = &[
+        (Frequency::EveryOtherDay, &["every other day", "alternate day", "একদিন পর পর", "এক দিন পর পর"]),
+        (Frequency::Weekly, &["weekly", "once a week", "সপ্তাহে"]),
+        (Frequency::AsNeeded, &["as needed", "if needed", "when needed", "sos", "prn", "প্রয়োজনে", "প্রয়োজন হলে"]),
+        (Frequency::Daily, &["daily", "every day", "প্রতিদিন", "রোজ"]),
+    ];
+
+    /// Finds the frequency mentioned anywhere in `text`.
+    pub fn find(text: &str) -> Option<Self> {
+        find_keyword(text, Self::WORDS)
+    }
+
+    /// Fraction of days a dose is taken on, `None` for [`Frequency::AsNeeded`].
+    pub fn days_fraction(&self) -> Option<f64> {
+        match self {
+            Frequency::Daily => Some(1.0),
+            Frequency::EveryOtherDay => Some(0.5),
+            Frequency::Weekly => Some(1.0 / 7.0),
+            Frequency::AsNeeded => None,
+        }
+    }
+
+    pub fn english(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "daily",
+            Frequency::EveryOtherDay => "every other day",
+            Frequency::Weekly => "once a week",
+            Frequency::AsNeeded => "when needed",
+        }
+    }
+
+    pub fn bangla(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "প্রতিদিন",
+            Frequency::EveryOtherDay => "একদিন পর পর",
+            Frequency::Weekly => "সপ্তাহে একবার",
+            Frequency::AsNeeded => "প্রয়োজন হলে",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MealTiming {
+    BeforeMeal,
+    AfterMeal,
+    WithMeal,
+    EmptyStomach,
+}
+
+impl MealTiming {
+    const WORDS: &[(MealTiming, &[&str])] = &[
+        (MealTiming::EmptyStomach, &["empty stomach", "খালি পেটে"]),
+        (MealTiming::BeforeMeal, &["before meal", "before food", "খাবারের আগে", "খাওয়ার আগে"]),
+        (MealTiming::AfterMeal, &["after meal", "after food", "খাবারের পরে", "খাবার পরে", "খাওয়ার পরে", "ভরা পেটে"]),
+        (MealTiming::WithMeal, &["with meal", "with food", "খাবারের সাথে", "খাবারের সময়"]),
+    ];
+
+    /// Finds the meal timing mentioned anywhere in `text`, typically the instructions.
+    pub fn find(text: &str) -> Option<Self> {
+        find_keyword(text, Self::WORDS)
+    }
+
+    pub fn english(&self) -> &'static str {
+        match self {
+            MealTiming::BeforeMeal => "before meal",
+            MealTiming::AfterMeal => "after meal",
+            MealTiming::WithMeal => "with meal",
+            MealTiming::EmptyStomach => "on an empty stomach",
+        }
+    }
+
+    pub fn bangla(&self) -> &'static str {
+        match self {
+            MealTiming::BeforeMeal => "খাবারের আগে",
+            MealTiming::AfterMeal => "খাবারের পরে",
+            MealTiming::WithMeal => "খাবারের সাথে",
+            MealTiming::EmptyStomach => "খালি পেটে",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationUnit {
+    Days,
+    Weeks,
+    Months,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DosingDuration {
+    pub amount: u32,
+    pub unit: DurationUnit,
+}
+
+impl DosingDuration {
+    /// Parses durations such as `"7 days"`, `"2 weeks"`, `"1 month"` or `"৭ দিন"`. A bare number is a number of days.
+    pub fn parse(value: &str) -> Option<Self> {
+        let normalized = normalize_digits(value).trim().to_lowercase();
+        let amount_end = normalized.find(|c: char| !c.is_ascii_digit()).unwrap_or(normalized.len());
+        let amount = normalized[..amount_end].parse::<u32>().ok().filter(|amount| *amount > 0)?;
+        let unit = normalized[amount_end..].trim();
+        let unit = if unit.is_empty() || unit.starts_with('d') || unit.starts_with("দিন") {
+            DurationUnit::Days
+        } else if unit.starts_with('w') || unit.starts_with("সপ্তাহ") {
+            DurationUnit::Weeks
+        } else if unit.starts_with('m') || unit.starts_with("মাস") {
+            DurationUnit::Months
+        } else {
+            return None;
+        };
+        Some(Self { amount, unit })
+    }
+
+    /// Like [`DosingDuration::parse`], but only when the unit is written out as a whole word, so
+    /// `"7 days then stop"` or `"1 month, review"` is not read as a plain course length.
+    pub fn parse_strict(value: &str) -> Option<Self> {
+        const UNITS: &[&str] = &[
+            "", "d", "day", "days", "w", "wk", "wks", "week", "weeks", "m", "mo", "month", "months", "দিন", "সপ্তাহ", "মাস",
+        ];
+        let duration = Self::parse(value)?;
+        let normalized = normalize_digits(value).trim().to_lowercase();
+        let unit = normalized.trim_start_matches(|c: char| c.is_ascii_digit()).trim();
+        UNITS.contains(&unit).then_some(duration)
+    }
+
+    /// Length in days, counting a month as 30 days.
+    pub fn days(&self) -> u32 {
+        match self.unit {
+            DurationUnit::Days => self.amount,
+            DurationUnit::Weeks => self.amount * 7,
+            DurationUnit::Months => self.amount * 30,
+        }
+    }
+
+    pub fn english(&self) -> String {
+        let unit = match (self.unit, self.amount == 1) {
+            (DurationUnit::Days, true) => "day",
+            (DurationUnit::Days, false) => "days",
+            (DurationUnit::Weeks, true) => "week",
+            (DurationUnit::Weeks, false) => "weeks",
+            (DurationUnit::Months, true) => "month",
+            (DurationUnit::Months, false) => "months",
+        };
+        format!("{} {}", self.amount, unit)
+    }
+
+    pub fn bangla(&self) -> String {
+        let unit = match self.unit {
+            DurationUnit::Days => "দিন",
+            DurationUnit::Weeks => "সপ্তাহ",
+            DurationUnit::Months => "মাস",
+        };
+        format!("{} {}", bangla_digits(&self.amount.to_string()), unit)
+    }
+}
+
+/// Structured form of a prescription line's dosing, instructions and duration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DosingSchedule {
+    pub morning: f64,
+    pub noon: f64,
+    pub night: f64,
+    /// `None` when the shorthand does not say, e.g. a bare `"1+0+1"`; see [`DoseUnit::from_formulation`].
+    pub unit: Option<DoseUnit>,
+    pub frequency: Frequency,
+    pub meal_timing: Option<MealTiming>,
+    pub duration: Option<DosingDuration>,
+}
+
+impl DosingSchedule {
+    /// Parses the dosing shorthand `"morning+noon+night"` as typed in the dosing box, e.g. `"1+0+1"`,
+    /// `"½+0+½"`, `"১+০+১"` or `"2+2+2 tsp"`. Two parts are morning and night (`"1+1"`), a single
+    /// number is a morning dose. Unit, frequency and meal timing may follow the numbers.
+    pub fn parse(dosing: &str) -> Option<Self> {
+        Self::parse_with_rest(dosing).map(|(schedule, _)| schedule)
+    }
+
+    /// Like [`DosingSchedule::parse`], but only when nothing but the unit, frequency and meal timing follows
+    /// the numbers. `"1+0+1 for 3 days then 1+0+0"` or `"0+0+1 if fever > 102"` say more than a schedule can.
+    pub fn parse_strict(dosing: &str) -> Option<Self> {
+        let (schedule, rest) = Self::parse_with_rest(dosing)?;
+        let mut rest = rest.trim().to_lowercase();
+        if let Some(word) = DoseUnit::WORDS.iter().flat_map(|(_, words)| words.iter()).find(|word| rest.starts_with(*word)) {
+            // plurals and longer spellings, "tabs" or "tablets"
+            rest = rest[word.len()..].trim_start_matches(char::is_alphabetic).to_string();
+        }
+        let words = Frequency::WORDS.iter().flat_map(|(_, words)| words.iter())
+            .chain(MealTiming::WORDS.iter().flat_map(|(_, words)| words.iter()));
+        for word in words {
+            rest = rest.replace(word, " ");
+        }
+        rest.chars().all(|c| c.is_whitespace() || matches!(c, ',' | '.' | ';' | '-' | '(' | ')')).then_some(schedule)
+    }
+
+    /// The schedule and the text after the numbers, which is left for the unit, frequency and meal timing.
+    fn parse_with_rest(dosing: &str) -> Option<(Self, String)> {
+        let normalized = normalize_digits(dosing);
+        let normalized = normalized.trim();
+        let numbers_end = normalized
+            .find(|c: char| !(c.is_ascii_digit() || c.is_whitespace() || matches!(c, '+' | '.' | '½' | '¼' | '¾' | '/')))
+            .unwrap_or(normalized.len());
+        let (numbers, rest) = normalized.split_at(numbers_end);
+
+        let doses = numbers.split('+').map(parse_quantity).collect::<Option<Vec<f64>>>()?;
+        let (morning, noon, night) = match doses[..] {
+            [morning] => (morning, 0.0, 0.0),
+            [morning, night] => (morning, 0.0, night),
+            [morning, noon, night] => (morning, noon, night),
+            _ => return None,
+        };
+        if morning + noon + night <= 0.0 {
+            return None;
+        }
+        let schedule = Self {
+            morning,
+            noon,
+            night,
+            unit: DoseUnit::parse(rest),
+            frequency: Frequency::find(rest).unwrap_or_default(),
+            meal_timing: MealTiming::find(rest),
+            duration: None,
+        };
+        Some((schedule, rest.to_string()))
+    }
+
+    /// Builds the schedule of a prescription line: dosing shorthand from `dosing`, meal timing and
+    /// frequency from `instructions`, the duration, and the unit from the formulation when not written.
+    pub fn from_medicine(medicine: &MedicineData) -> Option<Self> {
+        let mut schedule = Self::parse(&medicine.dosing)?;
+        schedule.unit = schedule.unit.or_else(|| DoseUnit::from_formulation(&medicine.formulation));
+        schedule.meal_timing = schedule.meal_timing.or_else(|| MealTiming::find(&medicine.instructions));
+        if schedule.frequency == Frequency::Daily {
+            schedule.frequency = Frequency::find(&medicine.instructions).unwrap_or_default();
+        }
+        schedule.duration = DosingDuration::parse(&medicine.duration);
+        Some(schedule)
+    }
+
+    /// Units taken on a day the medicine is taken.
+    pub fn units_per_day(&self) -> f64 {
+        self.morning + self.noon + self.night
+    }
+
+    /// Number of times a day a dose is taken.
+    pub fn doses_per_day(&self) -> usize {
+        [self.morning, self.noon, self.night].iter().filter(|dose| **dose > 0.0).count()
+    }
+
+    /// Units needed for the whole course, `None` without a duration or for as-needed medicines.
+    pub fn total_units(&self) -> Option<f64> {
+        let days = self.duration?.days() as f64;
+        Some((self.units_per_day() * days * self.frequency.days_fraction()?).ceil())
+    }
+
+    /// The `"1+0+1"` notation.
+    pub fn shorthand(&self) -> String {
+        [self.morning, self.noon, self.night].iter().map(|dose| format_quantity(*dose)).collect::<Vec<String>>().join("+")
+    }
+
+    /// E.g. `"1 tablet in the morning and 1 tablet at night, after meal, for 7 days"`.
+    pub fn render_english(&self) -> String {
+        let doses: Vec<String> = self
+            .slots()
+            .map(|(quantity, slot)| {
+                let quantity_text = format_quantity(quantity);
+                let slot = match slot {
+                    Slot::Morning => "in the morning",
+                    Slot::Noon => "at noon",
+                    Slot::Night => "at night",
+                };
+                match self.unit {
+                    Some(unit) => format!("{} {} {}", quantity_text, unit.english(quantity), slot),
+                    None => format!("{} {}", quantity_text, slot),
+                }
+            })
+            .collect();
+        let mut parts = vec![join_list(&doses, "and")];
+        if self.frequency != Frequency::Daily {
+            parts.push(self.frequency.english().to_string());
+        }
+        if let Some(meal_timing) = self.meal_timing {
+            parts.push(meal_timing.english().to_string());
+        }
+        if let Some(duration) = self.duration {
+            parts.push(format!("for {}", duration.english()));
+        }
+        parts.join(", ")
+    }
+
+    /// E.g. `"সকালে ১ ট্যাবলেট ও রাতে ১ ট্যাবলেট, খাবারের পরে, ৭ দিন"`.
+    pub fn render_bangla(&self) -> String {
+        let doses: Vec<String> = self
+            .slots()
+            .map(|(quantity, slot)| {
+                let slot = match slot {
+                    Slot::Morning => "সকালে",
+                    Slot::Noon => "দুপুরে",
+                    Slot::Night => "রাতে",
+                };
+                let quantity = bangla_digits(&format_quantity(quantity));
+                match self.unit {
+                    Some(unit) => format!("{} {} {}", slot, quantity, unit.bangla()),
+                    None => format!("{} {}টি", slot, quantity),
+                }
+            })
+            .collect();
+        let mut parts = vec![join_list(&doses, "ও")];
+        if self.frequency != Frequency::Daily {
+            parts.push(self.frequency.bangla().to_string());
+        }
+        if let Some(meal_timing) = self.meal_timing {
+            parts.push(meal_timing.bangla().to_string());
+        }
+        if let Some(duration) = self.duration {
+            parts.push(duration.bangla());
+        }
+        parts.join(", ")
+    }
+
+    fn slots(&self) -> impl Iterator<Item = (f64, Slot)> {
+        [(self.morning, Slot::Morning), (self.noon, Slot::Noon), (self.night, Slot::Night)]
+            .into_iter()
+            .filter(|(quantity, _)| *quantity > 0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    Morning,
+    Noon,
+    Night,
+}
+
+/// Parses one dose: `"1"`, `"0.5"`, `"½"`, `"1½"` or `"1/2"`.
+fn parse_quantity(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    if let Some((numerator, denominator)) = value.split_once('/') {
+        let denominator = denominator.trim().parse::<f64>().ok().filter(|d| *d > 0.0)?;
+        return Some(numerator.trim().parse::<f64>().ok()? / denominator);
+    }
+    let (whole, fraction) = match value.char_indices().last() {
+        Some((index, '½')) => (&value[..index], 0.5),
+        Some((index, '¼')) => (&value[..index], 0.25),
+        Some((index, '¾')) => (&value[..index], 0.75),
+        _ => (value, 0.0),
+    };
+    let whole = if whole.is_empty() && fraction > 0.0 { 0.0 } else { whole.trim().parse::<f64>().ok()? };
+    Some(whole + fraction)
+}
+
+/// Writes a dose the way it is written on a prescription: `1`, `½`, `1½`, `0.75` for anything else.
+pub fn format_quantity(quantity: f64) -> String {
+    let whole = quantity.trunc();
+    let fraction = match quantity - whole {
+        f if f.abs() < 1e-9 => "",
+        f if (f - 0.5).abs() < 1e-9 => "½",
+        f if (f - 0.25).abs() < 1e-9 => "¼",
+        f if (f - 0.75).abs() < 1e-9 => "¾",
+        _ => return format!("{:.2}", quantity).trim_end_matches('0').trim_end_matches('.').to_string(),
+    };
+    match (whole as u64, fraction) {
+        (whole, "") => whole.to_string(),
+        (0, fraction) => fraction.to_string(),
+        (whole, fraction) => format!("{}{}", whole, fraction),
+    }
+}
+
+fn join_list(parts: &[String], conjunction: &str) -> String {
+    match parts {
+        [] => String::new(),
+        [only] => only.clone(),
+        [init @ .., last] => format!("{} {} {}", init.join(", "), conjunction, last),
+    }
+}
+
+fn find_keyword<T: Copy>(text: &str, table: &[(T, &[&str])]) -> Option<T> {
+    let text = text.to_lowercase();
+    table
+        .iter()
+        .find(|(_, words)| words.iter().any(|word| text.contains(word)))
+        .map(|(value, _)| *value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn medicine(formulation: &str, dosing: &str, instructions: &str, duration: &str) -> MedicineData {
+        MedicineData {
+            dosing: dosing.into(),
+            instructions: instructions.into(),
+            duration: duration.into(),
+            ..MedicineData::for_test("Generic", "", formulation)
+        }
+    }
+
+    #[test]
+    fn parses_shorthand() {
+        let schedule = DosingSchedule::parse("1+0+1").unwrap();
+        assert_eq!((schedule.morning, schedule.noon, schedule.night), (1.0, 0.0, 1.0));
+        assert_eq!(schedule.units_per_day(), 2.0);
+        assert_eq!(schedule.doses_per_day(), 2);
+
+        let schedule = DosingSchedule::parse("½+0+½").unwrap();
+        assert_eq!(schedule.units_per_day(), 1.0);
+        assert_eq!(DosingSchedule::parse("১+১+১").unwrap().units_per_day(), 3.0);
+        assert_eq!(DosingSchedule::parse("1½+0+1/2").unwrap().units_per_day(), 2.0);
+    }
+
+    #[test]
+    fn two_parts_are_morning_and_night() {
+        let schedule = DosingSchedule::parse("1+1").unwrap();
+        assert_eq!((schedule.morning, schedule.noon, schedule.night), (1.0, 0.0, 1.0));
+        assert_eq!(DosingSchedule::parse("2").unwrap().morning, 2.0);
+    }
+
+    #[test]
+    fn rejects_empty_or_malformed_shorthand() {
+        assert!(DosingSchedule::parse("0+0+0").is_none());
+        assert!(DosingSchedule::parse("").is_none());
+        assert!(DosingSchedule::parse("1+1+1+1").is_none());
+        assert!(DosingSchedule::parse("as directed").is_none());
+    }
+
+    #[test]
+    fn reads_unit_frequency_and_meal_timing_after_the_numbers() {
+        let schedule = DosingSchedule::parse("2+2+2 tsp").unwrap();
+        assert_eq!(schedule.unit, Some(DoseUnit::Spoon));
+        let schedule = DosingSchedule::parse("1+0+0 tab sos").unwrap();
+        assert_eq!(schedule.unit, Some(DoseUnit::Tablet));
+        assert_eq!(schedule.frequency, Frequency::AsNeeded);
+    }
+
+    #[test]
+    fn strict_parsing_needs_the_whole_shorthand() {
+        assert!(DosingSchedule::parse_strict("1+0+1").is_some());
+        assert!(DosingSchedule::parse_strict("2+2+2 tsp after meal").is_some());
+        assert!(DosingSchedule::parse_strict("1+0+0 tablets, sos").is_some());
+        assert!(DosingSchedule::parse_strict("1+0+1 for 3 days then 1+0+0").is_none());
+        assert!(DosingSchedule::parse_strict("0+0+1 if fever > 102").is_none());
+    }
+
+    #[test]
+    fn strict_parsing_needs_the_whole_duration() {
+        assert_eq!(DosingDuration::parse_strict("7 days").unwrap().days(), 7);
+        assert_eq!(DosingDuration::parse_strict("৭ দিন").unwrap().days(), 7);
+        assert_eq!(DosingDuration::parse_strict("2").unwrap().days(), 2);
+        assert!(DosingDuration::parse_strict("7 days then stop").is_none());
+        assert!(DosingDuration::parse_strict("চলবে").is_none());
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(DosingDuration::parse("7 days").unwrap().days(), 7);
+        assert_eq!(DosingDuration::parse("2 weeks").unwrap().days(), 14);
+        assert_eq!(DosingDuration::parse("1 month").unwrap().days(), 30);
+        assert_eq!(DosingDuration::parse("৭ দিন").unwrap().days(), 7);
+        assert_eq!(DosingDuration::parse("5").unwrap().days(), 5);
+        assert!(DosingDuration::parse("0 days").is_none());
+        assert!(DosingDuration::parse("continue").is_none());
+    }
+
+    #[test]
+    fn total_units_is_units_per_day_times_days() {
+        let schedule = DosingSchedule::from_medicine(&medicine("Tablet", "1+0+1", "", "7 days")).unwrap();
+        assert_eq!(schedule.total_units(), Some(14.0));
+        let schedule = DosingSchedule::from_medicine(&medicine("Tablet", "½+0+½", "", "5 days")).unwrap();
+        assert_eq!(schedule.total_units(), Some(5.0));
+        let schedule = DosingSchedule::from_medicine(&medicine("Tablet", "1+0+0", "every other day", "9 days")).unwrap();
+        assert_eq!(schedule.total_units(), Some(5.0));
+    }
+
+    #[test]
+    fn total_units_needs_a_duration_and_a_regular_frequency() {
+        let schedule = DosingSchedule::from_medicine(&medicine("Tablet", "1+0+1", "", "")).unwrap();
+        assert_eq!(schedule.total_units(), None);
+        let schedule = DosingSchedule::from_medicine(&medicine("Tablet", "1+0+1", "if needed", "5 days")).unwrap();
+        assert_eq!(schedule.total_units(), None);
+    }
+
+    #[test]
+    fn from_medicine_fills_in_unit_and_meal_timing() {
+        let schedule = DosingSchedule::from_medicine(&medicine("Suspension", "1+1+1", "after meal", "5 days")).unwrap();
+        assert_eq!(schedule.unit, Some(DoseUnit::Spoon));
+        assert_eq!(schedule.meal_timing, MealTiming::find("after meal"));
+        assert!(schedule.meal_timing.is_some());
+    }
+
+    #[test]
+    fn renders_english() {
+        let schedule = DosingSchedule::from_medicine(&medicine("Tablet", "1+0+1", "", "7 days")).unwrap();
+        assert_eq!(schedule.render_english(), "1 tablet in the morning and 1 tablet at night, for 7 days");
+        assert_eq!(schedule.shorthand(), "1+0+1");
+    }
+
+    #[test]
+    fn formats_quantities() {
+        assert_eq!(format_quantity(1.0), "1");
+        assert_eq!(format_quantity(0.5), "½");
+        assert_eq!(format_quantity(1.5), "1½");
+        assert_eq!(format_quantity(0.75), "¾");
+        assert_eq!(format_quantity(0.3), "0.3");
+    }
+}
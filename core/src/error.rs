@@ -0,0 +1,54 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum DbError {
+    Io(std::io::Error),
+    Sqlite(rusqlite::Error),
+    Migration(rusqlite_migration::Error),
+    NotFound,
+    InvalidInput(String),
+    Pdf(String),
+}
+
+pub type DbResult<T> = Result<T, DbError>;
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbError::Io(err) => write!(f, "io error: {}", err),
+            DbError::Sqlite(err) => write!(f, "database error: {}", err),
+            DbError::Migration(err) => write!(f, "migration error: {}", err),
+            DbError::NotFound => write!(f, "record not found"),
+            DbError::InvalidInput(message) => write!(f, "invalid input: {}", message),
+            DbError::Pdf(message) => write!(f, "pdf error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for DbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DbError::Io(err) => Some(err),
+            DbError::Sqlite(err) => Some(err),
+            DbError::Migration(err) => Some(err),
+            DbError::NotFound | DbError::InvalidInput(_) | DbError::Pdf(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DbError {
+    fn from(err: std::io::Error) -> Self { DbError::Io(err) }
+}
+
+impl From<rusqlite::Error> for DbError {
+    fn from(err: rusqlite::Error) -> Self {
+        match err {
+            rusqlite::Error::QueryReturnedNoRows => DbError::NotFound,
+            err => DbError::Sqlite(err),
+        }
+    }
+}
+
+impl From<rusqlite_migration::Error> for DbError {
+    fn from(err: rusqlite_migration::Error) -> Self { DbError::Migration(err) }
+}
@@ -1,79 +1,105 @@
 #![allow(dead_code)]
 
-use std::ffi::{CStr, CString};
-use ffi_convert::{AsRust, CStringArray};
-
-use crate::db::*;
+use crate::db::{drug_repository, query_or_default};
+use crate::dose_limits::DoseWarning;
+use crate::error::DbResult;
+use crate::ffi::prescription::MedicineData;
+use crate::interactions::InteractionWarning;
+use crate::models::drug::{Drug, DrugAlternative};
+use crate::pricing::CostEstimate;
+use crate::safety::{AllergyWarning, ContraindicationWarning};
 
 pub fn get_generic_names() -> Vec<String> {
-    let c_string_array: CStringArray = get_generic_names_c();
-    c_string_array.as_rust().unwrap() 
+    query_or_default(|repository| repository.get_generic_names())
 }
 
 pub fn get_generic_name_by_brand_name(brand_name: String) -> String {
-    let brand_name_cstr = CString::new(brand_name).unwrap_or_default();
-    let generic_name_cstr = get_generic_name_by_brand_name_c(brand_name_cstr.as_ptr());
-    let generic_name_str = unsafe { CStr::from_ptr(generic_name_cstr) };
-    generic_name_str.as_rust().unwrap()
+    query_or_default(|repository| repository.get_generic_name_by_brand_name(&brand_name)).unwrap_or_default()
 }
 
 pub fn get_manufacturer_by_brand_name(brand_name: String) -> String {
-    let brand_name_cstr = CString::new(brand_name).unwrap_or_default();
-    let manufacturer_cstr = get_manufacturer_by_brand_name_c(brand_name_cstr.as_ptr());
-    let manufacturer_str = unsafe { CStr::from_ptr(manufacturer_cstr) };
-    manufacturer_str.as_rust().unwrap()
+    query_or_default(|repository| repository.get_manufacturer_by_brand_name(&brand_name)).unwrap_or_default()
 }
 
 pub fn get_brand_name_by_generic_name_manufacturer_and_strength(generic_name: String, manufacturer: String, strength: String) -> Option<String> {
-    let generic_name_cstr = CString::new(generic_name).unwrap_or_default();
-    let manufacturer_cstr = CString::new(manufacturer).unwrap_or_default();
-    let strength_cstr = CString::new(strength).unwrap_or_default();
-    let brand_name_cstr = get_brand_name_by_generic_name_manufacturer_and_strength_c(
-        generic_name_cstr.as_ptr(), manufacturer_cstr.as_ptr(), strength_cstr.as_ptr()
-    );
-    if !brand_name_cstr.is_null() {
-        let brand_name_str = unsafe { CStr::from_ptr(brand_name_cstr) };
-        Some(brand_name_str.as_rust().unwrap())
-    } else {
-        None
-    }
+    query_or_default(|repository| {
+        repository.get_brand_name_by_generic_name_manufacturer_and_strength(&generic_name, &manufacturer, &strength)
+    })
 }
 
 pub fn get_brand_name_by_generic_name_and_manufacturer(generic_name: String, manufacturer: String) -> String {
-    let generic_name_cstr = CString::new(generic_name).unwrap_or_default();
-    let manufacturer_cstr = CString::new(manufacturer).unwrap_or_default();
-    let brand_name_cstr = get_brand_name_by_generic_name_and_manufacturer_c(generic_name_cstr.as_ptr(), manufacturer_cstr.as_ptr());
-    let brand_name_str = unsafe { CStr::from_ptr(brand_name_cstr) };
-    brand_name_str.as_rust().unwrap()
+    query_or_default(|repository| {
+        repository.get_brand_name_by_generic_name_and_manufacturer(&generic_name, &manufacturer)
+    }).unwrap_or_default()
 }
 
 pub fn get_formulations_by_brand_name_and_strength(brand_name: String, strength: String) -> Vec<String> {
-    let brand_name_cstr = CString::new(brand_name).unwrap_or_default();
-    let strength_cstr = CString::new(strength).unwrap_or_default();
-    let c_string_array = get_formulations_by_brand_name_and_strength_c(
-        brand_name_cstr.as_ptr(), strength_cstr.as_ptr()
-    );
-    c_string_array.as_rust().unwrap()
+    query_or_default(|repository| repository.get_formulations_by_brand_name_and_strength(&brand_name, &strength))
 }
 
 pub fn get_strengths_by_generic_name(generic_name: String) -> Vec<String> {
-    let generic_name_cstr = CString::new(generic_name).unwrap_or_default();
-    let c_string_array = get_strengths_by_generic_name_c(generic_name_cstr.as_ptr());
-    c_string_array.as_rust().unwrap()
+    query_or_default(|repository| repository.get_strengths_by_generic_name(&generic_name))
 }
 
 pub fn get_brand_names() -> Vec<String> {
-    let c_string_array: CStringArray = get_brand_names_c();
-    c_string_array.as_rust().unwrap() 
+    query_or_default(|repository| repository.get_brand_names())
 }
 
 pub fn get_manufacturers() -> Vec<String> {
-    let c_string_array: CStringArray = get_manufacturers_c();
-    c_string_array.as_rust().unwrap() 
+    query_or_default(|repository| repository.get_manufacturers())
 }
 
 pub fn get_manufacturers_by_generic_name(generic_name: String) -> Vec<String> {
-    let generic_name_cstr = CString::new(generic_name).unwrap_or_default();
-    let c_string_array = get_manufacturers_by_generic_name_c(generic_name_cstr.as_ptr());
-    c_string_array.as_rust().unwrap()
+    query_or_default(|repository| repository.get_manufacturers_by_generic_name(&generic_name))
+}
+
+pub fn search_drugs(query: String, limit: usize) -> Vec<Drug> {
+    query_or_default(|repository| repository.search_drugs(&query, limit))
+}
+
+pub fn get_unit_price(brand_name: String, strength: String, formulation: String) -> Option<f64> {
+    query_or_default(|repository| repository.get_unit_price(&brand_name, &strength, &formulation))
+}
+
+pub fn estimate_prescription_cost(items: &[MedicineData]) -> CostEstimate {
+    query_or_default(|repository| repository.estimate_prescription_cost(items))
+}
+
+pub fn get_alternatives(brand_name: String, strength: String, formulation: String) -> Vec<DrugAlternative> {
+    query_or_default(|repository| repository.get_alternatives(&brand_name, &strength, &formulation))
+}
+
+pub fn get_indications_by_generic_name(generic_name: String) -> Vec<String> {
+    query_or_default(|repository| repository.get_indications_by_generic_name(&generic_name))
+}
+
+pub fn get_contraindications_by_generic_name(generic_name: String) -> Vec<String> {
+    query_or_default(|repository| repository.get_contraindications_by_generic_name(&generic_name))
+}
+
+pub fn get_indications() -> Vec<String> {
+    query_or_default(|repository| repository.get_indications())
+}
+
+pub fn get_generic_names_by_indication(indication: String) -> Vec<String> {
+    query_or_default(|repository| repository.get_generic_names_by_indication(&indication))
+}
+
+/// Database errors are returned rather than read as "not contraindicated", like [`check_allergies`].
+pub fn check_contraindications(medicine: &MedicineData, conditions: &[String]) -> DbResult<Vec<ContraindicationWarning>> {
+    drug_repository()?.check_contraindications(medicine, conditions)
+}
+
+/// Database errors are returned rather than read as "no allergy", a check that could not run is not a pass.
+pub fn check_allergies(medicine: &MedicineData, allergies: &[String]) -> DbResult<Vec<AllergyWarning>> {
+    drug_repository()?.check_allergies(medicine, allergies)
+}
+
+/// Database errors are returned rather than read as "within the limit", like [`check_allergies`].
+pub fn check_dose(medicine: &MedicineData, weight_kg: Option<f64>, age_years: Option<u32>) -> DbResult<Vec<DoseWarning>> {
+    drug_repository()?.check_dose(medicine, weight_kg, age_years)
+}
+
+pub fn check_interactions(items: &[MedicineData]) -> Vec<InteractionWarning> {
+    query_or_default(|repository| repository.check_interactions(items))
 }
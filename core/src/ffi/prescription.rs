@@ -1,9 +1,10 @@
 use std::ffi::{CStr, CString};
 use ffi_convert::AsRust;
+use crate::models::drug::Drug;
 use crate::models::prescription::{CMedicineData, medicine_new};
 
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MedicineData {
     pub id: u64,
     pub brand_name: String,
@@ -84,4 +85,28 @@ impl MedicineData {
         );
         c_medicine_data.as_rust().unwrap()
     }
+
+    /// The same line with another brand, keeping its id, dosing, instructions and duration.
+    pub fn with_drug(&self, drug: &Drug) -> Self {
+        Self {
+            brand_name: drug.brand_name.clone(),
+            generic_name: drug.generic_name.clone(),
+            strength: drug.strength.clone(),
+            formulation: drug.dosage_form.clone(),
+            manufacturer: drug.manufacturer.clone(),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+impl MedicineData {
+    /// A line of brand `"Brand"` with the fields the checks in this crate look at. Dosing, instructions
+    /// and duration are left empty, set them with struct update syntax.
+    pub(crate) fn for_test(generic_name: &str, strength: &str, formulation: &str) -> Self {
+        Self::new(
+            "Brand".into(), generic_name.into(), strength.into(), formulation.into(),
+            String::new(), String::new(), String::new(), String::new(),
+        )
+    }
 }
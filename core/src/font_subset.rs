@@ -0,0 +1,336 @@
+use std::collections::BTreeSet;
+
+/// Tables kept in a subset: what a PDF viewer needs to draw the glyphs and what printpdf reads
+/// for the widths and the `ToUnicode` map. Layout tables are dropped, text is shaped before it is drawn.
+/// `cmap` is rebuilt with the kept glyphs only, printpdf writes a width and a `ToUnicode` entry for
+/// every glyph it maps.
+const KEPT_TABLES: &[&[u8; 4]] = &[
+    b"cmap", b"cvt ", b"fpgm", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"OS/2", b"post", b"prep",
+];
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+struct Table<'a> {
+    tag: [u8; 4],
+    data: &'a [u8],
+}
+
+/// Copy of the TrueType font `data` in which every glyph outside `glyphs` (and the components of
+/// composite glyphs) is left empty. Glyph ids are unchanged, so text drawn with the full font can
+/// be embedded with the subset. `None` for fonts that cannot be subset this way, such as CFF
+/// based OpenType fonts or collections; those are embedded in full.
+pub fn subset(data: &[u8], glyphs: &BTreeSet<u16>) -> Option<Vec<u8>> {
+    let tables = read_tables(data)?;
+    let table = |tag: &[u8; 4]| tables.iter().find(|table| &table.tag == tag).map(|table| table.data);
+    let head = table(b"head")?;
+    let glyf = table(b"glyf")?;
+    let loca = table(b"loca")?;
+    let glyph_count = read_u16(table(b"maxp")?, 4)? as usize;
+    let long_offsets = read_u16(head, 50)? != 0;
+    let offsets = (0..=glyph_count)
+        .map(|index| match long_offsets {
+            true => read_u32(loca, index * 4).map(|offset| offset as usize),
+            false => read_u16(loca, index * 2).map(|offset| offset as usize * 2),
+        })
+        .collect::<Option<Vec<usize>>>()?;
+    let glyph_data = |id: usize| -> Option<&[u8]> {
+        let (start, end) = (*offsets.get(id)?, *offsets.get(id + 1)?);
+        glyf.get(start..end.max(start))
+    };
+
+    // .notdef is always kept, composite glyphs pull in their components
+    let mut kept = BTreeSet::new();
+    let mut pending: Vec<u16> = glyphs.iter().copied().filter(|id| (*id as usize) < glyph_count).collect();
+    pending.push(0);
+    while let Some(id) = pending.pop() {
+        if !kept.insert(id) {
+            continue;
+        }
+        for component in composite_components(glyph_data(id as usize)?)? {
+            if (component as usize) < glyph_count && !kept.contains(&component) {
+                pending.push(component);
+            }
+        }
+    }
+
+    let mut new_glyf = Vec::new();
+    let mut new_loca = Vec::with_capacity((glyph_count + 1) * 4);
+    for id in 0..glyph_count {
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        if kept.contains(&(id as u16)) {
+            new_glyf.extend_from_slice(glyph_data(id)?);
+            pad(&mut new_glyf);
+        }
+    }
+    new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+
+    let mut new_head = head.to_vec();
+    new_head.get_mut(8..12)?.copy_from_slice(&[0; 4]);
+    new_head.get_mut(50..52)?.copy_from_slice(&1u16.to_be_bytes());
+    // version 3 drops the glyph names
+    let new_post = table(b"post").map(|post| {
+        let mut post = post.get(..32).unwrap_or(post).to_vec();
+        if post.len() == 32 {
+            post[..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+        }
+        post
+    });
+
+    let new_cmap = unicode_cmap(data, &kept)?;
+
+    let mut output: Vec<([u8; 4], Vec<u8>)> = Vec::new();
+    for table in tables.iter().filter(|table| KEPT_TABLES.contains(&&table.tag)) {
+        let data = match &table.tag {
+            b"glyf" => new_glyf.clone(),
+            b"loca" => new_loca.clone(),
+            b"head" => new_head.clone(),
+            b"post" => new_post.clone().unwrap_or_default(),
+            b"cmap" => new_cmap.clone(),
+            _ => table.data.to_vec(),
+        };
+        output.push((table.tag, data));
+    }
+    output.sort_by_key(|(tag, _)| *tag);
+    Some(write_font(read_u32(data, 0)?, &output))
+}
+
+fn read_tables(data: &[u8]) -> Option<Vec<Table<'_>>> {
+    let version = read_u32(data, 0)?;
+    if version != 0x0001_0000 && version != u32::from_be_bytes(*b"true") {
+        return None;
+    }
+    let count = read_u16(data, 4)? as usize;
+    (0..count)
+        .map(|index| {
+            let record = 12 + index * 16;
+            let offset = read_u32(data, record + 8)? as usize;
+            let length = read_u32(data, record + 12)? as usize;
+            Some(Table {
+                tag: data.get(record..record + 4)?.try_into().ok()?,
+                data: data.get(offset..offset.checked_add(length)?)?,
+            })
+        })
+        .collect()
+}
+
+/// A `cmap` with a single Windows Unicode (format 12) subtable mapping the characters of `data`
+/// whose glyph is in `kept`.
+fn unicode_cmap(data: &[u8], kept: &BTreeSet<u16>) -> Option<Vec<u8>> {
+    let face = rustybuzz::ttf_parser::Face::parse(data, 0).ok()?;
+    let mut mappings: Vec<(u32, u16)> = Vec::new();
+    for subtable in face.tables().cmap?.subtables.into_iter().filter(|subtable| subtable.is_unicode()) {
+        subtable.codepoints(|codepoint| {
+            if let Some(glyph) = subtable.glyph_index(codepoint).filter(|glyph| glyph.0 != 0 && kept.contains(&glyph.0)) {
+                mappings.push((codepoint, glyph.0));
+            }
+        });
+    }
+    mappings.sort_unstable();
+    mappings.dedup_by_key(|(codepoint, _)| *codepoint);
+
+    // consecutive characters mapped to consecutive glyphs form one group
+    let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+    for (codepoint, glyph) in mappings {
+        match groups.last_mut() {
+            Some((start, end, start_glyph)) if *end + 1 == codepoint && *start_glyph + (codepoint - *start) == glyph as u32 => *end = codepoint,
+            _ => groups.push((codepoint, codepoint, glyph as u32)),
+        }
+    }
+    let length = 16 + groups.len() * 12;
+    let mut cmap = Vec::with_capacity(12 + length);
+    // version, one subtable: Windows (3), Unicode full repertoire (10), at offset 12
+    for value in [0u16, 1, 3, 10] {
+        cmap.extend_from_slice(&value.to_be_bytes());
+    }
+    cmap.extend_from_slice(&12u32.to_be_bytes());
+    cmap.extend_from_slice(&12u16.to_be_bytes());
+    cmap.extend_from_slice(&0u16.to_be_bytes());
+    for value in [length as u32, 0, groups.len() as u32] {
+        cmap.extend_from_slice(&value.to_be_bytes());
+    }
+    for (start, end, start_glyph) in groups {
+        for value in [start, end, start_glyph] {
+            cmap.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    Some(cmap)
+}
+
+/// Glyph ids a composite glyph is made of, empty for a simple glyph.
+fn composite_components(glyph: &[u8]) -> Option<Vec<u16>> {
+    if glyph.is_empty() || read_u16(glyph, 0)? as i16 >= 0 {
+        return Some(Vec::new());
+    }
+    let mut components = Vec::new();
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph, offset)?;
+        components.push(read_u16(glyph, offset + 2)?);
+        offset += 4;
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            offset += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            offset += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            offset += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            return Some(components);
+        }
+    }
+}
+
+fn write_font(version: u32, tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let count = tables.len() as u16;
+    let entry_selector = 15 - count.max(1).leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+    let mut font = Vec::new();
+    font.extend_from_slice(&version.to_be_bytes());
+    for value in [count, search_range, entry_selector, count * 16 - search_range] {
+        font.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, data) in tables {
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&checksum(data).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    let mut head_offset = None;
+    for (tag, data) in tables {
+        if tag == b"head" {
+            head_offset = Some(font.len());
+        }
+        font.extend_from_slice(data);
+        pad(&mut font);
+    }
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn pad(data: &mut Vec<u8>) {
+    data.resize(data.len().next_multiple_of(4), 0);
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustybuzz::ttf_parser::{Face, GlyphId};
+
+    /// One triangle with sides of `size`.
+    fn simple_glyph(size: i16) -> Vec<u8> {
+        let mut glyph = Vec::new();
+        for value in [1, 0, 0, size, size, 2] {
+            glyph.extend_from_slice(&value.to_be_bytes());
+        }
+        glyph.extend_from_slice(&[0, 0, 1, 1, 1]);
+        for delta in [0, size, -size, 0, 0, size] {
+            glyph.extend_from_slice(&delta.to_be_bytes());
+        }
+        glyph
+    }
+
+    fn composite_glyph(component: u16) -> Vec<u8> {
+        let mut glyph = Vec::new();
+        for value in [-1i16, 0, 0, 0, 0] {
+            glyph.extend_from_slice(&value.to_be_bytes());
+        }
+        for value in [ARG_1_AND_2_ARE_WORDS, component, 0, 0] {
+            glyph.extend_from_slice(&value.to_be_bytes());
+        }
+        glyph
+    }
+
+    /// Glyphs: .notdef, `A`, `B` built from `A`, and `C`.
+    fn font() -> Vec<u8> {
+        let glyphs = [simple_glyph(5), simple_glyph(10), composite_glyph(1), simple_glyph(30)];
+        let mut glyf = Vec::new();
+        let mut loca = Vec::new();
+        for glyph in &glyphs {
+            loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+            glyf.extend_from_slice(glyph);
+            pad(&mut glyf);
+        }
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+
+        let mut head = vec![0; 54];
+        head[..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        head[12..16].copy_from_slice(&0x5F0F_3CF5u32.to_be_bytes());
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        head[50..52].copy_from_slice(&1u16.to_be_bytes());
+        let mut hhea = vec![0; 36];
+        hhea[..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        hhea[34..36].copy_from_slice(&(glyphs.len() as u16).to_be_bytes());
+        let hmtx = [500u16, 0].repeat(glyphs.len()).iter().flat_map(|value| value.to_be_bytes()).collect();
+        let mut maxp = 0x0000_5000u32.to_be_bytes().to_vec();
+        maxp.extend_from_slice(&(glyphs.len() as u16).to_be_bytes());
+        let mut cmap = Vec::new();
+        for value in [0u16, 1, 3, 10, 0, 12, 12, 0, 0, 52, 0, 0, 0, 3] {
+            cmap.extend_from_slice(&value.to_be_bytes());
+        }
+        for (character, glyph) in [('A', 1u32), ('B', 2), ('C', 3)] {
+            for value in [character as u32, character as u32, glyph] {
+                cmap.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        write_font(0x0001_0000, &[
+            (*b"cmap", cmap), (*b"glyf", glyf), (*b"head", head), (*b"hhea", hhea),
+            (*b"hmtx", hmtx), (*b"loca", loca), (*b"maxp", maxp),
+        ])
+    }
+
+    #[test]
+    fn keeps_used_glyphs_and_their_components() {
+        let font = font();
+        let subset = subset(&font, &BTreeSet::from([2])).unwrap();
+        let face = Face::parse(&subset, 0).unwrap();
+        assert_eq!(face.number_of_glyphs(), 4);
+        assert!(face.glyph_bounding_box(GlyphId(1)).is_some());
+        assert!(face.glyph_bounding_box(GlyphId(3)).is_none());
+        assert_eq!(face.glyph_hor_advance(GlyphId(3)), Some(500));
+    }
+
+    #[test]
+    fn maps_only_kept_glyphs() {
+        let font = font();
+        let face = Face::parse(&font, 0).unwrap();
+        assert_eq!(face.glyph_index('C'), Some(GlyphId(3)));
+
+        let subset = subset(&font, &BTreeSet::from([2])).unwrap();
+        let face = Face::parse(&subset, 0).unwrap();
+        assert_eq!(face.glyph_index('A'), Some(GlyphId(1)));
+        assert_eq!(face.glyph_index('B'), Some(GlyphId(2)));
+        assert_eq!(face.glyph_index('C'), None);
+    }
+
+    #[test]
+    fn leaves_other_fonts_alone() {
+        assert!(subset(b"OTTO", &BTreeSet::new()).is_none());
+        assert!(subset(&[], &BTreeSet::new()).is_none());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::ffi::prescription::MedicineData;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Minor,
+    Moderate,
+    Major,
+    Contraindicated,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Minor => "minor",
+            Severity::Moderate => "moderate",
+            Severity::Major => "major",
+            Severity::Contraindicated => "contraindicated",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "minor" => Some(Severity::Minor),
+            "moderate" => Some(Severity::Moderate),
+            "major" => Some(Severity::Major),
+            "contraindicated" => Some(Severity::Contraindicated),
+            _ => None,
+        }
+    }
+}
+
+/// A row of the `Interactions` table. Generics are stored without their salt, e.g. `"Warfarin"`
+/// stands for `"Warfarin Sodium"` too, see [`generic_matches`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interaction {
+    pub generic_a: String,
+    pub generic_b: String,
+    pub severity: Severity,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteractionWarning {
+    pub first_medicine_id: u64,
+    pub first_brand_name: String,
+    /// Component of the first medicine's generic that interacts, e.g. `"Calcium"` for `"Calcium + Vitamin D3"`.
+    pub first_generic: String,
+    pub second_medicine_id: u64,
+    pub second_brand_name: String,
+    pub second_generic: String,
+    pub severity: Severity,
+    pub description: String,
+}
+
+impl InteractionWarning {
+    pub fn message(&self) -> String {
+        format!(
+            "{} ({}) and {} ({}): {} interaction. {}",
+            self.first_brand_name, self.first_generic, self.second_brand_name, self.second_generic,
+            self.severity.as_str(), self.description
+        )
+    }
+
+    pub fn involves(&self, medicine_id: u64) -> bool {
+        self.first_medicine_id == medicine_id || self.second_medicine_id == medicine_id
+    }
+}
+
+/// Splits a combination generic such as `"Calcium + Vitamin D3"` into its components.
+pub fn split_generic_components(generic_name: &str) -> Vec<String> {
+    generic_name
+        .split('+')
+        .map(str::trim)
+        .filter(|component| !component.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether `component` is the `generic` listed in an interaction, ignoring case and any trailing
+/// salt or qualifier, so `"Warfarin"` matches `"Warfarin Sodium"` and `"Calcium"` matches `"Calcium Carbonate"`.
+pub fn generic_matches(component: &str, generic: &str) -> bool {
+    let component_words = words(component);
+    let generic_words = words(generic);
+    !generic_words.is_empty() && component_words.starts_with(&generic_words)
+}
+
+/// Checks every pair of lines in `items` against `interactions` and returns each interacting pair,
+/// most severe first. Combination generics are checked component by component.
+pub fn find_interactions(items: &[MedicineData], interactions: &[Interaction]) -> Vec<InteractionWarning> {
+    let components: Vec<Vec<String>> = items.iter().map(|item| split_generic_components(&item.generic_name)).collect();
+    let mut warnings = Vec::new();
+    for (i, first) in items.iter().enumerate() {
+        for (j, second) in items.iter().enumerate().skip(i + 1) {
+            for interaction in interactions {
+                let pair = find_pair(&components[i], &components[j], interaction)
+                    .or_else(|| find_pair(&components[j], &components[i], interaction).map(|(b, a)| (a, b)));
+                if let Some((first_generic, second_generic)) = pair {
+                    warnings.push(InteractionWarning {
+                        first_medicine_id: first.id,
+                        first_brand_name: first.brand_name.clone(),
+                        first_generic: first_generic.clone(),
+                        second_medicine_id: second.id,
+                        second_brand_name: second.brand_name.clone(),
+                        second_generic: second_generic.clone(),
+                        severity: interaction.severity,
+                        description: interaction.description.clone(),
+                    });
+                }
+            }
+        }
+    }
+    // stable sort keeps prescription order within a severity
+    warnings.sort_by_key(|warning| std::cmp::Reverse(warning.severity));
+    warnings
+}
+
+/// Component of `a` matching `generic_a` and component of `b` matching `generic_b`, if any.
+fn find_pair<'a>(a: &'a [String], b: &'a [String], interaction: &Interaction) -> Option<(&'a String, &'a String)> {
+    let from_a = a.iter().find(|component| generic_matches(component, &interaction.generic_a))?;
+    let from_b = b.iter().find(|component| generic_matches(component, &interaction.generic_b))?;
+    Some((from_a, from_b))
+}
+
+fn words(term: &str) -> Vec<String> {
+    term.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
@@ -0,0 +1,89 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use crate::config;
+use crate::error::{DbError, DbResult};
+
+/// Clinic details printed at the top of a prescription and under the doctor's signature.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Letterhead {
+    pub doctor_name: String,
+    /// One entry per printed line, e.g. `"MBBS, FCPS (Medicine)"`, `"Assistant Professor, DMCH"`.
+    pub degrees: Vec<String>,
+    /// Bangladesh Medical & Dental Council registration number.
+    pub bmdc_number: String,
+    pub chamber_name: String,
+    /// May span several lines.
+    pub chamber_address: String,
+    /// Free text such as `"Sat-Thu, 5 pm - 9 pm"`.
+    pub chamber_hours: String,
+    pub phone: Option<String>,
+}
+
+impl Letterhead {
+    pub fn default_path() -> DbResult<PathBuf> {
+        Ok(config::current()?.letterhead_path.clone())
+    }
+
+    /// The saved letterhead, or an empty one when none has been configured yet.
+    pub fn load_default() -> DbResult<Self> {
+        let path = Self::default_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load(&path)
+    }
+
+    pub fn save_default(&self) -> DbResult<()> {
+        self.save(&Self::default_path()?)
+    }
+
+    pub fn load(path: &Path) -> DbResult<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> DbResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    pub fn from_json(text: &str) -> DbResult<Self> {
+        let value = json::parse(text).map_err(|err| DbError::InvalidInput(format!("letterhead: {}", err)))?;
+        if !value.is_object() {
+            return Err(DbError::InvalidInput("letterhead: expected an object".to_string()));
+        }
+        let text_field = |key: &str| value[key].as_str().unwrap_or_default().trim().to_string();
+        Ok(Self {
+            doctor_name: text_field("doctor_name"),
+            degrees: value["degrees"].members()
+                .filter_map(|degree| degree.as_str())
+                .map(|degree| degree.trim().to_string())
+                .filter(|degree| !degree.is_empty())
+                .collect(),
+            bmdc_number: text_field("bmdc_number"),
+            chamber_name: text_field("chamber_name"),
+            chamber_address: text_field("chamber_address"),
+            chamber_hours: text_field("chamber_hours"),
+            phone: value["phone"].as_str().map(str::trim).filter(|phone| !phone.is_empty()).map(str::to_string),
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        json::object! {
+            doctor_name: self.doctor_name.as_str(),
+            degrees: self.degrees.clone(),
+            bmdc_number: self.bmdc_number.as_str(),
+            chamber_name: self.chamber_name.as_str(),
+            chamber_address: self.chamber_address.as_str(),
+            chamber_hours: self.chamber_hours.as_str(),
+            phone: self.phone.clone(),
+        }.pretty(4)
+    }
+
+    /// `"BMDC Reg. No. A-12345"`, `None` when the number has not been set.
+    pub fn bmdc_line(&self) -> Option<String> {
+        (!self.bmdc_number.is_empty()).then(|| format!("BMDC Reg. No. {}", self.bmdc_number))
+    }
+}
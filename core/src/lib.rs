@@ -1,6 +1,18 @@
 pub mod utils;
+pub mod error;
+pub mod config;
 pub mod db;
+pub mod patient_db;
 pub mod models;
+pub mod strength;
+pub mod dosing;
+pub mod dose_limits;
+pub mod dose_calculator;
+pub mod pricing;
+pub mod letterhead;
+pub mod pdf;
+pub mod font_subset;
+pub mod safety;
+pub mod interactions;
 pub mod avro_phonetic;
 pub mod ffi;
-
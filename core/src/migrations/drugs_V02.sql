@@ -0,0 +1,14 @@
+CREATE VIRTUAL TABLE Drugs_Search USING fts5 (
+            brand_name,
+            generic_name,
+            manufacturer,
+            strength,
+            dosage_form,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+INSERT INTO Drugs_Search (rowid, brand_name, generic_name, manufacturer, strength, dosage_form)
+SELECT d.id, d.brand_name, g.name, m.name, s.value, do.value FROM Drugs d
+LEFT JOIN Generics g ON g.id == d.generic_id
+LEFT JOIN Manufacturers m ON m.id == d.manufacturer_id
+LEFT JOIN Strengths s ON s.id == d.strength_id
+LEFT JOIN Dosages do ON do.id == d.dosage_id;
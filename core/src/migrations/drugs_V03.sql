@@ -0,0 +1,100 @@
+CREATE TABLE Interactions (
+            id INTEGER PRIMARY KEY,
+            generic_a TEXT NOT NULL COLLATE NOCASE,
+            generic_b TEXT NOT NULL COLLATE NOCASE,
+            severity TEXT NOT NULL CHECK (severity IN ('minor', 'moderate', 'major', 'contraindicated')),
+            description TEXT NOT NULL,
+            UNIQUE (generic_a, generic_b)
+        );
+INSERT INTO Interactions VALUES(1,'Warfarin','Aspirin','major','Additive antiplatelet and anticoagulant effect, high risk of bleeding.');
+INSERT INTO Interactions VALUES(2,'Warfarin','Clopidogrel','major','Additive antiplatelet and anticoagulant effect, high risk of bleeding.');
+INSERT INTO Interactions VALUES(3,'Warfarin','Ibuprofen','major','NSAIDs increase the risk of gastrointestinal bleeding with warfarin.');
+INSERT INTO Interactions VALUES(4,'Warfarin','Naproxen','major','NSAIDs increase the risk of gastrointestinal bleeding with warfarin.');
+INSERT INTO Interactions VALUES(5,'Warfarin','Diclofenac','major','NSAIDs increase the risk of gastrointestinal bleeding with warfarin.');
+INSERT INTO Interactions VALUES(6,'Warfarin','Ketorolac','major','NSAIDs increase the risk of gastrointestinal bleeding with warfarin.');
+INSERT INTO Interactions VALUES(7,'Warfarin','Metronidazole','major','Inhibits warfarin metabolism, INR rises; monitor INR and reduce the warfarin dose.');
+INSERT INTO Interactions VALUES(8,'Warfarin','Fluconazole','major','Inhibits warfarin metabolism, INR rises; monitor INR and reduce the warfarin dose.');
+INSERT INTO Interactions VALUES(9,'Warfarin','Amiodarone','major','Inhibits warfarin metabolism, INR rises; monitor INR and reduce the warfarin dose.');
+INSERT INTO Interactions VALUES(10,'Warfarin','Ciprofloxacin','moderate','May raise INR; monitor INR during and after the course.');
+INSERT INTO Interactions VALUES(11,'Warfarin','Levofloxacin','moderate','May raise INR; monitor INR during and after the course.');
+INSERT INTO Interactions VALUES(12,'Warfarin','Clarithromycin','moderate','May raise INR; monitor INR during and after the course.');
+INSERT INTO Interactions VALUES(13,'Warfarin','Erythromycin','moderate','May raise INR; monitor INR during and after the course.');
+INSERT INTO Interactions VALUES(14,'Warfarin','Rifampicin','major','Induces warfarin metabolism, anticoagulant effect is reduced.');
+INSERT INTO Interactions VALUES(15,'Warfarin','Carbamazepine','major','Induces warfarin metabolism, anticoagulant effect is reduced.');
+INSERT INTO Interactions VALUES(16,'Clopidogrel','Omeprazole','moderate','Reduces activation of clopidogrel; prefer pantoprazole.');
+INSERT INTO Interactions VALUES(17,'Clopidogrel','Esomeprazole','moderate','Reduces activation of clopidogrel; prefer pantoprazole.');
+INSERT INTO Interactions VALUES(18,'Aspirin','Ibuprofen','moderate','Increased risk of gastrointestinal bleeding; ibuprofen may blunt the antiplatelet effect of aspirin.');
+INSERT INTO Interactions VALUES(19,'Aspirin','Naproxen','moderate','Increased risk of gastrointestinal bleeding; naproxen may blunt the antiplatelet effect of aspirin.');
+INSERT INTO Interactions VALUES(20,'Aspirin','Diclofenac','moderate','Increased risk of gastrointestinal bleeding and ulceration from combined NSAID use.');
+INSERT INTO Interactions VALUES(21,'Aspirin','Ketorolac','moderate','Increased risk of serious gastrointestinal bleeding; ketorolac is not advised with aspirin or other NSAIDs.');
+INSERT INTO Interactions VALUES(22,'Simvastatin','Clarithromycin','contraindicated','Greatly raises simvastatin levels, risk of myopathy and rhabdomyolysis.');
+INSERT INTO Interactions VALUES(23,'Simvastatin','Erythromycin','contraindicated','Greatly raises simvastatin levels, risk of myopathy and rhabdomyolysis.');
+INSERT INTO Interactions VALUES(24,'Simvastatin','Ketoconazole','contraindicated','Greatly raises simvastatin levels, risk of myopathy and rhabdomyolysis.');
+INSERT INTO Interactions VALUES(25,'Atorvastatin','Clarithromycin','major','Raises atorvastatin levels, risk of myopathy; limit the atorvastatin dose.');
+INSERT INTO Interactions VALUES(26,'Atorvastatin','Ketoconazole','major','Raises atorvastatin levels, risk of myopathy; limit the atorvastatin dose.');
+INSERT INTO Interactions VALUES(27,'Simvastatin','Amlodipine','moderate','Raises simvastatin levels; do not exceed 20 mg simvastatin daily.');
+INSERT INTO Interactions VALUES(28,'Simvastatin','Amiodarone','moderate','Raises simvastatin levels; do not exceed 20 mg simvastatin daily.');
+INSERT INTO Interactions VALUES(29,'Sildenafil','Nitroglycerin','contraindicated','Severe hypotension.');
+INSERT INTO Interactions VALUES(30,'Sildenafil','Glyceryl Trinitrate','contraindicated','Severe hypotension.');
+INSERT INTO Interactions VALUES(31,'Sildenafil','Isosorbide','contraindicated','Severe hypotension.');
+INSERT INTO Interactions VALUES(32,'Tramadol','Sertraline','major','Risk of serotonin syndrome and seizures.');
+INSERT INTO Interactions VALUES(33,'Tramadol','Fluoxetine','major','Risk of serotonin syndrome and seizures.');
+INSERT INTO Interactions VALUES(34,'Tramadol','Escitalopram','major','Risk of serotonin syndrome and seizures.');
+INSERT INTO Interactions VALUES(35,'Tramadol','Clonazepam','major','Additive CNS and respiratory depression.');
+INSERT INTO Interactions VALUES(36,'Linezolid','Sertraline','major','Risk of serotonin syndrome.');
+INSERT INTO Interactions VALUES(37,'Linezolid','Fluoxetine','major','Risk of serotonin syndrome.');
+INSERT INTO Interactions VALUES(38,'Linezolid','Escitalopram','major','Risk of serotonin syndrome.');
+INSERT INTO Interactions VALUES(39,'Domperidone','Ketoconazole','contraindicated','Raises domperidone levels, risk of QT prolongation and arrhythmia.');
+INSERT INTO Interactions VALUES(40,'Domperidone','Clarithromycin','contraindicated','Raises domperidone levels, risk of QT prolongation and arrhythmia.');
+INSERT INTO Interactions VALUES(41,'Domperidone','Erythromycin','contraindicated','Raises domperidone levels, risk of QT prolongation and arrhythmia.');
+INSERT INTO Interactions VALUES(42,'Domperidone','Azithromycin','moderate','Additive QT prolongation.');
+INSERT INTO Interactions VALUES(43,'Domperidone','Ondansetron','moderate','Additive QT prolongation.');
+INSERT INTO Interactions VALUES(44,'Domperidone','Amiodarone','moderate','Additive QT prolongation.');
+INSERT INTO Interactions VALUES(45,'Digoxin','Amiodarone','major','Raises digoxin levels, risk of toxicity; halve the digoxin dose and monitor.');
+INSERT INTO Interactions VALUES(46,'Digoxin','Clarithromycin','major','Raises digoxin levels, risk of toxicity; halve the digoxin dose and monitor.');
+INSERT INTO Interactions VALUES(47,'Digoxin','Spironolactone','minor','May raise digoxin levels and interfere with digoxin assays.');
+INSERT INTO Interactions VALUES(48,'Allopurinol','Azathioprine','major','Blocks azathioprine breakdown, severe bone marrow suppression; reduce azathioprine to a quarter of the dose.');
+INSERT INTO Interactions VALUES(49,'Methotrexate','Trimethoprim','major','Additive antifolate effect, risk of bone marrow suppression.');
+INSERT INTO Interactions VALUES(50,'Methotrexate','Ibuprofen','moderate','Reduces methotrexate clearance, risk of toxicity.');
+INSERT INTO Interactions VALUES(51,'Methotrexate','Naproxen','moderate','Reduces methotrexate clearance, risk of toxicity.');
+INSERT INTO Interactions VALUES(52,'Methotrexate','Diclofenac','moderate','Reduces methotrexate clearance, risk of toxicity.');
+INSERT INTO Interactions VALUES(53,'Methotrexate','Ketorolac','moderate','Reduces methotrexate clearance, risk of toxicity.');
+INSERT INTO Interactions VALUES(54,'Methotrexate','Aspirin','moderate','Reduces methotrexate clearance, risk of toxicity.');
+INSERT INTO Interactions VALUES(55,'Spironolactone','Potassium Chloride','major','Risk of severe hyperkalaemia.');
+INSERT INTO Interactions VALUES(56,'Spironolactone','Enalapril','moderate','Risk of hyperkalaemia; monitor serum potassium.');
+INSERT INTO Interactions VALUES(57,'Spironolactone','Lisinopril','moderate','Risk of hyperkalaemia; monitor serum potassium.');
+INSERT INTO Interactions VALUES(58,'Spironolactone','Ramipril','moderate','Risk of hyperkalaemia; monitor serum potassium.');
+INSERT INTO Interactions VALUES(59,'Spironolactone','Losartan','moderate','Risk of hyperkalaemia; monitor serum potassium.');
+INSERT INTO Interactions VALUES(60,'Enalapril','Potassium Chloride','moderate','Risk of hyperkalaemia; monitor serum potassium.');
+INSERT INTO Interactions VALUES(61,'Lisinopril','Potassium Chloride','moderate','Risk of hyperkalaemia; monitor serum potassium.');
+INSERT INTO Interactions VALUES(62,'Ramipril','Potassium Chloride','moderate','Risk of hyperkalaemia; monitor serum potassium.');
+INSERT INTO Interactions VALUES(63,'Losartan','Potassium Chloride','moderate','Risk of hyperkalaemia; monitor serum potassium.');
+INSERT INTO Interactions VALUES(64,'Ciprofloxacin','Theophylline','major','Raises theophylline levels, risk of seizures.');
+INSERT INTO Interactions VALUES(65,'Fluvoxamine','Tizanidine','contraindicated','Greatly raises tizanidine or theophylline levels.');
+INSERT INTO Interactions VALUES(66,'Fluvoxamine','Theophylline','contraindicated','Greatly raises tizanidine or theophylline levels.');
+INSERT INTO Interactions VALUES(67,'Carbamazepine','Clarithromycin','major','Raises carbamazepine levels, risk of toxicity.');
+INSERT INTO Interactions VALUES(68,'Carbamazepine','Erythromycin','major','Raises carbamazepine levels, risk of toxicity.');
+INSERT INTO Interactions VALUES(69,'Sodium Valproate','Lamotrigine','major','Raises lamotrigine levels, risk of serious skin rash; halve the lamotrigine dose.');
+INSERT INTO Interactions VALUES(70,'Ciprofloxacin','Calcium','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(71,'Ciprofloxacin','Magnesium','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(72,'Ciprofloxacin','Zinc','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(73,'Ciprofloxacin','Ferrous','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(74,'Ciprofloxacin','Iron','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(75,'Levofloxacin','Calcium','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(76,'Levofloxacin','Magnesium','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(77,'Levofloxacin','Zinc','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(78,'Levofloxacin','Ferrous','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(79,'Levofloxacin','Iron','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(80,'Doxycycline','Calcium','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(81,'Doxycycline','Magnesium','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(82,'Doxycycline','Zinc','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(83,'Doxycycline','Ferrous','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(84,'Doxycycline','Iron','moderate','Reduces absorption of the antibiotic; give it 2 hours before or 6 hours after.');
+INSERT INTO Interactions VALUES(85,'Levothyroxine','Calcium','moderate','Reduces levothyroxine absorption; separate the doses by 4 hours.');
+INSERT INTO Interactions VALUES(86,'Levothyroxine','Ferrous','moderate','Reduces levothyroxine absorption; separate the doses by 4 hours.');
+INSERT INTO Interactions VALUES(87,'Levothyroxine','Iron','moderate','Reduces levothyroxine absorption; separate the doses by 4 hours.');
+INSERT INTO Interactions VALUES(88,'Metformin','Cimetidine','minor','May raise metformin levels.');
+INSERT INTO Interactions VALUES(89,'Prednisolone','Ibuprofen','moderate','Increased risk of gastrointestinal ulceration and bleeding.');
+INSERT INTO Interactions VALUES(90,'Prednisolone','Naproxen','moderate','Increased risk of gastrointestinal ulceration and bleeding.');
+INSERT INTO Interactions VALUES(91,'Prednisolone','Diclofenac','moderate','Increased risk of gastrointestinal ulceration and bleeding.');
+INSERT INTO Interactions VALUES(92,'Prednisolone','Ketorolac','moderate','Increased risk of gastrointestinal ulceration and bleeding.');
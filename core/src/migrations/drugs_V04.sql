@@ -0,0 +1,147 @@
+CREATE TABLE Drug_Classes (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE
+        );
+CREATE TABLE Drug_Class_Aliases (
+            class_id INTEGER NOT NULL REFERENCES Drug_Classes(id),
+            alias TEXT NOT NULL COLLATE NOCASE,
+            PRIMARY KEY (class_id, alias)
+        );
+CREATE TABLE Drug_Class_Members (
+            class_id INTEGER NOT NULL REFERENCES Drug_Classes(id),
+            generic TEXT NOT NULL COLLATE NOCASE,
+            PRIMARY KEY (class_id, generic)
+        );
+CREATE TABLE Drug_Class_Cross_Reactions (
+            class_id INTEGER NOT NULL REFERENCES Drug_Classes(id),
+            related_class_id INTEGER NOT NULL REFERENCES Drug_Classes(id),
+            PRIMARY KEY (class_id, related_class_id)
+        );
+INSERT INTO Drug_Classes VALUES(1,'Penicillins');
+INSERT INTO Drug_Classes VALUES(2,'Cephalosporins');
+INSERT INTO Drug_Classes VALUES(3,'Carbapenems');
+INSERT INTO Drug_Classes VALUES(4,'Sulfonamides');
+INSERT INTO Drug_Classes VALUES(5,'Macrolides');
+INSERT INTO Drug_Classes VALUES(6,'Fluoroquinolones');
+INSERT INTO Drug_Classes VALUES(7,'Tetracyclines');
+INSERT INTO Drug_Classes VALUES(8,'Aminoglycosides');
+INSERT INTO Drug_Classes VALUES(9,'NSAIDs');
+INSERT INTO Drug_Classes VALUES(10,'Statins');
+INSERT INTO Drug_Classes VALUES(11,'ACE Inhibitors');
+INSERT INTO Drug_Class_Aliases VALUES(1,'Penicillin');
+INSERT INTO Drug_Class_Aliases VALUES(2,'Cephalosporin');
+INSERT INTO Drug_Class_Aliases VALUES(3,'Carbapenem');
+INSERT INTO Drug_Class_Aliases VALUES(3,'Penem');
+INSERT INTO Drug_Class_Aliases VALUES(4,'Sulfa');
+INSERT INTO Drug_Class_Aliases VALUES(4,'Sulpha');
+INSERT INTO Drug_Class_Aliases VALUES(4,'Sulfa drugs');
+INSERT INTO Drug_Class_Aliases VALUES(4,'Sulphonamides');
+INSERT INTO Drug_Class_Aliases VALUES(5,'Macrolide');
+INSERT INTO Drug_Class_Aliases VALUES(6,'Quinolones');
+INSERT INTO Drug_Class_Aliases VALUES(7,'Tetracycline');
+INSERT INTO Drug_Class_Aliases VALUES(8,'Aminoglycoside');
+INSERT INTO Drug_Class_Aliases VALUES(9,'NSAID');
+INSERT INTO Drug_Class_Aliases VALUES(9,'Non-steroidal anti-inflammatory drugs');
+INSERT INTO Drug_Class_Aliases VALUES(10,'Statin');
+INSERT INTO Drug_Class_Aliases VALUES(11,'ACE Inhibitor');
+INSERT INTO Drug_Class_Members VALUES(1,'Amoxicillin');
+INSERT INTO Drug_Class_Members VALUES(1,'Ampicillin');
+INSERT INTO Drug_Class_Members VALUES(1,'Benzathine Penicillin');
+INSERT INTO Drug_Class_Members VALUES(1,'Benzyl Penicillin');
+INSERT INTO Drug_Class_Members VALUES(1,'Cloxacillin');
+INSERT INTO Drug_Class_Members VALUES(1,'Dicloxacillin');
+INSERT INTO Drug_Class_Members VALUES(1,'Flucloxacillin');
+INSERT INTO Drug_Class_Members VALUES(1,'Phenoxymethyl Penicillin');
+INSERT INTO Drug_Class_Members VALUES(1,'Piperacillin');
+INSERT INTO Drug_Class_Members VALUES(1,'Pivmecillinam');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefaclor');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefadroxil');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefazolin');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefdinir');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefditoren');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefepime');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefetamet');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefixime');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefoperazone');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefotaxime');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefotetan');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefoxitin');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefpirome');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefpodoxime');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefprozil');
+INSERT INTO Drug_Class_Members VALUES(2,'Ceftaroline');
+INSERT INTO Drug_Class_Members VALUES(2,'Ceftazidime');
+INSERT INTO Drug_Class_Members VALUES(2,'Ceftibuten');
+INSERT INTO Drug_Class_Members VALUES(2,'Ceftriaxone');
+INSERT INTO Drug_Class_Members VALUES(2,'Cefuroxime');
+INSERT INTO Drug_Class_Members VALUES(2,'Cephalexin');
+INSERT INTO Drug_Class_Members VALUES(2,'Cephradine');
+INSERT INTO Drug_Class_Members VALUES(3,'Doripenem');
+INSERT INTO Drug_Class_Members VALUES(3,'Ertapenem');
+INSERT INTO Drug_Class_Members VALUES(3,'Faropenem');
+INSERT INTO Drug_Class_Members VALUES(3,'Imipenem');
+INSERT INTO Drug_Class_Members VALUES(3,'Meropenem');
+INSERT INTO Drug_Class_Members VALUES(4,'Sulfadoxine');
+INSERT INTO Drug_Class_Members VALUES(4,'Sulfasalazine');
+INSERT INTO Drug_Class_Members VALUES(4,'Sulphacetamide');
+INSERT INTO Drug_Class_Members VALUES(4,'Sulphadimidine');
+INSERT INTO Drug_Class_Members VALUES(4,'Sulphamethoxazole');
+INSERT INTO Drug_Class_Members VALUES(4,'Silver Sulphadiazine');
+INSERT INTO Drug_Class_Members VALUES(5,'Azithromycin');
+INSERT INTO Drug_Class_Members VALUES(5,'Clarithromycin');
+INSERT INTO Drug_Class_Members VALUES(5,'Erythromycin');
+INSERT INTO Drug_Class_Members VALUES(5,'Roxithromycin');
+INSERT INTO Drug_Class_Members VALUES(5,'Spiramycine');
+INSERT INTO Drug_Class_Members VALUES(6,'Besifloxacin');
+INSERT INTO Drug_Class_Members VALUES(6,'Ciprofloxacin');
+INSERT INTO Drug_Class_Members VALUES(6,'Delafloxacin');
+INSERT INTO Drug_Class_Members VALUES(6,'Gatifloxacin');
+INSERT INTO Drug_Class_Members VALUES(6,'Gemefloxacin');
+INSERT INTO Drug_Class_Members VALUES(6,'Levofloxacin');
+INSERT INTO Drug_Class_Members VALUES(6,'Lomefloxacin');
+INSERT INTO Drug_Class_Members VALUES(6,'Moxifloxacin');
+INSERT INTO Drug_Class_Members VALUES(6,'Ofloxacin');
+INSERT INTO Drug_Class_Members VALUES(6,'Pefloxacin');
+INSERT INTO Drug_Class_Members VALUES(6,'Sparfloxacin');
+INSERT INTO Drug_Class_Members VALUES(7,'Demeclocycline');
+INSERT INTO Drug_Class_Members VALUES(7,'Doxycycline');
+INSERT INTO Drug_Class_Members VALUES(7,'Lymecycline');
+INSERT INTO Drug_Class_Members VALUES(7,'Minocycline');
+INSERT INTO Drug_Class_Members VALUES(7,'Oxytetracycline');
+INSERT INTO Drug_Class_Members VALUES(7,'Tetracycline');
+INSERT INTO Drug_Class_Members VALUES(7,'Tigecycline');
+INSERT INTO Drug_Class_Members VALUES(8,'Neomycin');
+INSERT INTO Drug_Class_Members VALUES(8,'Streptomycin');
+INSERT INTO Drug_Class_Members VALUES(8,'Tobramycin');
+INSERT INTO Drug_Class_Members VALUES(8,'Spectinomycin');
+INSERT INTO Drug_Class_Members VALUES(9,'Aceclofenac');
+INSERT INTO Drug_Class_Members VALUES(9,'Aspirin');
+INSERT INTO Drug_Class_Members VALUES(9,'Celecoxib');
+INSERT INTO Drug_Class_Members VALUES(9,'Dex-Ketoprofen');
+INSERT INTO Drug_Class_Members VALUES(9,'Dexibuprofen');
+INSERT INTO Drug_Class_Members VALUES(9,'Dexketoprofen');
+INSERT INTO Drug_Class_Members VALUES(9,'Diclofenac');
+INSERT INTO Drug_Class_Members VALUES(9,'Etoricoxib');
+INSERT INTO Drug_Class_Members VALUES(9,'Flurbiprofen');
+INSERT INTO Drug_Class_Members VALUES(9,'Ibuprofen');
+INSERT INTO Drug_Class_Members VALUES(9,'Ketoprofen');
+INSERT INTO Drug_Class_Members VALUES(9,'Ketorolac');
+INSERT INTO Drug_Class_Members VALUES(9,'Mefenamic Acid');
+INSERT INTO Drug_Class_Members VALUES(9,'Naproxen');
+INSERT INTO Drug_Class_Members VALUES(10,'Atorvastatin');
+INSERT INTO Drug_Class_Members VALUES(10,'Fluvastatin');
+INSERT INTO Drug_Class_Members VALUES(10,'Lovastatin');
+INSERT INTO Drug_Class_Members VALUES(10,'Pitavastatin');
+INSERT INTO Drug_Class_Members VALUES(10,'Pravastatin');
+INSERT INTO Drug_Class_Members VALUES(10,'Rosuvastatin');
+INSERT INTO Drug_Class_Members VALUES(10,'Simvastatin');
+INSERT INTO Drug_Class_Members VALUES(11,'Captopril');
+INSERT INTO Drug_Class_Members VALUES(11,'Enalapril');
+INSERT INTO Drug_Class_Members VALUES(11,'Lisinopril');
+INSERT INTO Drug_Class_Members VALUES(11,'Ramipril');
+INSERT INTO Drug_Class_Cross_Reactions VALUES(1,2);
+INSERT INTO Drug_Class_Cross_Reactions VALUES(2,1);
+INSERT INTO Drug_Class_Cross_Reactions VALUES(1,3);
+INSERT INTO Drug_Class_Cross_Reactions VALUES(3,1);
+INSERT INTO Drug_Class_Cross_Reactions VALUES(2,3);
+INSERT INTO Drug_Class_Cross_Reactions VALUES(3,2);
@@ -0,0 +1,42 @@
+CREATE TABLE Dose_Limits (
+            id INTEGER PRIMARY KEY,
+            generic TEXT NOT NULL COLLATE NOCASE,
+            strength TEXT COLLATE NOCASE,
+            adult_max_daily_mg REAL,
+            pediatric_max_mg_per_kg_day REAL,
+            pediatric_max_daily_mg REAL,
+            UNIQUE (generic, strength)
+        );
+INSERT INTO Dose_Limits VALUES(1,'Paracetamol',NULL,4000,75,4000);
+INSERT INTO Dose_Limits VALUES(2,'Ibuprofen',NULL,2400,30,2400);
+INSERT INTO Dose_Limits VALUES(3,'Diclofenac',NULL,150,3,150);
+INSERT INTO Dose_Limits VALUES(4,'Naproxen',NULL,1500,15,1000);
+INSERT INTO Dose_Limits VALUES(5,'Mefenamic Acid',NULL,1500,25,1500);
+INSERT INTO Dose_Limits VALUES(6,'Ketorolac',NULL,40,NULL,NULL);
+INSERT INTO Dose_Limits VALUES(7,'Aspirin',NULL,4000,NULL,NULL);
+INSERT INTO Dose_Limits VALUES(8,'Aspirin','75 mg',325,NULL,NULL);
+INSERT INTO Dose_Limits VALUES(9,'Tramadol',NULL,400,NULL,NULL);
+INSERT INTO Dose_Limits VALUES(10,'Amoxicillin',NULL,3000,90,3000);
+INSERT INTO Dose_Limits VALUES(11,'Flucloxacillin',NULL,4000,100,4000);
+INSERT INTO Dose_Limits VALUES(12,'Cephalexin',NULL,4000,100,4000);
+INSERT INTO Dose_Limits VALUES(13,'Cephradine',NULL,4000,100,4000);
+INSERT INTO Dose_Limits VALUES(14,'Cefixime',NULL,400,8,400);
+INSERT INTO Dose_Limits VALUES(15,'Cefuroxime',NULL,1000,30,1000);
+INSERT INTO Dose_Limits VALUES(16,'Azithromycin',NULL,500,10,500);
+INSERT INTO Dose_Limits VALUES(17,'Clarithromycin',NULL,1000,15,1000);
+INSERT INTO Dose_Limits VALUES(18,'Erythromycin',NULL,4000,50,4000);
+INSERT INTO Dose_Limits VALUES(19,'Ciprofloxacin',NULL,1500,30,1500);
+INSERT INTO Dose_Limits VALUES(20,'Doxycycline',NULL,200,NULL,NULL);
+INSERT INTO Dose_Limits VALUES(21,'Metronidazole',NULL,2400,50,2400);
+INSERT INTO Dose_Limits VALUES(22,'Domperidone',NULL,30,0.75,30);
+INSERT INTO Dose_Limits VALUES(23,'Cetirizine',NULL,20,NULL,10);
+INSERT INTO Dose_Limits VALUES(24,'Loratadine',NULL,10,NULL,10);
+INSERT INTO Dose_Limits VALUES(25,'Desloratadine',NULL,5,NULL,5);
+INSERT INTO Dose_Limits VALUES(26,'Fexofenadine',NULL,180,NULL,120);
+INSERT INTO Dose_Limits VALUES(27,'Montelukast',NULL,10,NULL,5);
+INSERT INTO Dose_Limits VALUES(28,'Metformin',NULL,3000,NULL,2000);
+INSERT INTO Dose_Limits VALUES(29,'Amlodipine',NULL,10,NULL,NULL);
+INSERT INTO Dose_Limits VALUES(30,'Losartan',NULL,100,NULL,NULL);
+INSERT INTO Dose_Limits VALUES(31,'Atorvastatin',NULL,80,NULL,NULL);
+INSERT INTO Dose_Limits VALUES(32,'Rosuvastatin',NULL,40,NULL,NULL);
+INSERT INTO Dose_Limits VALUES(33,'Simvastatin',NULL,80,NULL,NULL);
@@ -0,0 +1,21 @@
+CREATE TABLE Patients (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            date_of_birth TEXT,
+            age_years INTEGER,
+            sex TEXT CHECK (sex IN ('male', 'female', 'other')),
+            phone TEXT,
+            address TEXT,
+            weight_kg REAL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            deleted_at TEXT
+        );
+CREATE INDEX Patients_name_idx ON Patients (name);
+CREATE INDEX Patients_phone_idx ON Patients (phone);
+CREATE TABLE Patient_Allergies (
+            patient_id INTEGER NOT NULL,
+            allergen TEXT NOT NULL,
+            PRIMARY KEY (patient_id, allergen),
+            FOREIGN KEY (patient_id) REFERENCES Patients(id)
+        );
@@ -0,0 +1,28 @@
+CREATE TABLE Prescriptions (
+            id INTEGER PRIMARY KEY,
+            patient_id INTEGER NOT NULL,
+            date TEXT NOT NULL,
+            chief_complaints TEXT NOT NULL DEFAULT '',
+            diagnosis TEXT NOT NULL DEFAULT '',
+            advice TEXT NOT NULL DEFAULT '',
+            follow_up_date TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (patient_id) REFERENCES Patients(id)
+        );
+CREATE INDEX Prescriptions_patient_id_idx ON Prescriptions (patient_id, date);
+CREATE TABLE Prescription_Items (
+            id INTEGER PRIMARY KEY,
+            prescription_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            brand_name TEXT NOT NULL,
+            generic_name TEXT NOT NULL DEFAULT '',
+            strength TEXT NOT NULL DEFAULT '',
+            formulation TEXT NOT NULL DEFAULT '',
+            manufacturer TEXT NOT NULL DEFAULT '',
+            dosing TEXT NOT NULL DEFAULT '',
+            instructions TEXT NOT NULL DEFAULT '',
+            duration TEXT NOT NULL DEFAULT '',
+            UNIQUE (prescription_id, position),
+            FOREIGN KEY (prescription_id) REFERENCES Prescriptions(id) ON DELETE CASCADE
+        );
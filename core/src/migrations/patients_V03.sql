@@ -0,0 +1,6 @@
+CREATE TABLE Patient_Conditions (
+            patient_id INTEGER NOT NULL,
+            condition TEXT NOT NULL,
+            PRIMARY KEY (patient_id, condition),
+            FOREIGN KEY (patient_id) REFERENCES Patients(id)
+        );
@@ -0,0 +1,22 @@
+CREATE TABLE Templates (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            advice TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+CREATE TABLE Template_Items (
+            id INTEGER PRIMARY KEY,
+            template_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            brand_name TEXT NOT NULL,
+            generic_name TEXT NOT NULL DEFAULT '',
+            strength TEXT NOT NULL DEFAULT '',
+            formulation TEXT NOT NULL DEFAULT '',
+            manufacturer TEXT NOT NULL DEFAULT '',
+            dosing TEXT NOT NULL DEFAULT '',
+            instructions TEXT NOT NULL DEFAULT '',
+            duration TEXT NOT NULL DEFAULT '',
+            UNIQUE (template_id, position),
+            FOREIGN KEY (template_id) REFERENCES Templates(id) ON DELETE CASCADE
+        );
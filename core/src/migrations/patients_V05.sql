@@ -0,0 +1,28 @@
+CREATE TABLE Drafts (
+            id INTEGER PRIMARY KEY,
+            patient_id INTEGER,
+            prescription_id INTEGER,
+            date TEXT NOT NULL DEFAULT '',
+            chief_complaints TEXT NOT NULL DEFAULT '',
+            diagnosis TEXT NOT NULL DEFAULT '',
+            advice TEXT NOT NULL DEFAULT '',
+            follow_up_date TEXT,
+            saved_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime')),
+            FOREIGN KEY (patient_id) REFERENCES Patients(id) ON DELETE SET NULL,
+            FOREIGN KEY (prescription_id) REFERENCES Prescriptions(id) ON DELETE SET NULL
+        );
+CREATE TABLE Draft_Items (
+            id INTEGER PRIMARY KEY,
+            draft_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            brand_name TEXT NOT NULL,
+            generic_name TEXT NOT NULL DEFAULT '',
+            strength TEXT NOT NULL DEFAULT '',
+            formulation TEXT NOT NULL DEFAULT '',
+            manufacturer TEXT NOT NULL DEFAULT '',
+            dosing TEXT NOT NULL DEFAULT '',
+            instructions TEXT NOT NULL DEFAULT '',
+            duration TEXT NOT NULL DEFAULT '',
+            UNIQUE (draft_id, position),
+            FOREIGN KEY (draft_id) REFERENCES Drafts(id) ON DELETE CASCADE
+        );
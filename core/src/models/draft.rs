@@ -0,0 +1,22 @@
+use crate::models::prescription::Prescription;
+
+/// A prescription autosaved while it is being written. Drafts are kept until their tab is saved or
+/// closed, so that they can be restored on the next launch, also after a crash or a power cut.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Draft {
+    /// Row id in `patients.db`, `0` until the draft has been saved.
+    pub id: i64,
+    /// The prescription as written. Its `id` is the stored prescription the tab was opened from,
+    /// `0` for a new one.
+    pub prescription: Prescription,
+    /// Name of the patient, empty when the tab has none. Only filled in when drafts are read.
+    pub patient_name: String,
+    /// Local time the draft was last written, `YYYY-MM-DD HH:MM:SS`.
+    pub saved_at: String,
+}
+
+impl Draft {
+    pub fn new(id: i64, prescription: Prescription) -> Self {
+        Self { id, prescription, ..Default::default() }
+    }
+}
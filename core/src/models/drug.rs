@@ -0,0 +1,43 @@
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Drug {
+    pub id: i64,
+    pub brand_name: String,
+    pub generic_name: String,
+    pub strength: String,
+    pub dosage_form: String,
+    pub manufacturer: String,
+    /// Price as listed in `drugs.db`, e.g. `"6.00 Tk"` or `"14.09 Tk, 14.94 Tk"` for multiple pack sizes.
+    pub price: String,
+    /// Drug Administration Registration number.
+    pub dar_number: String,
+}
+
+/// Another brand of the same generic, strength and dosage form.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrugAlternative {
+    pub drug: Drug,
+    /// Lowest listed price, `None` when the brand has no price on record.
+    pub unit_price: Option<f64>,
+}
+
+/// Pharmacological class used to match allergies such as "penicillin" against individual generics.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DrugClass {
+    pub name: String,
+    /// Other names the class is recorded under, e.g. `"Sulfa"` for sulfonamides.
+    pub aliases: Vec<String>,
+    /// Member generics without their salt, e.g. `"Amoxicillin"`.
+    pub members: Vec<String>,
+    /// Classes a patient allergic to this class may also react to.
+    pub cross_reactive_classes: Vec<String>,
+}
+
+impl DrugClass {
+    pub fn is_named(&self, term: &str) -> bool {
+        crate::safety::terms_match(&self.name, term) || self.aliases.iter().any(|alias| crate::safety::terms_match(alias, term))
+    }
+
+    pub fn contains(&self, component: &str) -> bool {
+        self.members.iter().any(|member| crate::interactions::generic_matches(component, member))
+    }
+}
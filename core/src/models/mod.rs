@@ -1 +1,6 @@
 pub mod prescription;
+pub mod prescription_editor;
+pub mod patient;
+pub mod drug;
+pub mod template;
+pub mod draft;
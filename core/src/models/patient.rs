@@ -0,0 +1,152 @@
+use crate::error::{DbError, DbResult};
+
+const MAX_AGE_YEARS: u32 = 150;
+
+/// Smallest and largest number of digits accepted in a phone number, `"01711-000000"` has 11.
+const PHONE_DIGITS: (usize, usize) = (6, 15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sex {
+    Male,
+    Female,
+    Other,
+}
+
+impl Sex {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sex::Male => "male",
+            Sex::Female => "female",
+            Sex::Other => "other",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "male" | "m" => Some(Sex::Male),
+            "female" | "f" => Some(Sex::Female),
+            "other" | "o" => Some(Sex::Other),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patient {
+    /// Row id in `patients.db`, `0` until the patient has been saved.
+    pub id: i64,
+    pub name: String,
+    /// ISO 8601 date (`YYYY-MM-DD`).
+    pub date_of_birth: Option<String>,
+    /// Used when only the age is known, e.g. when the date of birth is not on record.
+    pub age_years: Option<u32>,
+    pub sex: Option<Sex>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub weight_kg: Option<f64>,
+    /// Generics (`"Amoxicillin"`) or drug classes (`"Penicillin"`) the patient is allergic to.
+    pub allergies: Vec<String>,
+    /// Chronic conditions, checked against the contraindications of prescribed generics.
+    pub conditions: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Patient {
+    pub fn new(name: String) -> Self {
+        Self { name, ..Default::default() }
+    }
+
+    /// Checks the fields a patient form can get wrong, the error describes the first problem found.
+    pub fn validate(&self) -> DbResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(DbError::InvalidInput("patient name is required".to_string()));
+        }
+        if let Some(date_of_birth) = self.date_of_birth.as_deref().filter(|date| !date.trim().is_empty())
+            && !is_iso_date(date_of_birth.trim()) {
+            return Err(DbError::InvalidInput("date of birth must be a valid date written as YYYY-MM-DD".to_string()));
+        }
+        if self.age_years.is_some_and(|age| age > MAX_AGE_YEARS) {
+            return Err(DbError::InvalidInput(format!("age must be at most {} years", MAX_AGE_YEARS)));
+        }
+        if let Some(phone) = self.phone.as_deref().filter(|phone| !phone.trim().is_empty()) {
+            let digits = phone.chars().filter(char::is_ascii_digit).count();
+            let allowed = phone.chars().all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | ' '));
+            if !allowed || !(PHONE_DIGITS.0..=PHONE_DIGITS.1).contains(&digits) {
+                return Err(DbError::InvalidInput(format!(
+                    "phone number must have {} to {} digits, optionally with +, - or spaces", PHONE_DIGITS.0, PHONE_DIGITS.1
+                )));
+            }
+        }
+        if self.weight_kg.is_some_and(|weight| weight <= 0.0) {
+            return Err(DbError::InvalidInput("weight must be greater than zero".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Age in whole years on `date` (`YYYY-MM-DD`), counted from the date of birth when it is on record and
+    /// falling back to the age entered by hand, so a patient saved years ago is not checked at their old age.
+    pub fn age_years_on(&self, date: &str) -> Option<u32> {
+        let birth = self.date_of_birth.as_deref().and_then(|date_of_birth| parse_iso_date(date_of_birth.trim()));
+        let (Some((birth_year, birth_month, birth_day)), Some((year, month, day))) = (birth, parse_iso_date(date)) else {
+            return self.age_years;
+        };
+        let had_birthday = (month, day) >= (birth_month, birth_day);
+        year.checked_sub(birth_year)?.checked_sub(u32::from(!had_birthday))
+    }
+}
+
+/// `true` for an existing calendar date written as `YYYY-MM-DD`.
+fn is_iso_date(text: &str) -> bool {
+    parse_iso_date(text).is_some()
+}
+
+/// Year, month and day of an existing calendar date written as `YYYY-MM-DD`.
+fn parse_iso_date(text: &str) -> Option<(u32, u32, u32)> {
+    let parts: Vec<&str> = text.split('-').collect();
+    let [year, month, day] = parts.as_slice() else { return None; };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let (Ok(year), Ok(month), Ok(day)) = (year.parse::<u32>(), month.parse::<u32>(), day.parse::<u32>()) else { return None; };
+    let leap_year = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap_year => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days_in_month).contains(&day).then_some((year, month, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patient(date_of_birth: Option<&str>, age_years: Option<u32>) -> Patient {
+        Patient { date_of_birth: date_of_birth.map(str::to_string), age_years, ..Patient::new("Rahim".to_string()) }
+    }
+
+    #[test]
+    fn age_is_counted_from_the_date_of_birth() {
+        let patient = patient(Some("2018-03-15"), Some(2));
+        assert_eq!(patient.age_years_on("2026-03-14"), Some(7));
+        assert_eq!(patient.age_years_on("2026-03-15"), Some(8));
+        assert_eq!(patient.age_years_on("2026-12-31"), Some(8));
+    }
+
+    #[test]
+    fn age_falls_back_to_the_entered_age() {
+        assert_eq!(patient(None, Some(40)).age_years_on("2026-10-18"), Some(40));
+        assert_eq!(patient(Some("not a date"), Some(40)).age_years_on("2026-10-18"), Some(40));
+        assert_eq!(patient(Some("2018-03-15"), Some(8)).age_years_on("someday"), Some(8));
+        assert_eq!(patient(None, None).age_years_on("2026-10-18"), None);
+    }
+
+    #[test]
+    fn dates_of_birth_after_the_date_have_no_age() {
+        assert_eq!(patient(Some("2026-10-19"), None).age_years_on("2026-10-18"), None);
+        assert_eq!(patient(Some("2027-01-01"), None).age_years_on("2026-10-18"), None);
+    }
+}
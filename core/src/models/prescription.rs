@@ -1,5 +1,7 @@
 use std::{ffi::{c_char, CStr, CString}, sync::atomic::{AtomicU64, Ordering}};
 
+use crate::ffi::prescription::MedicineData;
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct CMedicineData {
@@ -39,3 +41,26 @@ pub fn medicine_new(
         duration: unsafe { CString::from(CStr::from_ptr(duration)).into_raw() } 
     }
 }
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Prescription {
+    /// Row id in `patients.db`, `0` until the prescription has been saved.
+    pub id: i64,
+    pub patient_id: i64,
+    /// ISO 8601 date (`YYYY-MM-DD`), today's date is used when left empty.
+    pub date: String,
+    pub chief_complaints: String,
+    pub diagnosis: String,
+    pub advice: String,
+    pub follow_up_date: Option<String>,
+    /// Medicine lines in the order they are printed.
+    pub items: Vec<MedicineData>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Prescription {
+    pub fn new(patient_id: i64) -> Self {
+        Self { patient_id, ..Default::default() }
+    }
+}
@@ -0,0 +1,348 @@
+use crate::ffi::prescription::MedicineData;
+use crate::models::prescription::Prescription;
+
+/// Edits kept for undo, older ones are dropped first.
+const MAX_HISTORY: usize = 100;
+
+/// Free text fields of a prescription that are edited as a whole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextField {
+    ChiefComplaints,
+    Diagnosis,
+    Advice,
+}
+
+/// One reversible change to a prescription. Every edit carries what it replaced, so undoing it is
+/// applying its [`inverse`](Self::inverse).
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrescriptionEdit {
+    InsertItem { index: usize, item: MedicineData },
+    RemoveItem { index: usize, item: MedicineData },
+    MoveItem { from: usize, to: usize },
+    ReplaceItem { index: usize, old: MedicineData, new: MedicineData },
+    SetText { field: TextField, old: String, new: String },
+}
+
+impl PrescriptionEdit {
+    pub fn inverse(&self) -> Self {
+        match self.clone() {
+            Self::InsertItem { index, item } => Self::RemoveItem { index, item },
+            Self::RemoveItem { index, item } => Self::InsertItem { index, item },
+            Self::MoveItem { from, to } => Self::MoveItem { from: to, to: from },
+            Self::ReplaceItem { index, old, new } => Self::ReplaceItem { index, old: new, new: old },
+            Self::SetText { field, old, new } => Self::SetText { field, old: new, new: old },
+        }
+    }
+
+    fn apply(&self, prescription: &mut Prescription) {
+        match self {
+            Self::InsertItem { index, item } => prescription.items.insert(*index, item.clone()),
+            Self::RemoveItem { index, .. } => { prescription.items.remove(*index); }
+            Self::MoveItem { from, to } => {
+                let item = prescription.items.remove(*from);
+                prescription.items.insert(*to, item);
+            }
+            Self::ReplaceItem { index, new, .. } => prescription.items[*index] = new.clone(),
+            Self::SetText { field, new, .. } => *prescription.text_mut(*field) = new.clone(),
+        }
+    }
+}
+
+impl Prescription {
+    pub fn text(&self, field: TextField) -> &str {
+        match field {
+            TextField::ChiefComplaints => &self.chief_complaints,
+            TextField::Diagnosis => &self.diagnosis,
+            TextField::Advice => &self.advice,
+        }
+    }
+
+    fn text_mut(&mut self, field: TextField) -> &mut String {
+        match field {
+            TextField::ChiefComplaints => &mut self.chief_complaints,
+            TextField::Diagnosis => &mut self.diagnosis,
+            TextField::Advice => &mut self.advice,
+        }
+    }
+}
+
+/// A prescription being written, with undo and redo of every change made through it.
+/// Front ends keep their widgets in step with [`prescription`](Self::prescription) after each call.
+#[derive(Debug, Clone, Default)]
+pub struct PrescriptionEditor {
+    prescription: Prescription,
+    undo_stack: Vec<PrescriptionEdit>,
+    redo_stack: Vec<PrescriptionEdit>,
+    /// Set while text is being typed into a field, further typing joins the last edit.
+    typing: bool,
+}
+
+impl PrescriptionEditor {
+    pub fn new(prescription: Prescription) -> Self {
+        Self { prescription, ..Default::default() }
+    }
+
+    pub fn prescription(&self) -> &Prescription {
+        &self.prescription
+    }
+
+    /// Index of the medicine line with `id`.
+    pub fn position(&self, id: u64) -> Option<usize> {
+        self.prescription.items.iter().position(|item| item.id == id)
+    }
+
+    /// Takes the row id, date and timestamps from `record`, e.g. after saving. This is not an edit
+    /// and cannot be undone.
+    pub fn set_record(&mut self, record: &Prescription) {
+        self.prescription.id = record.id;
+        self.prescription.patient_id = record.patient_id;
+        self.prescription.date = record.date.clone();
+        self.prescription.created_at = record.created_at.clone();
+        self.prescription.updated_at = record.updated_at.clone();
+    }
+
+    pub fn add_item(&mut self, item: MedicineData) {
+        let index = self.prescription.items.len();
+        self.execute(PrescriptionEdit::InsertItem { index, item });
+    }
+
+    /// Returns `false` when there is no line with `id`.
+    pub fn remove_item(&mut self, id: u64) -> bool {
+        let Some(index) = self.position(id) else { return false; };
+        let item = self.prescription.items[index].clone();
+        self.execute(PrescriptionEdit::RemoveItem { index, item });
+        true
+    }
+
+    /// Moves the line with `id` to `to`, clamped to the last position. Returns `false` when the line
+    /// does not exist or is already there.
+    pub fn move_item(&mut self, id: u64, to: usize) -> bool {
+        let Some(from) = self.position(id) else { return false; };
+        let to = to.min(self.prescription.items.len() - 1);
+        if from == to { return false; }
+        self.execute(PrescriptionEdit::MoveItem { from, to });
+        true
+    }
+
+    /// Puts `item` in place of the line with `id`. Returns `false` when there is no such line or
+    /// nothing would change.
+    pub fn replace_item(&mut self, id: u64, item: MedicineData) -> bool {
+        let Some(index) = self.position(id) else { return false; };
+        let old = self.prescription.items[index].clone();
+        if old == item { return false; }
+        self.execute(PrescriptionEdit::ReplaceItem { index, old, new: item });
+        true
+    }
+
+    /// Sets a text field. Consecutive changes to the same field are undone together, a word at a time.
+    pub fn set_text(&mut self, field: TextField, text: &str) -> bool {
+        if self.prescription.text(field) == text { return false; }
+        let typing = self.typing;
+        if typing
+            && let Some(PrescriptionEdit::SetText { field: last_field, new, .. }) = self.undo_stack.last_mut()
+            && *last_field == field
+            && !new.ends_with(char::is_whitespace)
+        {
+            *new = text.to_string();
+            *self.prescription.text_mut(field) = text.to_string();
+            self.redo_stack.clear();
+            return true;
+        }
+        let old = self.prescription.text(field).to_string();
+        self.execute(PrescriptionEdit::SetText { field, old, new: text.to_string() });
+        self.typing = true;
+        true
+    }
+
+    /// Sets a text field as one edit of its own, for changes made by the program rather than typed,
+    /// e.g. advice from a template. Typing before or after it is undone separately.
+    pub fn replace_text(&mut self, field: TextField, text: &str) -> bool {
+        if self.prescription.text(field) == text { return false; }
+        let old = self.prescription.text(field).to_string();
+        self.execute(PrescriptionEdit::SetText { field, old, new: text.to_string() });
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Reverts the last edit and returns it, `None` when there is nothing to undo.
+    pub fn undo(&mut self) -> Option<PrescriptionEdit> {
+        let edit = self.undo_stack.pop()?;
+        edit.inverse().apply(&mut self.prescription);
+        self.redo_stack.push(edit.clone());
+        self.typing = false;
+        Some(edit)
+    }
+
+    /// Applies the last undone edit again and returns it, `None` when there is nothing to redo.
+    pub fn redo(&mut self) -> Option<PrescriptionEdit> {
+        let edit = self.redo_stack.pop()?;
+        edit.apply(&mut self.prescription);
+        self.undo_stack.push(edit.clone());
+        self.typing = false;
+        Some(edit)
+    }
+
+    fn execute(&mut self, edit: PrescriptionEdit) {
+        edit.apply(&mut self.prescription);
+        self.undo_stack.push(edit);
+        if self.undo_stack.len() > MAX_HISTORY {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+        self.typing = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(brand_name: &str) -> MedicineData {
+        MedicineData { brand_name: brand_name.into(), ..MedicineData::for_test("Generic", "", "Tablet") }
+    }
+
+    fn brands(editor: &PrescriptionEditor) -> Vec<&str> {
+        editor.prescription().items.iter().map(|item| item.brand_name.as_str()).collect()
+    }
+
+    fn editor_with(brand_names: &[&str]) -> PrescriptionEditor {
+        let mut editor = PrescriptionEditor::default();
+        for brand_name in brand_names {
+            editor.add_item(line(brand_name));
+        }
+        editor
+    }
+
+    /// Typing `text` one character at a time, the way the advice box reports it.
+    fn type_text(editor: &mut PrescriptionEditor, field: TextField, text: &str) {
+        let mut typed = editor.prescription().text(field).to_string();
+        for c in text.chars() {
+            typed.push(c);
+            editor.set_text(field, &typed);
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_insert() {
+        let mut editor = editor_with(&["Napa", "Seclo"]);
+        assert!(matches!(editor.undo(), Some(PrescriptionEdit::InsertItem { index: 1, .. })));
+        assert_eq!(brands(&editor), ["Napa"]);
+        editor.redo();
+        assert_eq!(brands(&editor), ["Napa", "Seclo"]);
+    }
+
+    #[test]
+    fn undo_and_redo_remove() {
+        let mut editor = editor_with(&["Napa", "Seclo", "Fexo"]);
+        let id = editor.prescription().items[1].id;
+        assert!(editor.remove_item(id));
+        assert!(!editor.remove_item(id));
+        assert_eq!(brands(&editor), ["Napa", "Fexo"]);
+        editor.undo();
+        assert_eq!(brands(&editor), ["Napa", "Seclo", "Fexo"]);
+        assert_eq!(editor.position(id), Some(1));
+        editor.redo();
+        assert_eq!(brands(&editor), ["Napa", "Fexo"]);
+    }
+
+    #[test]
+    fn undo_and_redo_move() {
+        let mut editor = editor_with(&["Napa", "Seclo", "Fexo"]);
+        let id = editor.prescription().items[0].id;
+        assert!(editor.move_item(id, 10));
+        assert_eq!(brands(&editor), ["Seclo", "Fexo", "Napa"]);
+        assert!(!editor.move_item(id, 2));
+        editor.undo();
+        assert_eq!(brands(&editor), ["Napa", "Seclo", "Fexo"]);
+        editor.redo();
+        assert_eq!(brands(&editor), ["Seclo", "Fexo", "Napa"]);
+    }
+
+    #[test]
+    fn undo_and_redo_replace() {
+        let mut editor = editor_with(&["Napa"]);
+        let id = editor.prescription().items[0].id;
+        let edited = MedicineData { dosing: "1+1+1".into(), ..editor.prescription().items[0].clone() };
+        assert!(editor.replace_item(id, edited.clone()));
+        assert!(!editor.replace_item(id, edited));
+        editor.undo();
+        assert_eq!(editor.prescription().items[0].dosing, "");
+        editor.redo();
+        assert_eq!(editor.prescription().items[0].dosing, "1+1+1");
+    }
+
+    #[test]
+    fn undo_and_redo_text() {
+        let mut editor = PrescriptionEditor::default();
+        assert!(editor.set_text(TextField::Diagnosis, "Fever"));
+        assert!(!editor.set_text(TextField::Diagnosis, "Fever"));
+        editor.undo();
+        assert_eq!(editor.prescription().diagnosis, "");
+        editor.redo();
+        assert_eq!(editor.prescription().diagnosis, "Fever");
+    }
+
+    #[test]
+    fn typed_words_are_undone_one_at_a_time() {
+        let mut editor = PrescriptionEditor::default();
+        type_text(&mut editor, TextField::Advice, "Drink water");
+        editor.undo();
+        assert_eq!(editor.prescription().advice, "Drink ");
+        editor.undo();
+        assert_eq!(editor.prescription().advice, "");
+        assert!(!editor.can_undo());
+    }
+
+    #[test]
+    fn typing_in_another_field_starts_a_new_edit() {
+        let mut editor = PrescriptionEditor::default();
+        type_text(&mut editor, TextField::ChiefComplaints, "Cough");
+        type_text(&mut editor, TextField::Diagnosis, "Flu");
+        editor.undo();
+        assert_eq!((editor.prescription().chief_complaints.as_str(), editor.prescription().diagnosis.as_str()), ("Cough", ""));
+    }
+
+    #[test]
+    fn replaced_text_is_not_joined_by_typing() {
+        let mut editor = PrescriptionEditor::default();
+        type_text(&mut editor, TextField::Advice, "Rest");
+        assert!(editor.replace_text(TextField::Advice, "Rest\nDrink water"));
+        type_text(&mut editor, TextField::Advice, "!");
+        editor.undo();
+        assert_eq!(editor.prescription().advice, "Rest\nDrink water");
+        editor.undo();
+        assert_eq!(editor.prescription().advice, "Rest");
+    }
+
+    #[test]
+    fn a_new_edit_clears_redo() {
+        let mut editor = editor_with(&["Napa", "Seclo"]);
+        editor.undo();
+        assert!(editor.can_redo());
+        editor.add_item(line("Fexo"));
+        assert!(!editor.can_redo());
+        assert!(editor.redo().is_none());
+        assert_eq!(brands(&editor), ["Napa", "Fexo"]);
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut editor = PrescriptionEditor::default();
+        for index in 0..MAX_HISTORY + 5 {
+            editor.add_item(line(&index.to_string()));
+        }
+        let mut undone = 0;
+        while editor.undo().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_HISTORY);
+        assert_eq!(editor.prescription().items.len(), 5);
+    }
+}
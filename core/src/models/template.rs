@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+
+use crate::error::{DbError, DbResult};
+use crate::ffi::prescription::MedicineData;
+
+/// Version written into exported template files, bumped when the layout changes incompatibly.
+const EXPORT_VERSION: i64 = 1;
+
+/// A named set of medicine lines and advice, e.g. `"Acute gastroenteritis"`, that can be applied to a
+/// new prescription instead of writing the same lines again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrescriptionTemplate {
+    /// Row id in `patients.db`, `0` until the template has been saved.
+    pub id: i64,
+    /// Unique, compared case-insensitively.
+    pub name: String,
+    pub advice: String,
+    /// Medicine lines in the order they are applied.
+    pub items: Vec<MedicineData>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl PrescriptionTemplate {
+    pub fn new(name: &str, advice: &str, items: Vec<MedicineData>) -> Self {
+        Self { name: name.trim().to_string(), advice: advice.trim().to_string(), items, ..Default::default() }
+    }
+
+    /// Serializes `templates` into the file format shared between installations. Row ids and
+    /// timestamps are left out since they only make sense in the database they came from.
+    pub fn export_json(templates: &[PrescriptionTemplate]) -> String {
+        json::object! {
+            version: EXPORT_VERSION,
+            templates: templates.iter().map(Self::to_json_value).collect::<Vec<json::JsonValue>>(),
+        }.pretty(4)
+    }
+
+    /// Reads templates written by [`export_json`](Self::export_json). Lines without a brand name are dropped,
+    /// a file naming two templates alike is rejected since only one of them could be kept.
+    pub fn import_json(text: &str) -> DbResult<Vec<PrescriptionTemplate>> {
+        let value = json::parse(text).map_err(|err| DbError::InvalidInput(format!("templates: {}", err)))?;
+        let version = value["version"].as_i64().unwrap_or(EXPORT_VERSION);
+        if version > EXPORT_VERSION {
+            return Err(DbError::InvalidInput(format!("templates: unsupported version {}", version)));
+        }
+        if !value["templates"].is_array() {
+            return Err(DbError::InvalidInput("templates: expected a \"templates\" list".to_string()));
+        }
+        let templates = value["templates"].members().map(Self::from_json_value).collect::<DbResult<Vec<Self>>>()?;
+        let mut names = HashSet::new();
+        if let Some(template) = templates.iter().find(|template| !names.insert(template.name.to_lowercase())) {
+            return Err(DbError::InvalidInput(format!("templates: \"{}\" appears more than once", template.name)));
+        }
+        Ok(templates)
+    }
+
+    fn to_json_value(&self) -> json::JsonValue {
+        let items: Vec<json::JsonValue> = self.items.iter().map(|item| json::object! {
+            brand_name: item.brand_name.as_str(),
+            generic_name: item.generic_name.as_str(),
+            strength: item.strength.as_str(),
+            formulation: item.formulation.as_str(),
+            manufacturer: item.manufacturer.as_str(),
+            dosing: item.dosing.as_str(),
+            instructions: item.instructions.as_str(),
+            duration: item.duration.as_str(),
+        }).collect();
+        json::object! {
+            name: self.name.as_str(),
+            advice: self.advice.as_str(),
+            items: items,
+        }
+    }
+
+    fn from_json_value(value: &json::JsonValue) -> DbResult<Self> {
+        let name = value["name"].as_str().unwrap_or_default().trim();
+        if name.is_empty() {
+            return Err(DbError::InvalidInput("templates: every template needs a name".to_string()));
+        }
+        let items = value["items"].members()
+            .map(|item| {
+                let text_field = |key: &str| item[key].as_str().unwrap_or_default().trim().to_string();
+                MedicineData::new(
+                    text_field("brand_name"), text_field("generic_name"), text_field("strength"),
+                    text_field("formulation"), text_field("manufacturer"), text_field("dosing"),
+                    text_field("instructions"), text_field("duration"),
+                )
+            })
+            .filter(|item| !item.brand_name.is_empty())
+            .collect();
+        Ok(Self::new(name, value["advice"].as_str().unwrap_or_default(), items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(name: &str) -> PrescriptionTemplate {
+        let item = MedicineData { dosing: "1+0+1".into(), duration: "7 days".into(), ..MedicineData::for_test("Paracetamol", "500 mg", "Tablet") };
+        PrescriptionTemplate::new(name, "Drink plenty of water", vec![item])
+    }
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let templates = vec![template("Fever"), PrescriptionTemplate::new("Empty", "", Vec::new())];
+        let imported = PrescriptionTemplate::import_json(&PrescriptionTemplate::export_json(&templates)).unwrap();
+        assert_eq!(imported.len(), 2);
+        assert_eq!((imported[0].name.as_str(), imported[0].advice.as_str()), ("Fever", "Drink plenty of water"));
+        let (item, original) = (&imported[0].items[0], &templates[0].items[0]);
+        assert_eq!(
+            (&item.brand_name, &item.generic_name, &item.strength, &item.formulation, &item.dosing, &item.duration),
+            (&original.brand_name, &original.generic_name, &original.strength, &original.formulation, &original.dosing, &original.duration)
+        );
+        assert!(imported[1].items.is_empty());
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let text = format!(r#"{{"version": {}, "templates": []}}"#, EXPORT_VERSION + 1);
+        assert!(matches!(PrescriptionTemplate::import_json(&text), Err(DbError::InvalidInput(_))));
+        assert!(PrescriptionTemplate::import_json(r#"{"templates": []}"#).unwrap().is_empty());
+    }
+
+    #[test]
+    fn templates_need_a_name() {
+        let text = r#"{"version": 1, "templates": [{"name": "  ", "items": []}]}"#;
+        assert!(matches!(PrescriptionTemplate::import_json(text), Err(DbError::InvalidInput(_))));
+        assert!(PrescriptionTemplate::import_json(r#"{"version": 1}"#).is_err());
+    }
+
+    #[test]
+    fn duplicate_names_are_rejected() {
+        let text = PrescriptionTemplate::export_json(&[template("Fever"), template("fever")]);
+        assert!(matches!(PrescriptionTemplate::import_json(&text), Err(DbError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn lines_without_a_brand_are_dropped() {
+        let text = r#"{"version": 1, "templates": [{"name": "Fever", "items": [{"brand_name": ""}, {"brand_name": "Napa"}]}]}"#;
+        let imported = PrescriptionTemplate::import_json(text).unwrap();
+        assert_eq!(imported[0].items.len(), 1);
+        assert_eq!(imported[0].items[0].brand_name, "Napa");
+    }
+}
@@ -0,0 +1,755 @@
+use std::{collections::HashSet, fs, path::{Path, PathBuf}, sync::{Mutex, MutexGuard, OnceLock}};
+use rusqlite::{params, Connection, Row};
+use rusqlite_migration::{Migrations, M};
+
+use crate::config;
+use crate::error::{DbError, DbResult};
+use crate::ffi::prescription::MedicineData;
+use crate::models::draft::Draft;
+use crate::models::patient::{Patient, Sex};
+use crate::models::prescription::Prescription;
+use crate::models::template::PrescriptionTemplate;
+
+const PATIENTS_MIGRATION_SLICE: &[M] = &[
+    M::up(include_str!("./migrations/patients_V01.sql")),
+    M::up(include_str!("./migrations/patients_V02.sql")),
+    M::up(include_str!("./migrations/patients_V03.sql")),
+    M::up(include_str!("./migrations/patients_V04.sql")),
+    M::up(include_str!("./migrations/patients_V05.sql")),
+];
+
+const PATIENTS_MIGRATIONS: Migrations = Migrations::from_slice(PATIENTS_MIGRATION_SLICE);
+
+const PATIENT_COLUMNS: &str = "id, name, date_of_birth, age_years, sex, phone, address, weight_kg, created_at, updated_at";
+
+const PRESCRIPTION_COLUMNS: &str = "id, patient_id, date, chief_complaints, diagnosis, advice, follow_up_date, created_at, updated_at";
+
+const TEMPLATE_COLUMNS: &str = "id, name, advice, created_at, updated_at";
+
+const DRAFT_COLUMNS: &str = "
+    Drafts.id, Drafts.patient_id, Drafts.prescription_id, Drafts.date, Drafts.chief_complaints,
+    Drafts.diagnosis, Drafts.advice, Drafts.follow_up_date, Drafts.saved_at, Patients.name
+";
+
+static PATIENT_DB: OnceLock<Mutex<PatientDb>> = OnceLock::new();
+
+/// Read-write store for patient records, kept apart from the read-only `drugs.db`.
+pub struct PatientDb {
+    conn: Connection,
+}
+
+impl PatientDb {
+    pub fn default_path() -> DbResult<PathBuf> {
+        Ok(config::current()?.patients_db_path.clone())
+    }
+
+    pub fn open_default() -> DbResult<Self> {
+        Self::open(&Self::default_path()?)
+    }
+
+    pub fn open(path: &Path) -> DbResult<Self> {
+        if let Some(db_dir) = path.parent() {
+            fs::create_dir_all(db_dir)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "wal")?;
+        Self::init(conn)
+    }
+
+    pub fn open_in_memory() -> DbResult<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> DbResult<Self> {
+        conn.pragma_update(None, "foreign_keys", "on")?;
+        PATIENTS_MIGRATIONS.to_latest(&mut conn)?;
+        Ok(Self { conn })
+    }
+
+    pub fn create_patient(&self, patient: &Patient) -> DbResult<i64> {
+        patient.validate()?;
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("
+            INSERT INTO Patients (name, date_of_birth, age_years, sex, phone, address, weight_kg)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        ", params![
+            patient.name.trim(), patient.date_of_birth, patient.age_years,
+            patient.sex.map(|sex| sex.as_str()), patient.phone, patient.address, patient.weight_kg
+        ])?;
+        let id = tx.last_insert_rowid();
+        Self::replace_patient_lists(&tx, id, patient)?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    pub fn update_patient(&self, patient: &Patient) -> DbResult<()> {
+        patient.validate()?;
+        let tx = self.conn.unchecked_transaction()?;
+        let updated = tx.execute("
+            UPDATE Patients SET
+                name = ?2, date_of_birth = ?3, age_years = ?4, sex = ?5,
+                phone = ?6, address = ?7, weight_kg = ?8, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?1 AND deleted_at IS NULL
+        ", params![
+            patient.id, patient.name.trim(), patient.date_of_birth, patient.age_years,
+            patient.sex.map(|sex| sex.as_str()), patient.phone, patient.address, patient.weight_kg
+        ])?;
+        if updated == 0 {
+            return Err(DbError::NotFound);
+        }
+        Self::replace_patient_lists(&tx, patient.id, patient)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_patient(&self, id: i64) -> DbResult<Patient> {
+        let mut patient = self.conn.query_row(
+            &format!("SELECT {} FROM Patients WHERE id = ?1 AND deleted_at IS NULL", PATIENT_COLUMNS),
+            [id],
+            Self::patient_from_row,
+        )?;
+        self.load_patient_lists(&mut patient)?;
+        Ok(patient)
+    }
+
+    /// Matches `query` against the name or phone number of every patient that has not been deleted.
+    pub fn find_patients(&self, query: &str) -> DbResult<Vec<Patient>> {
+        let pattern = format!("%{}%", query.trim());
+        let mut stmt = self.conn.prepare(&format!("
+            SELECT {} FROM Patients
+            WHERE deleted_at IS NULL AND (name LIKE ?1 OR phone LIKE ?1)
+            ORDER BY name
+        ", PATIENT_COLUMNS))?;
+        let mut patients = stmt
+            .query_map([pattern], Self::patient_from_row)?
+            .collect::<Result<Vec<Patient>, _>>()?;
+        for patient in patients.iter_mut() {
+            self.load_patient_lists(patient)?;
+        }
+        Ok(patients)
+    }
+
+    /// Every patient that has not been deleted with the date of their latest prescription, most recent
+    /// visits first. Patients without a prescription come last, newest records first.
+    pub fn list_patients_by_last_visit(&self) -> DbResult<Vec<(Patient, Option<String>)>> {
+        let columns = PATIENT_COLUMNS.split(", ").map(|column| format!("p.{}", column)).collect::<Vec<String>>().join(", ");
+        let mut stmt = self.conn.prepare(&format!("
+            SELECT {}, MAX(rx.date) AS last_visit
+            FROM Patients p
+            LEFT JOIN Prescriptions rx ON rx.patient_id = p.id
+            WHERE p.deleted_at IS NULL
+            GROUP BY p.id
+            ORDER BY last_visit DESC NULLS LAST, p.created_at DESC, p.id DESC
+        ", columns))?;
+        let mut patients = stmt
+            .query_map([], |row| Ok((Self::patient_from_row(row)?, row.get::<usize, Option<String>>(10)?)))?
+            .collect::<Result<Vec<(Patient, Option<String>)>, _>>()?;
+        for (patient, _) in patients.iter_mut() {
+            self.load_patient_lists(patient)?;
+        }
+        Ok(patients)
+    }
+
+    /// Hides the patient from lookups while keeping the record (and anything that refers to it) intact.
+    pub fn delete_patient(&self, id: i64) -> DbResult<()> {
+        let deleted = self.conn.execute(
+            "UPDATE Patients SET deleted_at = CURRENT_TIMESTAMP WHERE id = ?1 AND deleted_at IS NULL",
+            [id],
+        )?;
+        if deleted == 0 { Err(DbError::NotFound) } else { Ok(()) }
+    }
+
+    /// Inserts the prescription when its `id` is `0`, otherwise overwrites the stored one.
+    /// Medicine lines are always rewritten so that their stored order matches `items`.
+    pub fn save_prescription(&self, prescription: &Prescription) -> DbResult<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        let id = if prescription.id == 0 {
+            tx.execute("
+                INSERT INTO Prescriptions (patient_id, date, chief_complaints, diagnosis, advice, follow_up_date)
+                VALUES (?1, COALESCE(NULLIF(?2, ''), date('now', 'localtime')), ?3, ?4, ?5, ?6)
+            ", params![
+                prescription.patient_id, prescription.date, prescription.chief_complaints,
+                prescription.diagnosis, prescription.advice, prescription.follow_up_date
+            ])?;
+            tx.last_insert_rowid()
+        } else {
+            let updated = tx.execute("
+                UPDATE Prescriptions SET
+                    patient_id = ?2, date = COALESCE(NULLIF(?3, ''), date), chief_complaints = ?4,
+                    diagnosis = ?5, advice = ?6, follow_up_date = ?7, updated_at = CURRENT_TIMESTAMP
+                WHERE id = ?1
+            ", params![
+                prescription.id, prescription.patient_id, prescription.date, prescription.chief_complaints,
+                prescription.diagnosis, prescription.advice, prescription.follow_up_date
+            ])?;
+            if updated == 0 {
+                return Err(DbError::NotFound);
+            }
+            prescription.id
+        };
+        Self::replace_prescription_items(&tx, id, &prescription.items)?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    pub fn get_prescription(&self, id: i64) -> DbResult<Prescription> {
+        let mut prescription = self.conn.query_row(
+            &format!("SELECT {} FROM Prescriptions WHERE id = ?1", PRESCRIPTION_COLUMNS),
+            [id],
+            Self::prescription_from_row,
+        )?;
+        prescription.items = self.get_prescription_items(id)?;
+        Ok(prescription)
+    }
+
+    /// Most recent prescriptions first.
+    pub fn list_prescriptions_by_patient(&self, patient_id: i64) -> DbResult<Vec<Prescription>> {
+        let mut stmt = self.conn.prepare(&format!("
+            SELECT {} FROM Prescriptions
+            WHERE patient_id = ?1
+            ORDER BY date DESC, id DESC
+        ", PRESCRIPTION_COLUMNS))?;
+        let mut prescriptions = stmt
+            .query_map([patient_id], Self::prescription_from_row)?
+            .collect::<Result<Vec<Prescription>, _>>()?;
+        for prescription in prescriptions.iter_mut() {
+            prescription.items = self.get_prescription_items(prescription.id)?;
+        }
+        Ok(prescriptions)
+    }
+
+    /// Copies a prescription and its medicine lines into a new prescription dated today.
+    /// The follow-up date is not carried over.
+    pub fn duplicate_prescription(&self, id: i64) -> DbResult<i64> {
+        let mut prescription = self.get_prescription(id)?;
+        prescription.id = 0;
+        prescription.date = String::new();
+        prescription.follow_up_date = None;
+        self.save_prescription(&prescription)
+    }
+
+    /// Inserts the template when its `id` is `0`, replacing any template with the same name, otherwise
+    /// overwrites the stored one. Medicine lines are always rewritten so that their stored order matches `items`.
+    pub fn save_template(&self, template: &PrescriptionTemplate) -> DbResult<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        let id = Self::write_template(&tx, template)?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    pub fn get_template(&self, id: i64) -> DbResult<PrescriptionTemplate> {
+        let mut template = self.conn.query_row(
+            &format!("SELECT {} FROM Templates WHERE id = ?1", TEMPLATE_COLUMNS),
+            [id],
+            Self::template_from_row,
+        )?;
+        template.items = self.get_template_items(id)?;
+        Ok(template)
+    }
+
+    /// All templates ordered by name.
+    pub fn list_templates(&self) -> DbResult<Vec<PrescriptionTemplate>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM Templates ORDER BY name", TEMPLATE_COLUMNS
+        ))?;
+        let mut templates = stmt
+            .query_map([], Self::template_from_row)?
+            .collect::<Result<Vec<PrescriptionTemplate>, _>>()?;
+        for template in templates.iter_mut() {
+            template.items = self.get_template_items(template.id)?;
+        }
+        Ok(templates)
+    }
+
+    /// Templates whose name fuzzy matches `query`, best match first. An empty query lists every template.
+    pub fn find_templates(&self, query: &str) -> DbResult<Vec<PrescriptionTemplate>> {
+        let query = query.trim().to_lowercase();
+        let templates = self.list_templates()?;
+        if query.is_empty() {
+            return Ok(templates);
+        }
+        let mut matched: Vec<(PrescriptionTemplate, i64)> = templates.into_iter()
+            .filter_map(|template| {
+                let score = crate::ffi::utils::fuzzy_match(&template.name.to_lowercase(), &query)?;
+                Some((template, score))
+            })
+            .collect();
+        matched.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+        Ok(matched.into_iter().map(|(template, _)| template).collect())
+    }
+
+    pub fn delete_template(&self, id: i64) -> DbResult<()> {
+        let deleted = self.conn.execute("DELETE FROM Templates WHERE id = ?1", [id])?;
+        if deleted == 0 { Err(DbError::NotFound) } else { Ok(()) }
+    }
+
+    /// Saves templates read from a shared file, replacing local templates with the same name.
+    /// Either all of them are imported or none are. Returns the number of templates stored, a name
+    /// given twice is stored, and counted, once.
+    pub fn import_templates(&self, templates: &[PrescriptionTemplate]) -> DbResult<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut ids = HashSet::new();
+        for template in templates {
+            ids.insert(Self::write_template(&tx, &PrescriptionTemplate { id: 0, ..template.clone() })?);
+        }
+        tx.commit()?;
+        Ok(ids.len())
+    }
+
+    /// Inserts the draft when its `id` is `0` or no longer exists, otherwise overwrites it.
+    /// Medicine lines are always rewritten so that their stored order matches the prescription.
+    pub fn save_draft(&self, draft: &Draft) -> DbResult<i64> {
+        let prescription = &draft.prescription;
+        let tx = self.conn.unchecked_transaction()?;
+        let id: i64 = tx.query_row("
+            INSERT INTO Drafts (
+                id, patient_id, prescription_id, date, chief_complaints, diagnosis, advice, follow_up_date
+            ) VALUES (NULLIF(?1, 0), NULLIF(?2, 0), NULLIF(?3, 0), ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT (id) DO UPDATE SET
+                patient_id = excluded.patient_id, prescription_id = excluded.prescription_id,
+                date = excluded.date, chief_complaints = excluded.chief_complaints,
+                diagnosis = excluded.diagnosis, advice = excluded.advice,
+                follow_up_date = excluded.follow_up_date, saved_at = datetime('now', 'localtime')
+            RETURNING id
+        ", params![
+            draft.id, prescription.patient_id, prescription.id, prescription.date,
+            prescription.chief_complaints, prescription.diagnosis, prescription.advice, prescription.follow_up_date
+        ], |row| row.get(0))?;
+        tx.execute("DELETE FROM Draft_Items WHERE draft_id = ?1", [id])?;
+        let mut stmt = tx.prepare("
+            INSERT INTO Draft_Items (
+                draft_id, position, brand_name, generic_name, strength,
+                formulation, manufacturer, dosing, instructions, duration
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        ")?;
+        for (position, item) in prescription.items.iter().enumerate() {
+            stmt.execute(params![
+                id, position as i64, item.brand_name, item.generic_name, item.strength,
+                item.formulation, item.manufacturer, item.dosing, item.instructions, item.duration
+            ])?;
+        }
+        drop(stmt);
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Every draft with its patient's name, most recently saved first.
+    pub fn list_drafts(&self) -> DbResult<Vec<Draft>> {
+        let mut stmt = self.conn.prepare(&format!("
+            SELECT {} FROM Drafts
+            LEFT JOIN Patients ON Patients.id = Drafts.patient_id
+            ORDER BY Drafts.saved_at DESC, Drafts.id DESC
+        ", DRAFT_COLUMNS))?;
+        let mut drafts = stmt
+            .query_map([], Self::draft_from_row)?
+            .collect::<Result<Vec<Draft>, _>>()?;
+        for draft in drafts.iter_mut() {
+            draft.prescription.items = self.get_draft_items(draft.id)?;
+        }
+        Ok(drafts)
+    }
+
+    pub fn delete_draft(&self, id: i64) -> DbResult<()> {
+        let deleted = self.conn.execute("DELETE FROM Drafts WHERE id = ?1", [id])?;
+        if deleted == 0 { Err(DbError::NotFound) } else { Ok(()) }
+    }
+
+    fn get_draft_items(&self, draft_id: i64) -> DbResult<Vec<MedicineData>> {
+        let mut stmt = self.conn.prepare("
+            SELECT brand_name, generic_name, strength, formulation, manufacturer, dosing, instructions, duration
+            FROM Draft_Items
+            WHERE draft_id = ?1
+            ORDER BY position
+        ")?;
+        let items = stmt
+            .query_map([draft_id], Self::medicine_data_from_row)?
+            .collect::<Result<Vec<MedicineData>, _>>()?;
+        Ok(items)
+    }
+
+    fn write_template(conn: &Connection, template: &PrescriptionTemplate) -> DbResult<i64> {
+        let name = template.name.trim();
+        if name.is_empty() {
+            return Err(DbError::InvalidInput("template name is required".to_string()));
+        }
+        let id = if template.id == 0 {
+            conn.query_row("
+                INSERT INTO Templates (name, advice) VALUES (?1, ?2)
+                ON CONFLICT (name) DO UPDATE SET
+                    name = excluded.name, advice = excluded.advice, updated_at = CURRENT_TIMESTAMP
+                RETURNING id
+            ", params![name, template.advice], |row| row.get(0))?
+        } else {
+            let updated = conn.execute("
+                UPDATE Templates SET name = ?2, advice = ?3, updated_at = CURRENT_TIMESTAMP
+                WHERE id = ?1
+            ", params![template.id, name, template.advice])?;
+            if updated == 0 {
+                return Err(DbError::NotFound);
+            }
+            template.id
+        };
+        conn.execute("DELETE FROM Template_Items WHERE template_id = ?1", [id])?;
+        let mut stmt = conn.prepare("
+            INSERT INTO Template_Items (
+                template_id, position, brand_name, generic_name, strength,
+                formulation, manufacturer, dosing, instructions, duration
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        ")?;
+        for (position, item) in template.items.iter().enumerate() {
+            stmt.execute(params![
+                id, position as i64, item.brand_name, item.generic_name, item.strength,
+                item.formulation, item.manufacturer, item.dosing, item.instructions, item.duration
+            ])?;
+        }
+        Ok(id)
+    }
+
+    fn get_template_items(&self, template_id: i64) -> DbResult<Vec<MedicineData>> {
+        let mut stmt = self.conn.prepare("
+            SELECT brand_name, generic_name, strength, formulation, manufacturer, dosing, instructions, duration
+            FROM Template_Items
+            WHERE template_id = ?1
+            ORDER BY position
+        ")?;
+        let items = stmt
+            .query_map([template_id], Self::medicine_data_from_row)?
+            .collect::<Result<Vec<MedicineData>, _>>()?;
+        Ok(items)
+    }
+
+    fn get_prescription_items(&self, prescription_id: i64) -> DbResult<Vec<MedicineData>> {
+        let mut stmt = self.conn.prepare("
+            SELECT brand_name, generic_name, strength, formulation, manufacturer, dosing, instructions, duration
+            FROM Prescription_Items
+            WHERE prescription_id = ?1
+            ORDER BY position
+        ")?;
+        let items = stmt
+            .query_map([prescription_id], Self::medicine_data_from_row)?
+            .collect::<Result<Vec<MedicineData>, _>>()?;
+        Ok(items)
+    }
+
+    fn replace_prescription_items(conn: &Connection, prescription_id: i64, items: &[MedicineData]) -> DbResult<()> {
+        conn.execute("DELETE FROM Prescription_Items WHERE prescription_id = ?1", [prescription_id])?;
+        let mut stmt = conn.prepare("
+            INSERT INTO Prescription_Items (
+                prescription_id, position, brand_name, generic_name, strength,
+                formulation, manufacturer, dosing, instructions, duration
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        ")?;
+        for (position, item) in items.iter().enumerate() {
+            stmt.execute(params![
+                prescription_id, position as i64, item.brand_name, item.generic_name, item.strength,
+                item.formulation, item.manufacturer, item.dosing, item.instructions, item.duration
+            ])?;
+        }
+        Ok(())
+    }
+
+    fn load_patient_lists(&self, patient: &mut Patient) -> DbResult<()> {
+        patient.allergies = self.get_patient_list("SELECT allergen FROM Patient_Allergies WHERE patient_id = ?1 ORDER BY allergen", patient.id)?;
+        patient.conditions = self.get_patient_list("SELECT condition FROM Patient_Conditions WHERE patient_id = ?1 ORDER BY condition", patient.id)?;
+        Ok(())
+    }
+
+    fn get_patient_list(&self, sql: &str, patient_id: i64) -> DbResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let values = stmt
+            .query_map([patient_id], |row| row.get::<usize, String>(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(values)
+    }
+
+    fn replace_patient_lists(conn: &Connection, patient_id: i64, patient: &Patient) -> DbResult<()> {
+        conn.execute("DELETE FROM Patient_Allergies WHERE patient_id = ?1", [patient_id])?;
+        conn.execute("DELETE FROM Patient_Conditions WHERE patient_id = ?1", [patient_id])?;
+        let mut stmt = conn.prepare("INSERT OR IGNORE INTO Patient_Allergies (patient_id, allergen) VALUES (?1, ?2)")?;
+        for allergen in patient.allergies.iter().map(|a| a.trim()).filter(|a| !a.is_empty()) {
+            stmt.execute(params![patient_id, allergen])?;
+        }
+        let mut stmt = conn.prepare("INSERT OR IGNORE INTO Patient_Conditions (patient_id, condition) VALUES (?1, ?2)")?;
+        for condition in patient.conditions.iter().map(|c| c.trim()).filter(|c| !c.is_empty()) {
+            stmt.execute(params![patient_id, condition])?;
+        }
+        Ok(())
+    }
+
+    fn patient_from_row(row: &Row) -> rusqlite::Result<Patient> {
+        Ok(Patient {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            date_of_birth: row.get(2)?,
+            age_years: row.get(3)?,
+            sex: row.get::<usize, Option<String>>(4)?.as_deref().and_then(Sex::parse),
+            phone: row.get(5)?,
+            address: row.get(6)?,
+            weight_kg: row.get(7)?,
+            allergies: Vec::new(),
+            conditions: Vec::new(),
+            created_at: row.get(8)?,
+            updated_at: row.get(9)?,
+        })
+    }
+
+    fn prescription_from_row(row: &Row) -> rusqlite::Result<Prescription> {
+        Ok(Prescription {
+            id: row.get(0)?,
+            patient_id: row.get(1)?,
+            date: row.get(2)?,
+            chief_complaints: row.get(3)?,
+            diagnosis: row.get(4)?,
+            advice: row.get(5)?,
+            follow_up_date: row.get(6)?,
+            items: Vec::new(),
+            created_at: row.get(7)?,
+            updated_at: row.get(8)?,
+        })
+    }
+
+    fn template_from_row(row: &Row) -> rusqlite::Result<PrescriptionTemplate> {
+        Ok(PrescriptionTemplate {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            advice: row.get(2)?,
+            items: Vec::new(),
+            created_at: row.get(3)?,
+            updated_at: row.get(4)?,
+        })
+    }
+
+    fn draft_from_row(row: &Row) -> rusqlite::Result<Draft> {
+        let prescription = Prescription {
+            id: row.get::<usize, Option<i64>>(2)?.unwrap_or_default(),
+            patient_id: row.get::<usize, Option<i64>>(1)?.unwrap_or_default(),
+            date: row.get(3)?,
+            chief_complaints: row.get(4)?,
+            diagnosis: row.get(5)?,
+            advice: row.get(6)?,
+            follow_up_date: row.get(7)?,
+            ..Default::default()
+        };
+        Ok(Draft {
+            id: row.get(0)?,
+            prescription,
+            patient_name: row.get::<usize, Option<String>>(9)?.unwrap_or_default(),
+            saved_at: row.get(8)?,
+        })
+    }
+
+    fn medicine_data_from_row(row: &Row) -> rusqlite::Result<MedicineData> {
+        Ok(MedicineData::new(
+            row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?,
+            row.get(4)?, row.get(5)?, row.get(6)?, row.get(7)?,
+        ))
+    }
+}
+
+/// Shared store for the default `patients.db`, opened on first use.
+pub fn patient_db() -> DbResult<MutexGuard<'static, PatientDb>> {
+    let db = match PATIENT_DB.get() {
+        Some(db) => db,
+        None => {
+            let db = PatientDb::open_default()?;
+            PATIENT_DB.get_or_init(|| Mutex::new(db))
+        }
+    };
+    Ok(db.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patient(name: &str) -> Patient {
+        Patient {
+            date_of_birth: Some("1990-04-12".into()),
+            sex: Some(Sex::Female),
+            phone: Some("01711-000000".into()),
+            weight_kg: Some(58.5),
+            allergies: vec!["Penicillin".into(), " Sulfa ".into(), "".into()],
+            conditions: vec!["Asthma".into()],
+            ..Patient::new(name.into())
+        }
+    }
+
+    #[test]
+    fn create_and_get_patient() {
+        let db = PatientDb::open_in_memory().unwrap();
+        let id = db.create_patient(&patient("  Rahima Begum ")).unwrap();
+        let stored = db.get_patient(id).unwrap();
+        assert_eq!(stored.id, id);
+        assert_eq!(stored.name, "Rahima Begum");
+        assert_eq!(stored.date_of_birth.as_deref(), Some("1990-04-12"));
+        assert_eq!(stored.sex, Some(Sex::Female));
+        assert_eq!(stored.weight_kg, Some(58.5));
+        assert_eq!(stored.allergies, ["Penicillin", "Sulfa"]);
+        assert_eq!(stored.conditions, ["Asthma"]);
+        assert!(!stored.created_at.is_empty());
+    }
+
+    #[test]
+    fn invalid_patients_are_not_stored() {
+        let db = PatientDb::open_in_memory().unwrap();
+        assert!(matches!(db.create_patient(&patient(" ")), Err(DbError::InvalidInput(_))));
+        let wrong_date = Patient { date_of_birth: Some("1990-02-30".into()), ..patient("Karim") };
+        assert!(matches!(db.create_patient(&wrong_date), Err(DbError::InvalidInput(_))));
+        assert!(db.find_patients("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn update_patient_replaces_fields_and_lists() {
+        let db = PatientDb::open_in_memory().unwrap();
+        let id = db.create_patient(&patient("Rahima Begum")).unwrap();
+        let mut stored = db.get_patient(id).unwrap();
+        stored.phone = Some("01819-123456".into());
+        stored.allergies = vec!["Aspirin".into()];
+        stored.conditions = Vec::new();
+        db.update_patient(&stored).unwrap();
+
+        let updated = db.get_patient(id).unwrap();
+        assert_eq!(updated.phone.as_deref(), Some("01819-123456"));
+        assert_eq!(updated.allergies, ["Aspirin"]);
+        assert!(updated.conditions.is_empty());
+    }
+
+    #[test]
+    fn find_patients_by_name_or_phone() {
+        let db = PatientDb::open_in_memory().unwrap();
+        db.create_patient(&patient("Rahima Begum")).unwrap();
+        db.create_patient(&Patient { phone: Some("01900-555555".into()), ..patient("Abdul Karim") }).unwrap();
+        let names = |query| db.find_patients(query).unwrap().into_iter().map(|patient| patient.name).collect::<Vec<String>>();
+        assert_eq!(names("rahima"), ["Rahima Begum"]);
+        assert_eq!(names("555"), ["Abdul Karim"]);
+        assert_eq!(names(""), ["Abdul Karim", "Rahima Begum"]);
+    }
+
+    #[test]
+    fn deleted_patients_are_hidden() {
+        let db = PatientDb::open_in_memory().unwrap();
+        let id = db.create_patient(&patient("Rahima Begum")).unwrap();
+        let other_id = db.create_patient(&patient("Abdul Karim")).unwrap();
+        db.delete_patient(id).unwrap();
+
+        assert!(matches!(db.get_patient(id), Err(DbError::NotFound)));
+        assert!(db.find_patients("Rahima").unwrap().is_empty());
+        let listed: Vec<i64> = db.list_patients_by_last_visit().unwrap().into_iter().map(|(patient, _)| patient.id).collect();
+        assert_eq!(listed, [other_id]);
+    }
+
+    #[test]
+    fn missing_patients_are_not_found() {
+        let db = PatientDb::open_in_memory().unwrap();
+        assert!(matches!(db.get_patient(42), Err(DbError::NotFound)));
+        assert!(matches!(db.update_patient(&Patient { id: 42, ..patient("Nobody") }), Err(DbError::NotFound)));
+        assert!(matches!(db.delete_patient(42), Err(DbError::NotFound)));
+
+        let id = db.create_patient(&patient("Rahima Begum")).unwrap();
+        db.delete_patient(id).unwrap();
+        assert!(matches!(db.delete_patient(id), Err(DbError::NotFound)));
+        assert!(matches!(db.update_patient(&Patient { id, ..patient("Rahima Begum") }), Err(DbError::NotFound)));
+    }
+
+    fn line(brand_name: &str) -> MedicineData {
+        MedicineData::new(
+            brand_name.into(), String::new(), "500 mg".into(), "Tablet".into(),
+            String::new(), "1+0+1".into(), String::new(), "7 days".into(),
+        )
+    }
+
+    fn today(db: &PatientDb) -> String {
+        db.conn.query_row("SELECT date('now', 'localtime')", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn medicine_lines_keep_their_order() {
+        let db = PatientDb::open_in_memory().unwrap();
+        let patient_id = db.create_patient(&patient("Rahima Begum")).unwrap();
+        let mut prescription = Prescription { items: vec![line("Napa"), line("Seclo"), line("Fexo")], ..Prescription::new(patient_id) };
+        prescription.id = db.save_prescription(&prescription).unwrap();
+        let brands = |prescription: &Prescription| prescription.items.iter().map(|item| item.brand_name.clone()).collect::<Vec<String>>();
+        assert_eq!(brands(&db.get_prescription(prescription.id).unwrap()), ["Napa", "Seclo", "Fexo"]);
+
+        prescription.items.swap(0, 2);
+        prescription.items.remove(1);
+        assert_eq!(db.save_prescription(&prescription).unwrap(), prescription.id);
+        assert_eq!(brands(&db.get_prescription(prescription.id).unwrap()), ["Fexo", "Napa"]);
+    }
+
+    #[test]
+    fn new_prescriptions_are_dated_today() {
+        let db = PatientDb::open_in_memory().unwrap();
+        let patient_id = db.create_patient(&patient("Rahima Begum")).unwrap();
+        let id = db.save_prescription(&Prescription::new(patient_id)).unwrap();
+        let stored = db.get_prescription(id).unwrap();
+        assert_eq!(stored.date, today(&db));
+
+        // saving again without a date keeps the stored one
+        db.save_prescription(&Prescription { date: String::new(), advice: "Rest".into(), ..stored.clone() }).unwrap();
+        assert_eq!(db.get_prescription(id).unwrap().date, stored.date);
+        let dated = db.save_prescription(&Prescription { date: "2024-01-05".into(), ..Prescription::new(patient_id) }).unwrap();
+        assert_eq!(db.get_prescription(dated).unwrap().date, "2024-01-05");
+    }
+
+    #[test]
+    fn prescriptions_are_listed_newest_first() {
+        let db = PatientDb::open_in_memory().unwrap();
+        let patient_id = db.create_patient(&patient("Rahima Begum")).unwrap();
+        let other_id = db.create_patient(&patient("Abdul Karim")).unwrap();
+        for date in ["2024-03-01", "2024-05-20", "2024-01-15"] {
+            db.save_prescription(&Prescription { date: date.into(), items: vec![line("Napa")], ..Prescription::new(patient_id) }).unwrap();
+        }
+        db.save_prescription(&Prescription { date: "2024-06-01".into(), ..Prescription::new(other_id) }).unwrap();
+
+        let listed = db.list_prescriptions_by_patient(patient_id).unwrap();
+        let dates: Vec<&str> = listed.iter().map(|prescription| prescription.date.as_str()).collect();
+        assert_eq!(dates, ["2024-05-20", "2024-03-01", "2024-01-15"]);
+        assert!(listed.iter().all(|prescription| prescription.items.len() == 1));
+        let last_visit = db.list_patients_by_last_visit().unwrap().into_iter()
+            .find(|(patient, _)| patient.id == patient_id)
+            .and_then(|(_, last_visit)| last_visit);
+        assert_eq!(last_visit.as_deref(), Some("2024-05-20"));
+    }
+
+    #[test]
+    fn duplicate_is_dated_today_without_the_follow_up() {
+        let db = PatientDb::open_in_memory().unwrap();
+        let patient_id = db.create_patient(&patient("Rahima Begum")).unwrap();
+        let original = Prescription {
+            date: "2024-01-05".into(),
+            diagnosis: "Acute gastroenteritis".into(),
+            follow_up_date: Some("2024-01-12".into()),
+            items: vec![line("Napa"), line("Seclo")],
+            ..Prescription::new(patient_id)
+        };
+        let id = db.save_prescription(&original).unwrap();
+        let copy_id = db.duplicate_prescription(id).unwrap();
+        assert_ne!(copy_id, id);
+
+        let copy = db.get_prescription(copy_id).unwrap();
+        assert_eq!(copy.date, today(&db));
+        assert_eq!(copy.follow_up_date, None);
+        assert_eq!(copy.diagnosis, original.diagnosis);
+        assert_eq!(copy.items.iter().map(|item| item.brand_name.as_str()).collect::<Vec<&str>>(), ["Napa", "Seclo"]);
+        assert_eq!(db.get_prescription(id).unwrap().follow_up_date.as_deref(), Some("2024-01-12"));
+        assert!(matches!(db.duplicate_prescription(42), Err(DbError::NotFound)));
+    }
+
+    #[test]
+    fn imported_templates_replace_those_with_the_same_name() {
+        let db = PatientDb::open_in_memory().unwrap();
+        db.save_template(&PrescriptionTemplate::new("Fever", "Rest", vec![line("Napa")])).unwrap();
+        let imported = [
+            PrescriptionTemplate::new("fever", "", vec![line("Ace")]),
+            PrescriptionTemplate::new("Cough", "", Vec::new()),
+            PrescriptionTemplate::new("FEVER", "Drink water", vec![line("Napa Extra")]),
+        ];
+        assert_eq!(db.import_templates(&imported).unwrap(), 2);
+        let templates = db.list_templates().unwrap();
+        assert_eq!(templates.len(), 2);
+        let fever = templates.iter().find(|template| template.name.eq_ignore_ascii_case("fever")).unwrap();
+        assert_eq!(fever.advice, "Drink water");
+        assert_eq!(fever.items.iter().map(|item| item.brand_name.as_str()).collect::<Vec<&str>>(), ["Napa Extra"]);
+    }
+}
@@ -0,0 +1,894 @@
+use std::{cell::RefCell, collections::BTreeSet, env, fs, path::{Path, PathBuf}};
+
+use printpdf::{Color, Greyscale, IndirectFontRef, Line, PdfDocument, PdfDocumentReference, PdfLayerReference, Point, Pt, TextMatrix};
+
+use crate::config;
+use crate::dosing::{DoseUnit, DosingDuration, DosingSchedule};
+use crate::error::{DbError, DbResult};
+use crate::font_subset;
+use crate::ffi::prescription::MedicineData;
+use crate::letterhead::Letterhead;
+use crate::models::patient::Patient;
+use crate::models::prescription::Prescription;
+
+/// Font used for Latin text, overrides the system font search.
+pub const FONT_ENV: &str = "HADOCRX_PDF_FONT";
+/// Bold variant of [`FONT_ENV`].
+pub const BOLD_FONT_ENV: &str = "HADOCRX_PDF_BOLD_FONT";
+/// Font used for Bangla text, overrides the system font search.
+pub const BANGLA_FONT_ENV: &str = "HADOCRX_PDF_BANGLA_FONT";
+
+const REGULAR_FONT_CANDIDATES: &[&str] = &[
+    "/usr/share/fonts/truetype/noto/NotoSans-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+    "/usr/share/fonts/google-noto/NotoSans-Regular.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+];
+
+const BOLD_FONT_CANDIDATES: &[&str] = &[
+    "/usr/share/fonts/truetype/noto/NotoSans-Bold.ttf",
+    "/usr/share/fonts/noto/NotoSans-Bold.ttf",
+    "/usr/share/fonts/google-noto/NotoSans-Bold.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans-Bold.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans-Bold.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans-Bold.ttf",
+];
+
+const BANGLA_FONT_CANDIDATES: &[&str] = &[
+    "/usr/share/fonts/truetype/noto/NotoSansBengali-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSansBengali-Regular.ttf",
+    "/usr/share/fonts/google-noto/NotoSansBengali-Regular.ttf",
+    "/usr/share/fonts/truetype/noto/NotoSerifBengali-Regular.ttf",
+    "/usr/share/fonts/truetype/lohit-bengali/Lohit-Bengali.ttf",
+    "/usr/share/fonts/lohit-bengali/Lohit-Bengali.ttf",
+    "/usr/share/fonts/truetype/fonts-beng-extra/kalpurush.ttf",
+    "/usr/share/fonts/truetype/fonts-beng-extra/MuktiNarrow.ttf",
+];
+
+/// Points per millimetre.
+const PT_PER_MM: f32 = 72.0 / 25.4;
+/// Baseline distance as a multiple of the font size.
+const LINE_SPACING: f32 = 1.4;
+/// Baseline position below the top of a line as a multiple of the font size.
+const ASCENT: f32 = 0.9;
+const ITEM_INDENT: f32 = 18.0;
+const FIELD_GAP: f32 = 16.0;
+const SIGNATURE_WIDTH_MM: f32 = 55.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaperSize {
+    #[default]
+    A4,
+    A5,
+    Letter,
+}
+
+impl PaperSize {
+    pub const ALL: [PaperSize; 3] = [PaperSize::A4, PaperSize::A5, PaperSize::Letter];
+
+    /// Width and height in millimetres, portrait.
+    pub fn size_mm(&self) -> (f32, f32) {
+        match self {
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::A5 => (148.0, 210.0),
+            PaperSize::Letter => (215.9, 279.4),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaperSize::A4 => "A4",
+            PaperSize::A5 => "A5",
+            PaperSize::Letter => "Letter",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "a4" => Some(PaperSize::A4),
+            "a5" => Some(PaperSize::A5),
+            "letter" => Some(PaperSize::Letter),
+            _ => None,
+        }
+    }
+}
+
+/// Page margins in millimetres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margins {
+    pub top: f32,
+    pub bottom: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+impl Default for Margins {
+    fn default() -> Self {
+        Self { top: 15.0, bottom: 15.0, left: 15.0, right: 15.0 }
+    }
+}
+
+/// TrueType or OpenType fonts embedded in the PDF.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfFonts {
+    pub regular: PathBuf,
+    /// The regular font is used for headings when unset.
+    pub bold: Option<PathBuf>,
+    /// Needed as soon as the prescription contains Bangla text.
+    pub bangla: Option<PathBuf>,
+}
+
+impl PdfFonts {
+    /// Fonts from `HADOCRX_PDF_FONT`, `HADOCRX_PDF_BOLD_FONT` and `HADOCRX_PDF_BANGLA_FONT`,
+    /// falling back to Noto Sans, DejaVu Sans and common Bangla fonts installed on the system.
+    pub fn discover() -> DbResult<Self> {
+        let regular = env_path(FONT_ENV)
+            .or_else(|| first_existing(REGULAR_FONT_CANDIDATES))
+            .ok_or_else(|| DbError::Pdf(format!("no font found, set {}", FONT_ENV)))?;
+        Ok(Self {
+            regular,
+            bold: env_path(BOLD_FONT_ENV).or_else(|| first_existing(BOLD_FONT_CANDIDATES)),
+            bangla: env_path(BANGLA_FONT_ENV).or_else(|| first_existing(BANGLA_FONT_CANDIDATES)),
+        })
+    }
+}
+
+/// Paper and margins shared by PDF export and printing. Pre-printed pads already carry the
+/// letterhead, so it can be left out and the top margin widened to clear the printed header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageLayout {
+    pub paper: PaperSize,
+    pub margins: Margins,
+    pub print_letterhead: bool,
+}
+
+impl Default for PageLayout {
+    fn default() -> Self {
+        Self { paper: PaperSize::default(), margins: Margins::default(), print_letterhead: true }
+    }
+}
+
+impl PageLayout {
+    pub fn default_path() -> DbResult<PathBuf> {
+        Ok(config::current()?.page_layout_path.clone())
+    }
+
+    /// The saved layout, or the default one when none has been saved yet.
+    pub fn load_default() -> DbResult<Self> {
+        let path = Self::default_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load(&path)
+    }
+
+    pub fn save_default(&self) -> DbResult<()> {
+        self.save(&Self::default_path()?)
+    }
+
+    pub fn load(path: &Path) -> DbResult<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> DbResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    /// Missing or invalid fields keep their default value.
+    pub fn from_json(text: &str) -> DbResult<Self> {
+        let value = json::parse(text).map_err(|err| DbError::InvalidInput(format!("page layout: {}", err)))?;
+        let default = Self::default();
+        let margin = |key: &str, default: f32| value["margins"][key].as_f32().filter(|margin| *margin >= 0.0).unwrap_or(default);
+        Ok(Self {
+            paper: value["paper"].as_str().and_then(PaperSize::parse).unwrap_or(default.paper),
+            margins: Margins {
+                top: margin("top", default.margins.top),
+                bottom: margin("bottom", default.margins.bottom),
+                left: margin("left", default.margins.left),
+                right: margin("right", default.margins.right),
+            },
+            print_letterhead: value["print_letterhead"].as_bool().unwrap_or(default.print_letterhead),
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        json::object! {
+            paper: self.paper.as_str(),
+            margins: {
+                top: self.margins.top,
+                bottom: self.margins.bottom,
+                left: self.margins.left,
+                right: self.margins.right,
+            },
+            print_letterhead: self.print_letterhead,
+        }.pretty(4)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfOptions {
+    pub layout: PageLayout,
+    pub fonts: PdfFonts,
+}
+
+impl PdfOptions {
+    pub fn new(fonts: PdfFonts) -> Self {
+        Self { layout: PageLayout::default(), fonts }
+    }
+}
+
+/// Renders `prescription` for `patient` under the clinic `letterhead`: patient details, the Rx list
+/// with Bangla dosing instructions when a Bangla font is available, advice, follow-up date and the
+/// doctor's signature block. Text is shaped, so Bangla conjuncts print correctly, and fonts are
+/// embedded with only the glyphs used.
+pub fn render_prescription(
+    prescription: &Prescription, patient: &Patient, letterhead: &Letterhead, options: &PdfOptions
+) -> DbResult<Vec<u8>> {
+    let font_files = FontFiles::read(&options.fonts)?;
+    let mut writer = Writer::new(options, &font_files, &format!("Prescription - {}", patient.name))?;
+    if options.layout.print_letterhead {
+        writer.letterhead(letterhead)?;
+    }
+    writer.patient_details(prescription, patient)?;
+    writer.clinical_notes(prescription)?;
+    writer.medicines(&prescription.items)?;
+    writer.advice(prescription)?;
+    writer.signature(letterhead)?;
+    writer.finish()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TextStyle {
+    size: f32,
+    bold: bool,
+    /// 0 is black.
+    grey: f32,
+}
+
+const TITLE: TextStyle = TextStyle { size: 16.0, bold: true, grey: 0.0 };
+const HEADING: TextStyle = TextStyle { size: 11.5, bold: true, grey: 0.0 };
+const BODY: TextStyle = TextStyle { size: 10.5, bold: false, grey: 0.0 };
+const BODY_BOLD: TextStyle = TextStyle { size: 10.5, bold: true, grey: 0.0 };
+const SMALL: TextStyle = TextStyle { size: 9.0, bold: false, grey: 0.35 };
+const RX: TextStyle = TextStyle { size: 20.0, bold: true, grey: 0.0 };
+
+/// How a printed line is set. Shared with the GTK print path, which maps each role to a Pango font.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextRole {
+    /// The doctor's name in the letterhead.
+    Title,
+    Heading,
+    Body,
+    Bold,
+    /// Generic names and the degrees under the signature, in grey.
+    Small,
+}
+
+impl TextRole {
+    fn style(self) -> TextStyle {
+        match self {
+            TextRole::Title => TITLE,
+            TextRole::Heading => HEADING,
+            TextRole::Body => BODY,
+            TextRole::Bold => BODY_BOLD,
+            TextRole::Small => SMALL,
+        }
+    }
+}
+
+/// Font files read for one export, regular first, then the bold and Bangla fonts when configured.
+struct FontFiles {
+    /// File name for error messages and the font data.
+    files: Vec<(String, Vec<u8>)>,
+    bold: Option<usize>,
+    bangla: Option<usize>,
+}
+
+impl FontFiles {
+    fn read(fonts: &PdfFonts) -> DbResult<Self> {
+        let mut files = vec![read_font(&fonts.regular)?];
+        let mut add_font = |path: &Option<PathBuf>| -> DbResult<Option<usize>> {
+            let Some(path) = path else { return Ok(None); };
+            files.push(read_font(path)?);
+            Ok(Some(files.len() - 1))
+        };
+        let bold = add_font(&fonts.bold)?;
+        let bangla = add_font(&fonts.bangla)?;
+        Ok(Self { files, bold, bangla })
+    }
+}
+
+struct Glyph {
+    id: u16,
+    /// Offsets from the start of the run, in points.
+    x: f32,
+    y: f32,
+}
+
+/// Glyphs shaped with a single font.
+struct Run {
+    font: usize,
+    glyphs: Vec<Glyph>,
+    width: f32,
+}
+
+struct Writer<'a> {
+    options: &'a PdfOptions,
+    document: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font_files: &'a FontFiles,
+    /// Parsed once per export, in the order of `font_files`.
+    faces: Vec<rustybuzz::Face<'a>>,
+    /// Fonts are embedded when the document is finished, so that only the glyphs drawn are included.
+    font_refs: Vec<IndirectFontRef>,
+    used_glyphs: RefCell<Vec<BTreeSet<u16>>>,
+    bold: Option<usize>,
+    bangla: Option<usize>,
+    page_width: f32,
+    page_height: f32,
+    /// Top of the next line, in points from the bottom of the page.
+    y: f32,
+}
+
+impl<'a> Writer<'a> {
+    fn new(options: &'a PdfOptions, font_files: &'a FontFiles, title: &str) -> DbResult<Self> {
+        let (width_mm, height_mm) = options.layout.paper.size_mm();
+        let (document, page, layer) = PdfDocument::new(title, printpdf::Mm(width_mm), printpdf::Mm(height_mm), "Prescription");
+        let layer = document.get_page(page).get_layer(layer);
+        let faces = font_files.files
+            .iter()
+            .map(|(name, data)| {
+                rustybuzz::Face::from_slice(data, 0)
+                    .ok_or_else(|| DbError::Pdf(format!("{} is not a TrueType or OpenType font", name)))
+            })
+            .collect::<DbResult<Vec<rustybuzz::Face>>>()?;
+        // printpdf names fonts by the order they are added in, see `finish`
+        let font_refs = (0..faces.len()).map(|index| IndirectFontRef::new(format!("F{}", index))).collect();
+        Ok(Self {
+            options,
+            document,
+            layer,
+            font_files,
+            used_glyphs: RefCell::new(vec![BTreeSet::new(); faces.len()]),
+            faces,
+            font_refs,
+            bold: font_files.bold,
+            bangla: font_files.bangla,
+            page_width: width_mm * PT_PER_MM,
+            page_height: height_mm * PT_PER_MM,
+            y: (height_mm - options.layout.margins.top) * PT_PER_MM,
+        })
+    }
+
+    fn finish(self) -> DbResult<Vec<u8>> {
+        let used_glyphs = self.used_glyphs.into_inner();
+        for (((name, data), glyphs), font_ref) in self.font_files.files.iter().zip(&used_glyphs).zip(&self.font_refs) {
+            let subset = font_subset::subset(data, glyphs);
+            let reference = self.document.add_external_font(subset.as_deref().unwrap_or(data))
+                .map_err(|err| DbError::Pdf(format!("{}: {}", name, err)))?;
+            if reference != *font_ref {
+                return Err(DbError::Pdf(format!("{}: embedded as {:?} instead of {:?}", name, reference, font_ref)));
+            }
+        }
+        self.document.save_to_bytes().map_err(|err| DbError::Pdf(err.to_string()))
+    }
+
+    fn left(&self) -> f32 {
+        self.options.layout.margins.left * PT_PER_MM
+    }
+
+    fn right(&self) -> f32 {
+        self.page_width - self.options.layout.margins.right * PT_PER_MM
+    }
+
+    fn bottom(&self) -> f32 {
+        self.options.layout.margins.bottom * PT_PER_MM
+    }
+
+    fn new_page(&mut self) {
+        let (width_mm, height_mm) = self.options.layout.paper.size_mm();
+        let (page, layer) = self.document.add_page(printpdf::Mm(width_mm), printpdf::Mm(height_mm), "Prescription");
+        self.layer = self.document.get_page(page).get_layer(layer);
+        self.y = self.page_height - self.options.layout.margins.top * PT_PER_MM;
+    }
+
+    /// Starts a new page unless `height` points fit above the bottom margin.
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < self.bottom() {
+            self.new_page();
+        }
+    }
+
+    fn letterhead(&mut self, letterhead: &Letterhead) -> DbResult<()> {
+        let top = self.y;
+        let (left, right) = (self.left(), self.right());
+        let left_lines = doctor_lines(letterhead);
+        let right_lines = chamber_lines(letterhead);
+        if left_lines.is_empty() && right_lines.is_empty() {
+            return Ok(());
+        }
+
+        let mut y = top;
+        for (text, role) in &left_lines {
+            let style = role.style();
+            self.draw_text(text, left, y - style.size * ASCENT, &style)?;
+            y -= style.size * LINE_SPACING;
+        }
+        let left_bottom = y;
+        let mut y = top;
+        for (text, role) in &right_lines {
+            let style = role.style();
+            let width = self.text_width(text, &style)?;
+            self.draw_text(text, right - width, y - style.size * ASCENT, &style)?;
+            y -= style.size * LINE_SPACING;
+        }
+        self.y = left_bottom.min(y);
+        self.rule(1.0);
+        Ok(())
+    }
+
+    fn patient_details(&mut self, prescription: &Prescription, patient: &Patient) -> DbResult<()> {
+        let (left, right) = (self.left(), self.right());
+        self.ensure_space(BODY.size * LINE_SPACING * 2.0);
+        let baseline = self.y - BODY.size * ASCENT;
+        self.draw_field(left, baseline, "Name", &patient.name)?;
+        if !prescription.date.is_empty() {
+            let date = format_date(&prescription.date);
+            let width = self.text_width("Date: ", &BODY_BOLD)? + self.text_width(&date, &BODY)?;
+            self.draw_field(right - width, baseline, "Date", &date)?;
+        }
+        self.y -= BODY.size * LINE_SPACING;
+
+        let fields = patient_fields(patient);
+        if !fields.is_empty() {
+            let baseline = self.y - BODY.size * ASCENT;
+            let mut x = left;
+            for (label, value) in &fields {
+                x = self.draw_field(x, baseline, label, value)? + FIELD_GAP;
+            }
+            self.y -= BODY.size * LINE_SPACING;
+        }
+        if !patient.allergies.is_empty() {
+            let allergies = patient.allergies.join(", ");
+            self.labelled_paragraph("Allergies", &allergies)?;
+        }
+        self.rule(0.5);
+        Ok(())
+    }
+
+    fn clinical_notes(&mut self, prescription: &Prescription) -> DbResult<()> {
+        for (label, text) in clinical_notes(prescription) {
+            self.labelled_paragraph(label, text)?;
+        }
+        Ok(())
+    }
+
+    fn medicines(&mut self, items: &[MedicineData]) -> DbResult<()> {
+        self.y -= 4.0;
+        self.ensure_space(RX.size * LINE_SPACING);
+        let left = self.left();
+        self.draw_text("Rx", left, self.y - RX.size * ASCENT, &RX)?;
+        self.y -= RX.size * LINE_SPACING;
+        for (index, item) in items.iter().enumerate() {
+            // keeps the medicine name on the same page as at least its first instruction line
+            self.ensure_space(BODY.size * LINE_SPACING * 3.0);
+            for (line, (text, role)) in medicine_lines(index, item, self.bangla.is_some()).iter().enumerate() {
+                let indent = if line == 0 { ITEM_INDENT / 2.0 } else { ITEM_INDENT };
+                self.paragraph(text, indent, &role.style())?;
+            }
+            self.y -= BODY.size * 0.4;
+        }
+        Ok(())
+    }
+
+    fn advice(&mut self, prescription: &Prescription) -> DbResult<()> {
+        let advice = prescription.advice.trim();
+        if !advice.is_empty() {
+            self.y -= BODY.size * 0.6;
+            self.ensure_space(HEADING.size * LINE_SPACING + BODY.size * LINE_SPACING);
+            self.paragraph("Advice", 0.0, &HEADING)?;
+            for line in advice_lines(prescription) {
+                self.paragraph(line, ITEM_INDENT / 2.0, &BODY)?;
+            }
+        }
+        if let Some(follow_up_date) = prescription.follow_up_date.as_deref().filter(|date| !date.trim().is_empty()) {
+            self.y -= BODY.size * 0.6;
+            self.labelled_paragraph("Follow-up", &format_date(follow_up_date))?;
+        }
+        Ok(())
+    }
+
+    /// Doctor's name, degrees and registration number under a signature line,
+    /// at the bottom right of the last page.
+    fn signature(&mut self, letterhead: &Letterhead) -> DbResult<()> {
+        let lines: Vec<(String, TextStyle)> = signature_lines(letterhead).into_iter().map(|(text, role)| (text, role.style())).collect();
+        let height = 36.0 + lines.iter().map(|(_, style)| style.size * LINE_SPACING).sum::<f32>();
+        self.ensure_space(height);
+
+        let right = self.right();
+        let mut width = SIGNATURE_WIDTH_MM * PT_PER_MM;
+        for (text, style) in &lines {
+            width = width.max(self.text_width(text, style)?);
+        }
+        let left = right - width;
+        let mut y = self.bottom() + height - 32.0;
+        self.draw_line(left, y, right, y, 0.5);
+        y -= 4.0;
+        for (text, style) in &lines {
+            let text_width = self.text_width(text, style)?;
+            self.draw_text(text, left + (width - text_width) / 2.0, y - style.size * ASCENT, style)?;
+            y -= style.size * LINE_SPACING;
+        }
+        self.y = self.bottom();
+        Ok(())
+    }
+
+    /// `"Label: value"` with the label in bold, returns where the value ends.
+    fn draw_field(&mut self, x: f32, baseline: f32, label: &str, value: &str) -> DbResult<f32> {
+        let label_width = self.draw_text(&format!("{}: ", label), x, baseline, &BODY_BOLD)?;
+        let value_width = self.draw_text(value, x + label_width, baseline, &BODY)?;
+        Ok(x + label_width + value_width)
+    }
+
+    /// A bold label followed by text wrapped under it, e.g. chief complaints or the follow-up date.
+    fn labelled_paragraph(&mut self, label: &str, text: &str) -> DbResult<()> {
+        let label = format!("{}: ", label);
+        let indent = self.text_width(&label, &BODY_BOLD)?;
+        let mut first = true;
+        for line in non_empty_lines(text) {
+            let max_width = self.right() - self.left() - indent;
+            for wrapped in self.wrap(line, max_width, &BODY)? {
+                self.ensure_space(BODY.size * LINE_SPACING);
+                let baseline = self.y - BODY.size * ASCENT;
+                if first {
+                    self.draw_text(&label, self.left(), baseline, &BODY_BOLD)?;
+                    first = false;
+                }
+                self.draw_text(&wrapped, self.left() + indent, baseline, &BODY)?;
+                self.y -= BODY.size * LINE_SPACING;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws `text` wrapped to the printable width, starting `indent` points from the left margin.
+    fn paragraph(&mut self, text: &str, indent: f32, style: &TextStyle) -> DbResult<()> {
+        let max_width = self.right() - self.left() - indent;
+        for line in self.wrap(text, max_width, style)? {
+            self.ensure_space(style.size * LINE_SPACING);
+            self.draw_text(&line, self.left() + indent, self.y - style.size * ASCENT, style)?;
+            self.y -= style.size * LINE_SPACING;
+        }
+        Ok(())
+    }
+
+    fn rule(&mut self, thickness: f32) {
+        self.y -= 4.0;
+        let (left, right, y) = (self.left(), self.right(), self.y);
+        self.draw_line(left, y, right, y, thickness);
+        self.y -= 8.0;
+    }
+
+    fn draw_line(&self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32) {
+        self.layer.set_outline_color(Color::Greyscale(Greyscale::new(0.0, None)));
+        self.layer.set_outline_thickness(thickness);
+        self.layer.add_line(Line {
+            points: vec![(Point { x: Pt(x1), y: Pt(y1) }, false), (Point { x: Pt(x2), y: Pt(y2) }, false)],
+            is_closed: false,
+        });
+    }
+
+    /// Greedy word wrap; a word wider than `max_width` gets a line of its own.
+    fn wrap(&self, text: &str, max_width: f32, style: &TextStyle) -> DbResult<Vec<String>> {
+        let space_width = self.text_width(" ", style)?;
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        let mut line_width = 0.0;
+        for word in text.split_whitespace() {
+            let word_width = self.text_width(word, style)?;
+            if !line.is_empty() && line_width + space_width + word_width > max_width {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0.0;
+            }
+            if !line.is_empty() {
+                line.push(' ');
+                line_width += space_width;
+            }
+            line.push_str(word);
+            line_width += word_width;
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        Ok(lines)
+    }
+
+    fn text_width(&self, text: &str, style: &TextStyle) -> DbResult<f32> {
+        Ok(self.shape(text, style)?.iter().map(|run| run.width).sum())
+    }
+
+    /// Draws a single line of text with its baseline at `baseline`, returns its width.
+    /// Every glyph is positioned explicitly so the shaped advances and mark offsets are kept.
+    fn draw_text(&self, text: &str, x: f32, baseline: f32, style: &TextStyle) -> DbResult<f32> {
+        let runs = self.shape(text, style)?;
+        self.layer.set_fill_color(Color::Greyscale(Greyscale::new(style.grey, None)));
+        self.layer.begin_text_section();
+        let mut run_x = x;
+        let mut used_glyphs = self.used_glyphs.borrow_mut();
+        for run in &runs {
+            self.layer.set_font(&self.font_refs[run.font], style.size);
+            used_glyphs[run.font].extend(run.glyphs.iter().map(|glyph| glyph.id));
+            for glyph in &run.glyphs {
+                self.layer.set_text_matrix(TextMatrix::Translate(Pt(run_x + glyph.x), Pt(baseline + glyph.y)));
+                self.layer.write_codepoints([glyph.id]);
+            }
+            run_x += run.width;
+        }
+        self.layer.end_text_section();
+        Ok(run_x - x)
+    }
+
+    fn shape(&self, text: &str, style: &TextStyle) -> DbResult<Vec<Run>> {
+        let mut runs = Vec::new();
+        for (bangla, segment) in split_scripts(text) {
+            let font = if bangla {
+                self.bangla.ok_or_else(|| DbError::Pdf(format!("no Bangla font found, set {}", BANGLA_FONT_ENV)))?
+            } else if style.bold {
+                self.bold.unwrap_or(0)
+            } else {
+                0
+            };
+            let face = &self.faces[font];
+            let scale = style.size / face.units_per_em() as f32;
+            let mut buffer = rustybuzz::UnicodeBuffer::new();
+            buffer.push_str(&segment);
+            buffer.guess_segment_properties();
+            let output = rustybuzz::shape(face, &[], buffer);
+            let mut glyphs = Vec::with_capacity(output.len());
+            let mut advance = 0.0;
+            for (info, position) in output.glyph_infos().iter().zip(output.glyph_positions()) {
+                glyphs.push(Glyph {
+                    id: info.glyph_id as u16,
+                    x: advance + position.x_offset as f32 * scale,
+                    y: position.y_offset as f32 * scale,
+                });
+                advance += position.x_advance as f32 * scale;
+            }
+            runs.push(Run { font, glyphs, width: advance });
+        }
+        Ok(runs)
+    }
+}
+
+/// The doctor's name, degrees and registration, printed on the left of the letterhead.
+pub fn doctor_lines(letterhead: &Letterhead) -> Vec<(String, TextRole)> {
+    let mut lines = Vec::new();
+    if !letterhead.doctor_name.is_empty() {
+        lines.push((letterhead.doctor_name.clone(), TextRole::Title));
+    }
+    lines.extend(letterhead.degrees.iter().map(|degree| (degree.clone(), TextRole::Body)));
+    lines.extend(letterhead.bmdc_line().map(|line| (line, TextRole::Body)));
+    lines
+}
+
+/// The chamber's name, address, hours and phone, printed on the right of the letterhead.
+pub fn chamber_lines(letterhead: &Letterhead) -> Vec<(String, TextRole)> {
+    let mut lines = Vec::new();
+    if !letterhead.chamber_name.is_empty() {
+        lines.push((letterhead.chamber_name.clone(), TextRole::Heading));
+    }
+    lines.extend(non_empty_lines(&letterhead.chamber_address).map(|line| (line.to_string(), TextRole::Body)));
+    lines.extend(non_empty_lines(&letterhead.chamber_hours).map(|line| (line.to_string(), TextRole::Body)));
+    lines.extend(letterhead.phone.as_ref().map(|phone| (format!("Phone: {}", phone), TextRole::Body)));
+    lines
+}
+
+/// The lines under the signature line.
+pub fn signature_lines(letterhead: &Letterhead) -> Vec<(String, TextRole)> {
+    let mut lines = Vec::new();
+    if !letterhead.doctor_name.is_empty() {
+        lines.push((letterhead.doctor_name.clone(), TextRole::Bold));
+    }
+    if !letterhead.degrees.is_empty() {
+        lines.push((letterhead.degrees.join(", "), TextRole::Small));
+    }
+    lines.extend(letterhead.bmdc_line().map(|line| (line, TextRole::Small)));
+    lines
+}
+
+/// Chief complaints and diagnosis that were written, as label and text.
+pub fn clinical_notes(prescription: &Prescription) -> Vec<(&'static str, &str)> {
+    [("C/C", &prescription.chief_complaints), ("Dx", &prescription.diagnosis)]
+        .into_iter()
+        .map(|(label, text)| (label, text.trim()))
+        .filter(|(_, text)| !text.is_empty())
+        .collect()
+}
+
+/// The numbered title of the `index`th line, then its generic name, dosing and instructions when written.
+/// The title is printed half indented, the rest fully indented under it.
+pub fn medicine_lines(index: usize, item: &MedicineData, bangla: bool) -> Vec<(String, TextRole)> {
+    let mut lines = vec![(format!("{}. {}", index + 1, medicine_title(item)), TextRole::Bold)];
+    if !item.generic_name.trim().is_empty() {
+        lines.push((item.generic_name.trim().to_string(), TextRole::Small));
+    }
+    let dosing = dosing_text(item, bangla);
+    if !dosing.is_empty() {
+        lines.push((dosing, TextRole::Body));
+    }
+    if !item.instructions.trim().is_empty() {
+        lines.push((item.instructions.trim().to_string(), TextRole::Body));
+    }
+    lines
+}
+
+/// The advice, one printed line per line written.
+pub fn advice_lines(prescription: &Prescription) -> Vec<&str> {
+    non_empty_lines(&prescription.advice).collect()
+}
+
+/// Age (or date of birth), sex, weight and phone, whichever are on record, as label and value.
+pub fn patient_fields(patient: &Patient) -> Vec<(&'static str, String)> {
+    let mut fields = Vec::new();
+    match (patient.age_years, &patient.date_of_birth) {
+        (Some(age), _) => fields.push(("Age", format!("{} years", age))),
+        (None, Some(date_of_birth)) => fields.push(("Date of birth", format_date(date_of_birth))),
+        (None, None) => {}
+    }
+    if let Some(sex) = patient.sex {
+        fields.push(("Sex", capitalize(sex.as_str())));
+    }
+    if let Some(weight) = patient.weight_kg {
+        fields.push(("Weight", format!("{} kg", format_number(weight))));
+    }
+    if let Some(phone) = &patient.phone {
+        fields.push(("Phone", phone.clone()));
+    }
+    fields
+}
+
+/// `"Napa 500 mg Tablet"`.
+pub fn medicine_title(item: &MedicineData) -> String {
+    [item.brand_name.trim(), item.strength.trim(), item.formulation.trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// The dosing line as printed, in Bangla or English. Meal timing and frequency are left to
+/// the instructions, which are printed as written. Dosing or a duration the schedule cannot hold
+/// in full, a taper or `"চলবে"`, is printed as written rather than losing what it says.
+pub fn dosing_text(item: &MedicineData, bangla: bool) -> String {
+    let duration = item.duration.trim();
+    let schedule = DosingSchedule::parse_strict(&item.dosing).and_then(|mut schedule| {
+        schedule.unit = schedule.unit.or_else(|| DoseUnit::from_formulation(&item.formulation));
+        schedule.duration = if duration.is_empty() { None } else { Some(DosingDuration::parse_strict(duration)?) };
+        Some(schedule)
+    });
+    match schedule {
+        Some(schedule) if bangla => schedule.render_bangla(),
+        Some(schedule) => schedule.render_english(),
+        None => [item.dosing.trim(), duration]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join(", "),
+    }
+}
+
+/// `"2026-10-18"` as `"18/10/2026"`, anything else unchanged.
+pub fn format_date(date: &str) -> String {
+    let date = date.trim();
+    let day = date.get(..10).unwrap_or(date);
+    match day.split('-').collect::<Vec<&str>>().as_slice() {
+        [year, month, day] if year.len() == 4 => format!("{}/{}/{}", day, month, year),
+        _ => date.to_string(),
+    }
+}
+
+fn read_font(path: &Path) -> DbResult<(String, Vec<u8>)> {
+    Ok((path.display().to_string(), fs::read(path)?))
+}
+
+/// Splits `text` into Bangla and non-Bangla segments. Spaces, digits and punctuation stay
+/// with the segment they follow so a phrase like `"১ + ০ + ১, ৭ দিন"` is shaped in one piece.
+fn split_scripts(text: &str) -> Vec<(bool, String)> {
+    let mut segments: Vec<(bool, String)> = Vec::new();
+    let mut pending = String::new();
+    for c in text.chars() {
+        let bangla = match c {
+            '\u{0980}'..='\u{09FF}' | '\u{0964}' | '\u{0965}' | '\u{200C}' | '\u{200D}' => Some(true),
+            c if c.is_whitespace() || c.is_ascii_punctuation() || c.is_ascii_digit() => None,
+            _ => Some(false),
+        };
+        match (bangla, segments.last_mut()) {
+            (None, Some((_, segment))) => segment.push(c),
+            (None, None) => pending.push(c),
+            (Some(bangla), Some((last, segment))) if *last == bangla => segment.push(c),
+            (Some(bangla), _) => {
+                let mut segment = std::mem::take(&mut pending);
+                segment.push(c);
+                segments.push((bangla, segment));
+            }
+        }
+    }
+    if !pending.is_empty() {
+        segments.push((false, pending));
+    }
+    segments
+}
+
+fn non_empty_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty())
+}
+
+fn format_number(value: f64) -> String {
+    format!("{:.1}", value).trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn first_existing(candidates: &[&str]) -> Option<PathBuf> {
+    candidates.iter().map(PathBuf::from).find(|path| path.is_file())
+}
+
+fn env_path(key: &str) -> Option<PathBuf> {
+    env::var_os(key).filter(|value| !value.is_empty()).map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(dosing: &str, duration: &str) -> MedicineData {
+        MedicineData { dosing: dosing.into(), duration: duration.into(), ..MedicineData::for_test("Generic", "", "Tablet") }
+    }
+
+    #[test]
+    fn medicine_lines_leave_out_what_is_not_written() {
+        let written = MedicineData { instructions: " after meal ".into(), ..item("1+0+1", "") };
+        let lines = medicine_lines(1, &written, false);
+        assert_eq!(lines.iter().map(|(_, role)| *role).collect::<Vec<TextRole>>(), [TextRole::Bold, TextRole::Small, TextRole::Body, TextRole::Body]);
+        assert_eq!(lines[0].0, "2. Brand Tablet");
+        assert_eq!(lines[3].0, "after meal");
+
+        let bare = MedicineData { generic_name: String::new(), ..item("", "") };
+        assert_eq!(medicine_lines(0, &bare, false), [("1. Brand Tablet".to_string(), TextRole::Bold)]);
+    }
+
+    #[test]
+    fn schedules_are_rendered() {
+        assert_eq!(dosing_text(&item("1+0+1", "7 days"), false), "1 tablet in the morning and 1 tablet at night, for 7 days");
+    }
+
+    #[test]
+    fn tapers_are_printed_as_written() {
+        let item = item("1+0+1 for 3 days then 1+0+0", "");
+        assert_eq!(dosing_text(&item, false), "1+0+1 for 3 days then 1+0+0");
+        assert_eq!(dosing_text(&item, true), "1+0+1 for 3 days then 1+0+0");
+    }
+
+    #[test]
+    fn conditions_are_printed_as_written() {
+        assert_eq!(dosing_text(&item("0+0+1 if fever > 102", "3 days"), false), "0+0+1 if fever > 102, 3 days");
+    }
+
+    #[test]
+    fn open_ended_durations_are_kept() {
+        assert_eq!(dosing_text(&item("1+0+0", "চলবে"), true), "1+0+0, চলবে");
+        assert_eq!(dosing_text(&item("1+0+0", "continue"), false), "1+0+0, continue");
+    }
+}
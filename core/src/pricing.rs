@@ -0,0 +1,120 @@
+use crate::dosing::DosingSchedule;
+use crate::ffi::prescription::MedicineData;
+
+/// Dosage forms priced per piece, where one unit of the dosing shorthand is one priced unit.
+/// Everything else (syrups, creams, drops, ...) is sold in packs whose size is not on record, so
+/// those lines are left unpriced rather than guessed.
+const COUNTABLE_FORMS: &[&str] = &["tablet", "capsule", "suppository", "sachet", "lozenge", "injection", "pessary"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineCost {
+    pub medicine_id: u64,
+    pub unit_price: Option<f64>,
+    /// Number of units the patient has to buy, `None` when it cannot be worked out.
+    pub quantity: Option<f64>,
+    pub cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostEstimate {
+    pub lines: Vec<LineCost>,
+    /// Sum of every line that could be priced.
+    pub total: f64,
+    /// Lines left out of `total` because their price, dosing, duration or pack size is unknown.
+    pub unpriced_lines: usize,
+}
+
+/// Parses a `Prices.value` such as `"6.00 Tk"` or `"18.29 Tk, 20.70 Tk"` into its listed prices.
+/// `"0"` is used for drugs without a listed price and yields nothing.
+pub fn parse_prices(value: &str) -> Vec<f64> {
+    value
+        .split(',')
+        .filter_map(|price| price.trim().trim_end_matches("Tk").trim().parse::<f64>().ok())
+        .filter(|price| *price > 0.0)
+        .collect()
+}
+
+/// Lowest listed price, i.e. the price of a single unit or of the smallest pack.
+pub fn unit_price(value: &str) -> Option<f64> {
+    parse_prices(value).into_iter().reduce(f64::min)
+}
+
+pub fn is_countable_form(formulation: &str) -> bool {
+    let formulation = formulation.to_lowercase();
+    COUNTABLE_FORMS.iter().any(|form| formulation.contains(form))
+}
+
+pub fn estimate_line_cost(item: &MedicineData, unit_price: Option<f64>) -> LineCost {
+    let quantity = if is_countable_form(&item.formulation) {
+        DosingSchedule::from_medicine(item).and_then(|schedule| schedule.total_units())
+    } else {
+        None
+    };
+    LineCost {
+        medicine_id: item.id,
+        unit_price,
+        quantity,
+        cost: unit_price.zip(quantity).map(|(price, quantity)| price * quantity),
+    }
+}
+
+/// Estimates what the patient pays for `items`, using `price_of` to look up each line's unit price.
+pub fn estimate_cost(items: &[MedicineData], mut price_of: impl FnMut(&MedicineData) -> Option<f64>) -> CostEstimate {
+    let lines: Vec<LineCost> = items.iter().map(|item| estimate_line_cost(item, price_of(item))).collect();
+    CostEstimate {
+        total: lines.iter().filter_map(|line| line.cost).sum(),
+        unpriced_lines: lines.iter().filter(|line| line.cost.is_none()).count(),
+        lines,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn medicine(formulation: &str, dosing: &str, duration: &str) -> MedicineData {
+        MedicineData { dosing: dosing.into(), duration: duration.into(), ..MedicineData::for_test("Generic", "", formulation) }
+    }
+
+    #[test]
+    fn parses_listed_prices() {
+        assert_eq!(parse_prices("6.00 Tk"), [6.0]);
+        assert_eq!(parse_prices("18.29 Tk, 20.70 Tk"), [18.29, 20.70]);
+        assert!(parse_prices("0").is_empty());
+        assert_eq!(unit_price("20.70 Tk, 18.29 Tk"), Some(18.29));
+        assert_eq!(unit_price("0"), None);
+    }
+
+    #[test]
+    fn countable_forms_are_priced_per_unit_for_the_whole_course() {
+        let line = estimate_line_cost(&medicine("Tablet", "1+0+1", "7 days"), Some(2.5));
+        assert_eq!(line.quantity, Some(14.0));
+        assert_eq!(line.cost, Some(35.0));
+        let line = estimate_line_cost(&medicine("Capsule", "1+1+1", "2 weeks"), Some(1.0));
+        assert_eq!(line.quantity, Some(42.0));
+    }
+
+    #[test]
+    fn forms_sold_in_packs_are_not_priced() {
+        for (formulation, dosing, duration) in [("Suspension", "2+2+2 tsp", "30 days"), ("Syrup", "1+1+1", "3 days"), ("Cream", "1+0+1", "7 days")] {
+            let line = estimate_line_cost(&medicine(formulation, dosing, duration), Some(60.0));
+            assert_eq!(line.quantity, None);
+            assert_eq!(line.cost, None);
+        }
+    }
+
+    #[test]
+    fn lines_without_duration_or_price_are_not_priced() {
+        assert_eq!(estimate_line_cost(&medicine("Tablet", "1+0+1", ""), Some(2.0)).cost, None);
+        assert_eq!(estimate_line_cost(&medicine("Tablet", "1+0+1", "5 days"), None).cost, None);
+    }
+
+    #[test]
+    fn total_leaves_out_unpriced_lines() {
+        let items = [medicine("Tablet", "1+0+1", "5 days"), medicine("Tablet", "1+0+0", ""), medicine("Syrup", "1+1+1", "5 days")];
+        let estimate = estimate_cost(&items, |item| if item.formulation == "Syrup" { Some(45.0) } else { Some(3.0) });
+        assert_eq!(estimate.total, 30.0);
+        assert_eq!(estimate.unpriced_lines, 2);
+        assert_eq!(estimate.lines.len(), 3);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::ffi::prescription::MedicineData;
+use crate::interactions::split_generic_components;
+use crate::models::drug::DrugClass;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContraindicationWarning {
+    pub medicine_id: u64,
+    pub brand_name: String,
+    pub generic_name: String,
+    /// Contraindication as listed for the generic in `drugs.db`.
+    pub contraindication: String,
+    /// Condition as recorded for the patient.
+    pub condition: String,
+}
+
+impl ContraindicationWarning {
+    pub fn message(&self) -> String {
+        format!(
+            "{} ({}) is contraindicated in {}, patient has {}",
+            self.brand_name, self.generic_name, self.contraindication, self.condition
+        )
+    }
+}
+
+/// Compares the contraindications of `medicine`'s generic against the patient's `conditions`.
+/// Returns one warning per matching pair; an empty list means nothing was found.
+pub fn check_contraindications(
+    medicine: &MedicineData, contraindications: &[String], conditions: &[String]
+) -> Vec<ContraindicationWarning> {
+    let mut warnings = Vec::new();
+    for contraindication in contraindications {
+        for condition in conditions {
+            if terms_match(contraindication, condition) {
+                warnings.push(ContraindicationWarning {
+                    medicine_id: medicine.id,
+                    brand_name: medicine.brand_name.clone(),
+                    generic_name: medicine.generic_name.clone(),
+                    contraindication: contraindication.clone(),
+                    condition: condition.clone(),
+                });
+            }
+        }
+    }
+    warnings
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllergyWarning {
+    pub medicine_id: u64,
+    pub brand_name: String,
+    pub generic_name: String,
+    /// Component of the generic that matched, e.g. `"Amoxicillin"` for `"Amoxicillin + Clavulanic Acid"`.
+    pub component: String,
+    /// Allergy as recorded for the patient.
+    pub allergen: String,
+    /// Class through which the component matched, `None` when the allergen names the generic itself.
+    pub drug_class: Option<String>,
+    /// The component is only related to the allergen (same or cross-reactive class), so the
+    /// prescriber may still go ahead.
+    pub cross_reactive: bool,
+}
+
+impl AllergyWarning {
+    /// Whether the medicine must not be prescribed.
+    pub fn is_blocking(&self) -> bool {
+        !self.cross_reactive
+    }
+
+    pub fn message(&self) -> String {
+        let component = match &self.drug_class {
+            Some(drug_class) => format!("{} ({})", self.component, drug_class),
+            None => self.component.clone(),
+        };
+        if self.cross_reactive {
+            format!(
+                "{} ({}) contains {}, which may cross-react with the patient's {} allergy",
+                self.brand_name, self.generic_name, component, self.allergen
+            )
+        } else {
+            format!(
+                "{} ({}) contains {}, patient is allergic to {}",
+                self.brand_name, self.generic_name, component, self.allergen
+            )
+        }
+    }
+}
+
+/// Compares every component of `medicine`'s generic against the patient's `allergies`, which may
+/// name a generic (`"Amoxicillin"`) or a class (`"Penicillin"`). Returns at most one warning per
+/// component and allergy, blocking ones first.
+pub fn check_allergies(medicine: &MedicineData, allergies: &[String], classes: &[DrugClass]) -> Vec<AllergyWarning> {
+    let mut warnings = Vec::new();
+    for component in split_generic_components(&medicine.generic_name) {
+        let component_classes: Vec<&DrugClass> = classes.iter().filter(|class| class.contains(&component)).collect();
+        for allergen in allergies {
+            let Some((drug_class, cross_reactive)) = match_allergen(&component, &component_classes, allergen, classes) else { continue; };
+            warnings.push(AllergyWarning {
+                medicine_id: medicine.id,
+                brand_name: medicine.brand_name.clone(),
+                generic_name: medicine.generic_name.clone(),
+                component: component.clone(),
+                allergen: allergen.clone(),
+                drug_class,
+                cross_reactive,
+            });
+        }
+    }
+    warnings.sort_by_key(|warning| warning.cross_reactive);
+    warnings
+}
+
+/// How `component` relates to `allergen`: the class it matched through (if any) and whether it is
+/// only cross-reactive. `None` when they are unrelated.
+fn match_allergen(
+    component: &str, component_classes: &[&DrugClass], allergen: &str, classes: &[DrugClass]
+) -> Option<(Option<String>, bool)> {
+    if terms_match(component, allergen) {
+        return Some((None, false));
+    }
+    if let Some(class) = component_classes.iter().find(|class| class.is_named(allergen)) {
+        return Some((Some(class.name.clone()), false));
+    }
+    // classes the allergen belongs to, either by name or through one of their members
+    let allergen_classes: Vec<&DrugClass> = classes
+        .iter()
+        .filter(|class| class.is_named(allergen) || class.contains(allergen))
+        .collect();
+    component_classes
+        .iter()
+        .find(|class| {
+            allergen_classes.iter().any(|allergen_class| {
+                allergen_class.name == class.name || allergen_class.cross_reactive_classes.contains(&class.name)
+            })
+        })
+        .map(|class| (Some(class.name.clone()), true))
+}
+
+/// Loose comparison of clinical terms: case, punctuation and plurals are ignored and a term
+/// matches when all of its words appear in the other one, so `"liver disease"` matches `"Liver Diseases"`
+/// and `"diabetes"` matches `"Diabetes Mellitus"`.
+pub fn terms_match(a: &str, b: &str) -> bool {
+    let a_words = normalized_words(a);
+    let b_words = normalized_words(b);
+    if a_words.is_empty() || b_words.is_empty() {
+        return false;
+    }
+    a_words.iter().all(|word| b_words.contains(word)) || b_words.iter().all(|word| a_words.contains(word))
+}
+
+fn normalized_words(term: &str) -> Vec<String> {
+    term.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let word = word.to_lowercase();
+            match word.strip_suffix('s') {
+                Some(stem) if stem.chars().count() > 2 && !stem.ends_with('s') => stem.to_string(),
+                _ => word,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn medicine(generic_name: &str) -> MedicineData {
+        MedicineData::for_test(generic_name, "", "Tablet")
+    }
+
+    fn class(name: &str, aliases: &[&str], members: &[&str], cross_reactive_classes: &[&str]) -> DrugClass {
+        let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+        DrugClass {
+            name: name.into(),
+            aliases: strings(aliases),
+            members: strings(members),
+            cross_reactive_classes: strings(cross_reactive_classes),
+        }
+    }
+
+    fn classes() -> Vec<DrugClass> {
+        vec![
+            class("Penicillins", &["Penicillin"], &["Amoxicillin", "Flucloxacillin"], &["Cephalosporins"]),
+            class("Cephalosporins", &[], &["Cefuroxime", "Ceftriaxone"], &["Penicillins"]),
+            class("Sulfonamides", &["Sulfa"], &["Sulfamethoxazole"], &[]),
+        ]
+    }
+
+    #[test]
+    fn terms_match_ignores_case_plurals_and_extra_words() {
+        assert!(terms_match("liver disease", "Liver Diseases"));
+        assert!(terms_match("Diabetes", "diabetes mellitus"));
+        assert!(!terms_match("kidney disease", "liver disease"));
+        assert!(!terms_match("", "asthma"));
+    }
+
+    #[test]
+    fn allergy_to_the_generic_blocks() {
+        let warnings = check_allergies(&medicine("Amoxicillin Trihydrate"), &["amoxicillin".into()], &classes());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].is_blocking());
+        assert_eq!(warnings[0].drug_class, None);
+    }
+
+    #[test]
+    fn allergy_to_the_class_blocks_each_matching_component() {
+        let warnings = check_allergies(&medicine("Amoxicillin + Clavulanic Acid"), &["Penicillin".into()], &classes());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].component, "Amoxicillin");
+        assert_eq!(warnings[0].drug_class.as_deref(), Some("Penicillins"));
+        assert!(warnings[0].is_blocking());
+    }
+
+    #[test]
+    fn allergy_to_a_related_class_only_warns() {
+        let warnings = check_allergies(&medicine("Cefuroxime Axetil"), &["Amoxicillin".into()], &classes());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].cross_reactive);
+        assert!(!warnings[0].is_blocking());
+        assert_eq!(warnings[0].drug_class.as_deref(), Some("Cephalosporins"));
+    }
+
+    #[test]
+    fn unrelated_allergy_is_ignored() {
+        assert!(check_allergies(&medicine("Paracetamol"), &["Sulfa".into(), "Penicillin".into()], &classes()).is_empty());
+    }
+
+    #[test]
+    fn blocking_allergies_come_first() {
+        let warnings = check_allergies(
+            &medicine("Cefuroxime + Sulfamethoxazole"), &["Penicillin".into(), "Sulfa".into()], &classes()
+        );
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].is_blocking());
+        assert!(warnings[1].cross_reactive);
+    }
+
+    #[test]
+    fn contraindication_matches_patient_condition() {
+        let warnings = check_contraindications(
+            &medicine("Ibuprofen"), &["Peptic ulcer".into(), "Renal impairment".into()], &["peptic ulcers".into()]
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].contraindication, "Peptic ulcer");
+        assert!(check_contraindications(&medicine("Ibuprofen"), &["Asthma".into()], &[]).is_empty());
+    }
+}
@@ -0,0 +1,299 @@
+use std::cmp::Ordering;
+
+use crate::utils::normalize_digits;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StrengthUnit {
+    Microgram,
+    Milligram,
+    Gram,
+    Millilitre,
+    InternationalUnit,
+    Percent,
+    /// Anything else, lowercased, e.g. `"vial"`, `"puff"`, `"metered inhalation"` or `"size"`.
+    Other(String),
+}
+
+impl StrengthUnit {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase();
+        let unit = match value.as_str() {
+            "" => return None,
+            "mcg" | "µg" | "ug" => StrengthUnit::Microgram,
+            "mg" => StrengthUnit::Milligram,
+            "g" | "gm" | "gram" => StrengthUnit::Gram,
+            "ml" => StrengthUnit::Millilitre,
+            "iu" | "unit" | "units" | "miu" | "lac iu" | "lac unit" => StrengthUnit::InternationalUnit,
+            "%" => StrengthUnit::Percent,
+            other => StrengthUnit::Other(other.to_string()),
+        };
+        Some(unit)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            StrengthUnit::Microgram => "mcg",
+            StrengthUnit::Milligram => "mg",
+            StrengthUnit::Gram => "gm",
+            StrengthUnit::Millilitre => "ml",
+            StrengthUnit::InternationalUnit => "IU",
+            StrengthUnit::Percent => "%",
+            StrengthUnit::Other(unit) => unit,
+        }
+    }
+
+    /// Units that can be converted into one another: mcg, mg and g are all masses.
+    pub fn is_compatible(&self, other: &StrengthUnit) -> bool {
+        self.normalized() == other.normalized()
+    }
+
+    /// The unit amounts are normalized to: mg for masses, IU and ml stay as they are.
+    pub fn normalized(&self) -> StrengthUnit {
+        match self {
+            StrengthUnit::Microgram | StrengthUnit::Gram => StrengthUnit::Milligram,
+            unit => unit.clone(),
+        }
+    }
+
+    fn factor(&self) -> f64 {
+        match self {
+            StrengthUnit::Microgram => 0.001,
+            StrengthUnit::Gram => 1000.0,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    pub amount: f64,
+    pub unit: StrengthUnit,
+}
+
+impl Quantity {
+    /// The same quantity in mg (for masses), IU or ml; see [`StrengthUnit::normalized`].
+    pub fn normalized(&self) -> Quantity {
+        Quantity { amount: self.amount * self.unit.factor(), unit: self.unit.normalized() }
+    }
+
+    /// Amount in `unit`, `None` when the units cannot be converted.
+    pub fn amount_in(&self, unit: &StrengthUnit) -> Option<f64> {
+        if !self.unit.is_compatible(unit) {
+            return None;
+        }
+        Some(self.amount * self.unit.factor() / unit.factor())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrengthComponent {
+    pub quantity: Quantity,
+    /// What the amount is contained in, e.g. `5 ml` for `"125 mg/5 ml"` or `1 vial` for `"500 mg/vial"`.
+    pub per: Option<Quantity>,
+}
+
+impl StrengthComponent {
+    /// Amount per ml, or per g for creams and ointments, in normalized units.
+    /// `None` without a measurable denominator, e.g. for `"500 mg/vial"`.
+    pub fn concentration(&self) -> Option<f64> {
+        let per = self.per.as_ref()?;
+        let per_amount = per.amount_in(&StrengthUnit::Millilitre).or_else(|| per.amount_in(&StrengthUnit::Gram))?;
+        if per_amount <= 0.0 {
+            return None;
+        }
+        Some(self.quantity.normalized().amount / per_amount)
+    }
+
+    /// Same amount once units are normalized; dissolved amounts are compared by concentration,
+    /// so `"125 mg/5 ml"` is equivalent to `"25 mg/ml"`.
+    pub fn is_equivalent(&self, other: &StrengthComponent) -> bool {
+        if !self.quantity.unit.is_compatible(&other.quantity.unit) {
+            return false;
+        }
+        match (self.concentration(), other.concentration()) {
+            (Some(a), Some(b)) => approx_eq(a, b),
+            (None, None) => {
+                let same_per = match (&self.per, &other.per) {
+                    (Some(a), Some(b)) => a.unit == b.unit && approx_eq(a.amount, b.amount),
+                    (a, b) => a.is_none() && b.is_none(),
+                };
+                same_per && approx_eq(self.quantity.normalized().amount, other.quantity.normalized().amount)
+            }
+            _ => false,
+        }
+    }
+
+    /// Amount in mg, `None` unless the component is a mass.
+    pub fn mg(&self) -> Option<f64> {
+        self.quantity.amount_in(&StrengthUnit::Milligram)
+    }
+
+    /// Volume in ml the amount is dissolved in, `None` unless the denominator is a volume.
+    pub fn per_ml(&self) -> Option<f64> {
+        self.per.as_ref()?.amount_in(&StrengthUnit::Millilitre)
+    }
+
+    /// Plain amounts first, then solutions by concentration, then amounts per vial, puff...;
+    /// within each group masses, IU, ml, percentages and other units.
+    fn sort_key(&self) -> (u8, u8, f64) {
+        let normalized = self.quantity.normalized();
+        let unit_rank = match normalized.unit {
+            StrengthUnit::Milligram => 0,
+            StrengthUnit::InternationalUnit => 1,
+            StrengthUnit::Millilitre => 2,
+            StrengthUnit::Percent => 3,
+            _ => 4,
+        };
+        match (&self.per, self.concentration()) {
+            (None, _) => (0, unit_rank, normalized.amount),
+            (Some(_), Some(concentration)) => (1, unit_rank, concentration),
+            (Some(_), None) => (2, unit_rank, normalized.amount),
+        }
+    }
+}
+
+/// Structured form of a `Strengths.value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strength {
+    pub components: Vec<StrengthComponent>,
+}
+
+impl Strength {
+    /// Parses strengths such as `"500 mg"`, `"500 mg + 200 IU"`, `"20 mg/5 ml"`, `".5 mg/ml"` or
+    /// `"400 mg + 57 mg/5 ml"`. A trailing denominator applies to every component, as in the database.
+    pub fn parse(value: &str) -> Option<Self> {
+        let normalized = normalize_digits(value);
+        let (amounts, per) = match normalized.rsplit_once('/') {
+            Some((amounts, per)) => (amounts, Some(parse_denominator(per)?)),
+            None => (normalized.as_str(), None),
+        };
+        let components = amounts
+            .split('+')
+            .map(|amount| parse_quantity(amount).map(|quantity| StrengthComponent { quantity, per: per.clone() }))
+            .collect::<Option<Vec<StrengthComponent>>>()?;
+        Some(Self { components })
+    }
+
+    /// Whether both strengths have the same components in the same amounts once units are normalized,
+    /// so `"1 gm"` is equivalent to `"1000 mg"`.
+    pub fn is_equivalent(&self, other: &Strength) -> bool {
+        self.components.len() == other.components.len()
+            && self.components.iter().zip(&other.components).all(|(a, b)| a.is_equivalent(b))
+    }
+
+    /// Orders by the amount of each component in turn (by concentration when dissolved), so `"5 mg"`
+    /// comes before `"10 mg"` and `"125 mg/5 ml"` before `"250 mg/5 ml"`, tablets before syrups.
+    pub fn compare(&self, other: &Strength) -> Ordering {
+        for (a, b) in self.components.iter().zip(&other.components) {
+            let (a_group, a_unit, a_value) = a.sort_key();
+            let (b_group, b_unit, b_value) = b.sort_key();
+            let ordering = a_group.cmp(&b_group).then(a_unit.cmp(&b_unit)).then(a_value.total_cmp(&b_value));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        self.components.len().cmp(&other.components.len())
+    }
+}
+
+/// Orders strength strings numerically, strings that do not parse go last in text order.
+pub fn compare_strengths(a: &str, b: &str) -> Ordering {
+    match (Strength::parse(a), Strength::parse(b)) {
+        (Some(a_strength), Some(b_strength)) => a_strength.compare(&b_strength).then_with(|| a.cmp(b)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+pub fn sort_strengths(strengths: &mut [String]) {
+    strengths.sort_by(|a, b| compare_strengths(a, b));
+}
+
+/// `"500 mg"`, `".5 gm"`, `"5 Lac IU"`, `"1 MIU"`.
+fn parse_quantity(value: &str) -> Option<Quantity> {
+    let value = value.trim();
+    let number_end = value.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(value.len());
+    let amount = value[..number_end].parse::<f64>().ok()?;
+    let unit_text = value[number_end..].trim().to_lowercase();
+    let amount = match unit_text.as_str() {
+        "miu" => amount * 1_000_000.0,
+        "lac iu" | "lac unit" => amount * 100_000.0,
+        _ => amount,
+    };
+    Some(Quantity { amount, unit: StrengthUnit::parse(&unit_text)? })
+}
+
+/// `"5 ml"`, `"ml"`, `"vial"`, `"Metered Inhalation"`; a missing amount means one.
+fn parse_denominator(value: &str) -> Option<Quantity> {
+    let value = value.trim();
+    if value.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        parse_quantity(value)
+    } else {
+        Some(Quantity { amount: 1.0, unit: StrengthUnit::parse(value)? })
+    }
+}
+
+fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_amounts() {
+        let strength = Strength::parse("500 mg").unwrap();
+        assert_eq!(strength.components.len(), 1);
+        assert_eq!(strength.components[0].mg(), Some(500.0));
+        assert_eq!(strength.components[0].per, None);
+        assert_eq!(Strength::parse(".5 gm").unwrap().components[0].mg(), Some(500.0));
+        assert_eq!(Strength::parse("২৫০ mg").unwrap().components[0].mg(), Some(250.0));
+        assert!(Strength::parse("mg").is_none());
+    }
+
+    #[test]
+    fn parses_amounts_per_volume() {
+        let strength = Strength::parse("125 mg/5 ml").unwrap();
+        let component = &strength.components[0];
+        assert_eq!(component.mg(), Some(125.0));
+        assert_eq!(component.per_ml(), Some(5.0));
+        assert_eq!(component.concentration(), Some(25.0));
+
+        let per_vial = &Strength::parse("500 mg/vial").unwrap().components[0];
+        assert_eq!(per_vial.per_ml(), None);
+        assert_eq!(per_vial.concentration(), None);
+    }
+
+    #[test]
+    fn trailing_denominator_applies_to_every_component() {
+        let strength = Strength::parse("400 mg + 57 mg/5 ml").unwrap();
+        assert_eq!(strength.components.len(), 2);
+        assert!(strength.components.iter().all(|component| component.per_ml() == Some(5.0)));
+    }
+
+    #[test]
+    fn converts_international_units() {
+        let strength = Strength::parse("5 Lac IU").unwrap();
+        assert_eq!(strength.components[0].quantity.amount, 500_000.0);
+        assert_eq!(strength.components[0].quantity.unit, StrengthUnit::InternationalUnit);
+    }
+
+    #[test]
+    fn equivalent_strengths() {
+        let parse = |value| Strength::parse(value).unwrap();
+        assert!(parse("1 gm").is_equivalent(&parse("1000 mg")));
+        assert!(parse("125 mg/5 ml").is_equivalent(&parse("25 mg/ml")));
+        assert!(!parse("125 mg/5 ml").is_equivalent(&parse("125 mg")));
+        assert!(!parse("500 mg").is_equivalent(&parse("500 IU")));
+    }
+
+    #[test]
+    fn sorts_numerically() {
+        let mut strengths: Vec<String> = ["250 mg/5 ml", "10 mg", "abc", "5 mg", "125 mg/5 ml"]
+            .iter().map(|value| value.to_string()).collect();
+        sort_strengths(&mut strengths);
+        assert_eq!(strengths, ["5 mg", "10 mg", "125 mg/5 ml", "250 mg/5 ml", "abc"]);
+    }
+}
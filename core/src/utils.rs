@@ -17,3 +17,23 @@ pub extern "C" fn c_fuzzy_match(choice: *const c_char, pattern: *const c_char) -
         score.unwrap_or_else(|| -1)
     }
 }
+
+/// Replaces Bangla digits with ASCII ones, so `"১+০+১"` parses like `"1+0+1"`.
+pub fn normalize_digits(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '০'..='৯' => char::from(b'0' + (c as u32 - '০' as u32) as u8),
+            c => c,
+        })
+        .collect()
+}
+
+/// Replaces ASCII digits with Bangla ones.
+pub fn bangla_digits(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '0'..='9' => char::from_u32('০' as u32 + (c as u32 - '0' as u32)).unwrap_or(c),
+            c => c,
+        })
+        .collect()
+}